@@ -0,0 +1,127 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use symbolic_debuginfo::breakpad::BreakpadObject;
+
+/// The number of functions in the generated symbol file, which results in roughly 50MB of data.
+const FUNCTIONS: u64 = 200_000;
+
+/// The number of random addresses looked up per iteration.
+const LOOKUPS: usize = 10_000;
+
+/// Generates a symbol file with the given number of functions, each with a few line records and
+/// CFI records.
+fn synthetic_symbols(functions: u64) -> Vec<u8> {
+    let mut data =
+        String::from("MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 synthetic\n");
+    for file in 0..100 {
+        data.push_str(&format!("FILE {} src/file_{}.cpp\n", file, file));
+    }
+
+    for function in 0..functions {
+        let address = 0x1000 + function * 0x100;
+        data.push_str(&format!(
+            "FUNC {:x} 100 0 namespace::function_{}(int, char const*)\n",
+            address, function
+        ));
+        for line in 0..8 {
+            data.push_str(&format!(
+                "{:x} 20 {} {}\n",
+                address + line * 0x20,
+                function * 10 + line,
+                function % 100
+            ));
+        }
+    }
+
+    for function in 0..functions {
+        let address = 0x1000 + function * 0x100;
+        data.push_str(&format!(
+            "STACK CFI INIT {:x} 100 .cfa: $rsp 8 + .ra: .cfa -8 + ^\n",
+            address
+        ));
+        data.push_str(&format!(
+            "STACK CFI {:x} .cfa: $rsp 16 + $rbp: .cfa -16 + ^\n",
+            address + 1
+        ));
+    }
+
+    data.into_bytes()
+}
+
+/// Generates pseudo-random addresses within the functions of [`synthetic_symbols`].
+fn random_addresses(functions: u64, count: usize) -> Vec<u64> {
+    // A fixed xorshift generator keeps the addresses identical across runs.
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..count)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            0x1000 + state % (functions * 0x100)
+        })
+        .collect()
+}
+
+pub fn breakpad(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Breakpad object benchmarks");
+    group.sample_size(10);
+
+    let data = synthetic_symbols(FUNCTIONS);
+    let object = BreakpadObject::parse(&data).unwrap();
+    let size = data.len();
+
+    group.bench_with_input(BenchmarkId::new("parse", size), &data, |b, data| {
+        b.iter(|| BreakpadObject::parse(data).unwrap())
+    });
+
+    group.bench_with_input(
+        BenchmarkId::new("func records", size),
+        &object,
+        |b, object| {
+            b.iter(|| {
+                for record in object.func_records() {
+                    record.unwrap();
+                }
+            })
+        },
+    );
+
+    group.bench_with_input(
+        BenchmarkId::new("stack records", size),
+        &object,
+        |b, object| {
+            b.iter(|| {
+                for record in object.stack_records() {
+                    record.unwrap();
+                }
+            })
+        },
+    );
+
+    group.bench_with_input(BenchmarkId::new("file map", size), &object, |b, object| {
+        b.iter(|| object.file_map())
+    });
+
+    group.bench_with_input(BenchmarkId::new("index", size), &object, |b, object| {
+        b.iter(|| object.index())
+    });
+
+    let index = object.index();
+    let addresses = random_addresses(FUNCTIONS, LOOKUPS);
+    group.bench_with_input(
+        BenchmarkId::new("function lookups", LOOKUPS),
+        &addresses,
+        |b, addresses| {
+            b.iter(|| {
+                for &address in addresses {
+                    index.function_at(address).unwrap();
+                }
+            })
+        },
+    );
+
+    group.finish();
+}
+
+criterion_group!(benches, breakpad);
+criterion_main!(benches);
@@ -0,0 +1,56 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use symbolic_debuginfo::breakpad::BreakpadObject;
+
+/// Generates a symbol file with the given number of functions, each with a few line records.
+fn synthetic_symbols(functions: u64) -> Vec<u8> {
+    let mut data =
+        String::from("MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 synthetic\n");
+    for file in 0..100 {
+        data.push_str(&format!("FILE {} src/file_{}.cpp\n", file, file));
+    }
+
+    for function in 0..functions {
+        let address = 0x1000 + function * 0x100;
+        data.push_str(&format!(
+            "FUNC {:x} 100 0 namespace::function_{}(int, char const*)\n",
+            address, function
+        ));
+        for line in 0..8 {
+            data.push_str(&format!(
+                "{:x} 20 {} {}\n",
+                address + line * 0x20,
+                function * 10 + line,
+                function % 100
+            ));
+        }
+    }
+
+    data.into_bytes()
+}
+
+pub fn breakpad_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Breakpad parallel function parsing");
+    group.sample_size(10);
+
+    let data = synthetic_symbols(200_000);
+    let object = BreakpadObject::parse(&data).unwrap();
+    let session = object.debug_session().unwrap();
+
+    group.bench_with_input(
+        BenchmarkId::new("sequential", data.len()),
+        &session,
+        |b, session| b.iter(|| session.functions().collect::<Result<Vec<_>, _>>().unwrap()),
+    );
+
+    group.bench_with_input(
+        BenchmarkId::new("parallel", data.len()),
+        &session,
+        |b, session| b.iter(|| session.functions().par_collect().unwrap()),
+    );
+
+    group.finish();
+}
+
+criterion_group!(benches, breakpad_parallel);
+criterion_main!(benches);
@@ -199,6 +199,21 @@ impl FromStr for FileFormat {
     }
 }
 
+/// A section header of an object file.
+///
+/// Returned by [`Object::section_headers`](enum.Object.html#method.section_headers).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SectionHeader<'data> {
+    /// The name of the section, such as `.text` or `__text`.
+    pub name: &'data str,
+    /// The virtual address of the section when loaded into memory.
+    pub virtual_addr: u64,
+    /// The offset of the section's contents in the file.
+    pub file_offset: usize,
+    /// The size of the section in bytes.
+    pub size: usize,
+}
+
 /// A symbol from a symbol table.
 #[derive(Clone, Default, Eq, PartialEq)]
 pub struct Symbol<'data> {
@@ -1,19 +1,41 @@
 //! Support for Breakpad ASCII symbols, used by the Breakpad and Crashpad libraries.
 
 use std::borrow::Cow;
-use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
+use std::io::{self, Write};
 use std::ops::Range;
+#[cfg(feature = "async")]
+use std::pin::Pin;
 use std::str;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
 
+use once_cell::sync::OnceCell;
 use thiserror::Error;
 
-use symbolic_common::{Arch, AsSelf, CodeId, DebugId, Language, Name, NameMangling};
+use symbolic_common::{
+    Arch, AsSelf, CodeId, CpuFamily, DebugId, Language, Name, NameMangling, SelfCell,
+};
 
 use crate::base::*;
 use crate::shared::Parse;
 
+mod compact;
+mod index;
+#[cfg(feature = "json")]
+mod json;
+mod writer;
+
+pub use compact::{
+    CompactBreakpadFunc, CompactBreakpadObject, CompactBreakpadRecords, CompactBreakpadStackCfi,
+};
+pub use writer::BreakpadStreamWriter;
+
 #[derive(Clone, Debug)]
 struct LineOffsets<'data> {
     data: &'data [u8],
@@ -24,10 +46,12 @@ struct LineOffsets<'data> {
 impl<'data> LineOffsets<'data> {
     #[inline]
     fn new(data: &'data [u8]) -> Self {
+        // Offsets remain relative to the original data, including a leading BOM.
+        let stripped = strip_bom(data);
         Self {
-            data,
+            data: stripped,
             finished: false,
-            index: 0,
+            index: data.len() - stripped.len(),
         }
     }
 }
@@ -97,6 +121,42 @@ impl<'data> Lines<'data> {
     pub fn new(data: &'data [u8]) -> Self {
         Self(LineOffsets::new(data))
     }
+
+    /// Creates lines starting at the given byte offset into the data.
+    ///
+    /// Offsets of the returned lines remain relative to the start of `data`. An offset past the
+    /// end of the data yields no lines.
+    #[inline]
+    fn from_offset(data: &'data [u8], offset: usize) -> Self {
+        if offset == 0 {
+            return Self::new(data);
+        }
+
+        let offset = offset.min(data.len());
+        Self(LineOffsets {
+            data: &data[offset..],
+            finished: false,
+            index: offset,
+        })
+    }
+
+    /// Returns the byte offset of the next line to be returned.
+    ///
+    /// Once the iterator is exhausted, this is the length of the data.
+    #[inline]
+    pub fn position(&self) -> usize {
+        if self.0.finished {
+            self.0.index + self.0.data.len()
+        } else {
+            self.0.index
+        }
+    }
+
+    /// Returns the next line along with its byte offset.
+    #[inline]
+    fn next_with_offset(&mut self) -> Option<(usize, &'data [u8])> {
+        self.0.next()
+    }
 }
 
 impl<'data> Iterator for Lines<'data> {
@@ -116,12 +176,131 @@ impl std::iter::FusedIterator for Lines<'_> {}
 /// Length at which the breakpad header will be capped.
 ///
 /// This is a protection against reading an entire breakpad file at once if the first characters do
-/// not contain a valid line break.
-const BREAKPAD_HEADER_CAP: usize = 320;
+/// not contain a valid line break. It is large enough for module names with very long paths.
+const BREAKPAD_HEADER_CAP: usize = 64 * 1024;
+
+/// The UTF-8 byte order mark, which some Windows tools prepend to text files.
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// Strips a leading UTF-8 byte order mark from the given data.
+#[inline]
+fn strip_bom(data: &[u8]) -> &[u8] {
+    data.strip_prefix(UTF8_BOM).unwrap_or(data)
+}
 
 /// Placeholder used for missing function or symbol names.
 const UNKNOWN_NAME: &str = "<unknown>";
 
+/// Returns true if the name only consists of characters that can appear in a mangled name.
+///
+/// Demangled names contain whitespace, parentheses or `::` separators, none of which are used by
+/// any of the supported mangling schemes.
+fn is_mangled_charset(name: &str) -> bool {
+    name.bytes()
+        .all(|b| b.is_ascii_graphic() && !matches!(b, b'(' | b')' | b':' | b','))
+}
+
+/// Returns true if the Itanium mangled name uses the legacy Rust scheme.
+///
+/// Legacy Rust symbols are Itanium names that end in a path segment with a 16 digit hash, such as
+/// `17h0123456789abcdefE`.
+fn is_legacy_rust(name: &str) -> bool {
+    let hash = name
+        .strip_suffix('E')
+        .and_then(|name| name.get(name.len().saturating_sub(19)..))
+        .and_then(|hash| hash.strip_prefix("17h"));
+    matches!(hash, Some(hash) if hash.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// Detects whether a Breakpad record name is mangled and which language it was mangled for.
+///
+/// Breakpad names are supposed to be demangled, but real-world symbol files often contain raw
+/// mangled names. This recognizes Itanium (`_Z`), Rust (`_R` and legacy `_ZN…17h…E`), MSVC (`?`)
+/// and Swift (`_T0`, `$s`, `$S`) names. All other names are considered unmangled.
+fn detect_name_mangling(name: &str) -> (NameMangling, Language) {
+    if !is_mangled_charset(name) {
+        return (NameMangling::Unmangled, Language::Unknown);
+    }
+
+    // Mach-O symbols carry an additional leading underscore.
+    let stripped = name
+        .strip_prefix('_')
+        .filter(|s| s.starts_with('_') || s.starts_with('$'));
+    let stripped = stripped.unwrap_or(name);
+
+    let language = if stripped.starts_with('?') {
+        Language::Cpp
+    } else if let Some(rest) = stripped.strip_prefix("_Z") {
+        match rest.bytes().next() {
+            Some(b'N') if is_legacy_rust(rest) => Language::Rust,
+            Some(b'N' | b'L' | b'T' | b'S' | b'Z' | b'G' | b'0'..=b'9') => Language::Cpp,
+            _ => return (NameMangling::Unmangled, Language::Unknown),
+        }
+    } else if let Some(rest) = stripped.strip_prefix("_R") {
+        match rest.bytes().next() {
+            Some(b'N' | b'I' | b'C' | b'0'..=b'9') => Language::Rust,
+            _ => return (NameMangling::Unmangled, Language::Unknown),
+        }
+    } else if stripped.starts_with("_T0")
+        || stripped.starts_with("$s")
+        || stripped.starts_with("$S")
+    {
+        Language::Swift
+    } else {
+        return (NameMangling::Unmangled, Language::Unknown);
+    };
+
+    (NameMangling::Mangled, language)
+}
+
+/// Creates a [`Name`] for a record name, detecting whether it is mangled.
+fn record_name(name: &str) -> Name<'_> {
+    let (mangling, language) = detect_name_mangling(name);
+    Name::new(name, mangling, language)
+}
+
+/// Demangles a record name, returning `None` if it is not mangled or cannot be demangled.
+#[cfg(feature = "demangle")]
+fn demangle_name(name: &str) -> Option<String> {
+    use symbolic_demangle::{Demangle, DemangleOptions};
+
+    Name::from(name)
+        .demangle(DemangleOptions::complete())
+        .filter(|demangled| demangled != name)
+}
+
+/// Decodes the data of a single record and trims surrounding whitespace.
+///
+/// Fails with [`BreakpadErrorKind::MultipleLines`] if a line break remains after trimming, since
+/// the record parsers would otherwise fold the following line into the last field of the record.
+fn record_line(data: &[u8]) -> Result<&str, BreakpadError> {
+    let string = str::from_utf8(data)?.trim();
+    if string.contains(['\r', '\n']) {
+        return Err(BreakpadErrorKind::MultipleLines.into());
+    }
+    Ok(string)
+}
+
+/// Writes the flags of a `FUNC` or `PUBLIC` record, each followed by a space.
+fn write_flags(f: &mut fmt::Formatter<'_>, multiple: bool, unknown_flags: &[char]) -> fmt::Result {
+    if multiple {
+        write!(f, "m ")?;
+    }
+    for flag in unknown_flags {
+        write!(f, "{} ", flag)?;
+    }
+    Ok(())
+}
+
+/// Writes a name field preceded by a space, unless it is missing.
+fn write_name(f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+    if name.is_empty() || name == UNKNOWN_NAME {
+        Ok(())
+    } else {
+        write!(f, " {}", name)
+    }
+}
+
 /// The error type for [`BreakpadError`].
 #[non_exhaustive]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -146,6 +325,73 @@ pub enum BreakpadErrorKind {
 
     /// The architecture is invalid.
     InvalidArchitecture,
+
+    /// A `MODULE` record passed to [`BreakpadObject::with_module_record`] is malformed.
+    InvalidModuleRecord,
+
+    /// A line record references a file id that has no corresponding `FILE` record.
+    ///
+    /// This is only reported by [`BreakpadDebugSession::functions_strict`].
+    UnknownFileId {
+        /// The address of the function containing the line record.
+        function_address: u64,
+        /// The address of the line record.
+        line_address: u64,
+        /// The file id that could not be resolved.
+        file_id: u64,
+    },
+
+    /// A line record constructed with [`BreakpadLineRecord::new`] is invalid.
+    InvalidLineRecord,
+
+    /// The address range of a `FUNC` or `STACK CFI INIT` record exceeds the address space.
+    FuncRecordOverflow,
+
+    /// The rules of a `STACK CFI` record are malformed.
+    InvalidCfiRules,
+
+    /// The data passed to a record's `parse` function contains more than one line.
+    MultipleLines,
+
+    /// A line record or `STACK CFI` delta record belongs to a record that could not be parsed.
+    ///
+    /// This is only counted by [`BreakpadObject::normalize_lossy`].
+    OrphanedRecord,
+
+    /// The data passed to [`BreakpadObject::from_compact_binary`] is malformed.
+    InvalidCompactBinary,
+
+    /// The objects passed to [`BreakpadObject::merge_with_strategy`] describe different modules.
+    ModuleMismatch,
+
+    /// Both objects passed to [`BreakpadObject::merge_with_strategy`] contain different records at
+    /// the same address.
+    ///
+    /// This is only reported with [`BreakpadMergeStrategy::Error`].
+    MergeConflict {
+        /// The address of the conflicting records.
+        address: u64,
+    },
+
+    /// The data passed to [`BreakpadIndex::load`] is malformed or does not match the object.
+    InvalidIndex,
+
+    /// A record was passed to a [`BreakpadStreamWriter`] outside of its section.
+    ///
+    /// Records must be written in the order `MODULE`, `INFO`, `FILE`, `FUNC` with its line records,
+    /// `PUBLIC` and `STACK`.
+    RecordOutOfOrder {
+        /// The type of the rejected record, such as `"FILE"`.
+        record: &'static str,
+        /// The type of the previously written record, or `None` if nothing was written yet.
+        previous: Option<&'static str>,
+    },
+
+    /// Writing to the output of a [`BreakpadStreamWriter`] failed.
+    WriteFailed,
+
+    /// A [`BreakpadFileIdRemapper`] cannot assign a new file ID, since the highest ID is in use.
+    FileIdOverflow,
 }
 
 impl fmt::Display for BreakpadErrorKind {
@@ -156,6 +402,33 @@ impl fmt::Display for BreakpadErrorKind {
             Self::Parse(_) => write!(f, "parsing error"),
             Self::InvalidModuleId => write!(f, "invalid module id"),
             Self::InvalidArchitecture => write!(f, "invalid architecture"),
+            Self::InvalidModuleRecord => write!(f, "invalid module record"),
+            Self::UnknownFileId {
+                function_address,
+                line_address,
+                file_id,
+            } => write!(
+                f,
+                "line record at {:#x} in function at {:#x} references unknown file id {}",
+                line_address, function_address, file_id
+            ),
+            Self::InvalidLineRecord => write!(f, "invalid line record"),
+            Self::FuncRecordOverflow => write!(f, "record address range overflows"),
+            Self::InvalidCfiRules => write!(f, "invalid cfi rules"),
+            Self::MultipleLines => write!(f, "record spans multiple lines"),
+            Self::OrphanedRecord => write!(f, "record belongs to an invalid record"),
+            Self::InvalidCompactBinary => write!(f, "invalid compact binary breakpad data"),
+            Self::ModuleMismatch => write!(f, "cannot merge symbols of different modules"),
+            Self::MergeConflict { address } => {
+                write!(f, "conflicting records at {:#x} while merging", address)
+            }
+            Self::RecordOutOfOrder { record, previous } => match previous {
+                Some(previous) => write!(f, "{} record cannot follow {} record", record, previous),
+                None => write!(f, "{} record cannot be the first record", record),
+            },
+            Self::WriteFailed => write!(f, "failed to write breakpad records"),
+            Self::InvalidIndex => write!(f, "invalid or stale breakpad index"),
+            Self::FileIdOverflow => write!(f, "no file id left to assign"),
             _ => Ok(()),
         }
     }
@@ -213,6 +486,7 @@ impl From<parsing::ParseBreakpadError> for BreakpadError {
 ///
 /// [module record]: https://github.com/google/breakpad/blob/master/docs/symbol_files.md#module-records
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BreakpadModuleRecord<'d> {
     /// Name of the operating system.
     pub os: &'d str,
@@ -231,8 +505,100 @@ pub struct BreakpadModuleRecord<'d> {
 impl<'d> BreakpadModuleRecord<'d> {
     /// Parses a module record from a single line.
     pub fn parse(data: &'d [u8]) -> Result<Self, BreakpadError> {
-        let string = str::from_utf8(data)?;
-        Ok(parsing::module_record_final(string.trim())?)
+        let string = record_line(data)?;
+        Ok(parsing::module_record_final(string)?)
+    }
+
+    /// The combination of operating system and CPU architecture of this module.
+    ///
+    /// Operating system names are matched case-insensitively. Architectures are recognized by their
+    /// CPU family, so `arm64e` is reported as [`Platform::MacOsArm64`], for instance.
+    pub fn platform(&self) -> Platform {
+        let family = self
+            .arch
+            .parse::<Arch>()
+            .map(Arch::cpu_family)
+            .unwrap_or_default();
+
+        let os = self.os.to_ascii_lowercase();
+        match (os.as_str(), family) {
+            ("linux", CpuFamily::Intel32) => Platform::LinuxX86,
+            ("linux", CpuFamily::Amd64) => Platform::LinuxX86_64,
+            ("linux", CpuFamily::Arm32) => Platform::LinuxArm,
+            ("linux", CpuFamily::Arm64) => Platform::LinuxArm64,
+            ("mac", CpuFamily::Amd64) => Platform::MacOsX86_64,
+            ("mac", CpuFamily::Arm64) => Platform::MacOsArm64,
+            ("windows", CpuFamily::Intel32) => Platform::WindowsX86,
+            ("windows", CpuFamily::Amd64) => Platform::WindowsX86_64,
+            ("android", _) => Platform::Android,
+            _ => Platform::Unknown(self.os.to_owned(), self.arch.to_owned()),
+        }
+    }
+}
+
+/// The operating system and CPU architecture of a Breakpad module.
+///
+/// This is returned by [`BreakpadModuleRecord::platform`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Platform {
+    /// Linux on 32-bit x86.
+    LinuxX86,
+    /// Linux on x86-64.
+    LinuxX86_64,
+    /// Linux on 32-bit ARM.
+    LinuxArm,
+    /// Linux on 64-bit ARM.
+    LinuxArm64,
+    /// macOS on x86-64.
+    MacOsX86_64,
+    /// macOS on 64-bit ARM.
+    MacOsArm64,
+    /// Windows on 32-bit x86.
+    WindowsX86,
+    /// Windows on x86-64.
+    WindowsX86_64,
+    /// Android on any architecture.
+    Android,
+    /// Any other combination, given by the operating system and architecture names of the module
+    /// record.
+    Unknown(String, String),
+}
+
+impl Platform {
+    /// Returns true if the platform uses 64-bit pointers.
+    ///
+    /// For [`Android`](Self::Android), the architecture is not known and this returns `false`. For
+    /// [`Unknown`](Self::Unknown) platforms, the architecture name is parsed as an [`Arch`].
+    pub fn is_64bit(&self) -> bool {
+        match self {
+            Self::LinuxX86_64
+            | Self::LinuxArm64
+            | Self::MacOsX86_64
+            | Self::MacOsArm64
+            | Self::WindowsX86_64 => true,
+            Self::LinuxX86 | Self::LinuxArm | Self::WindowsX86 | Self::Android => false,
+            Self::Unknown(_, arch) => matches!(
+                arch.parse::<Arch>()
+                    .map(|arch| arch.cpu_family().pointer_size()),
+                Ok(Some(8))
+            ),
+        }
+    }
+
+    /// Returns true if the platform is Windows.
+    pub fn is_windows(&self) -> bool {
+        match self {
+            Self::WindowsX86 | Self::WindowsX86_64 => true,
+            Self::Unknown(os, _) => os.eq_ignore_ascii_case("windows"),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for BreakpadModuleRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MODULE {} {} {}", self.os, self.arch, self.id)?;
+        write_name(f, self.name)
     }
 }
 
@@ -242,6 +608,7 @@ impl<'d> BreakpadModuleRecord<'d> {
 /// that currently only a `CODE_ID` scope is used, which contains the platform-dependent original
 /// code identifier of an object file.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum BreakpadInfoRecord<'d> {
     /// Information on the code file.
     CodeId {
@@ -262,8 +629,21 @@ pub enum BreakpadInfoRecord<'d> {
 impl<'d> BreakpadInfoRecord<'d> {
     /// Parses an info record from a single line.
     pub fn parse(data: &'d [u8]) -> Result<Self, BreakpadError> {
-        let string = str::from_utf8(data)?;
-        Ok(parsing::info_record_final(string.trim())?)
+        let string = record_line(data)?;
+        Ok(parsing::info_record_final(string)?)
+    }
+}
+
+impl fmt::Display for BreakpadInfoRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::CodeId { code_id, code_file } => {
+                write!(f, "INFO CODE_ID {}", code_id)?;
+                write_name(f, code_file)
+            }
+            Self::Other { scope, info: "" } => write!(f, "INFO {}", scope),
+            Self::Other { scope, info } => write!(f, "INFO {} {}", scope, info),
+        }
     }
 }
 
@@ -272,12 +652,12 @@ impl<'d> BreakpadInfoRecord<'d> {
 pub struct BreakpadInfoRecords<'d> {
     lines: Lines<'d>,
     finished: bool,
+    full_scan: bool,
 }
 
-impl<'d> Iterator for BreakpadInfoRecords<'d> {
-    type Item = Result<BreakpadInfoRecord<'d>, BreakpadError>;
-
-    fn next(&mut self) -> Option<Self::Item> {
+impl<'d> BreakpadInfoRecords<'d> {
+    /// Returns the next raw INFO line, stopping at the first line that is not an INFO record.
+    fn next_line(&mut self) -> Option<&'d [u8]> {
         if self.finished {
             return None;
         }
@@ -287,12 +667,14 @@ impl<'d> Iterator for BreakpadInfoRecords<'d> {
                 continue;
             }
 
+            if line.starts_with(b"INFO ") {
+                return Some(line);
+            }
+
             // Fast path: INFO records come right after the header.
-            if !line.starts_with(b"INFO ") {
+            if !self.full_scan {
                 break;
             }
-
-            return Some(BreakpadInfoRecord::parse(line));
         }
 
         self.finished = true;
@@ -300,6 +682,45 @@ impl<'d> Iterator for BreakpadInfoRecords<'d> {
     }
 }
 
+impl<'d> Iterator for BreakpadInfoRecords<'d> {
+    type Item = Result<BreakpadInfoRecord<'d>, BreakpadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_line().map(BreakpadInfoRecord::parse)
+    }
+}
+
+/// An iterator over the `(scope, info)` pairs of all INFO records in a Breakpad object.
+///
+/// Unlike [`BreakpadInfoRecords`], this does not interpret known scopes such as `CODE_ID`. The info
+/// string is the remainder of the line after the scope, with surrounding whitespace removed. Lines
+/// that are not valid UTF-8 are skipped.
+#[derive(Clone, Debug)]
+pub struct BreakpadInfos<'d> {
+    records: BreakpadInfoRecords<'d>,
+}
+
+impl<'d> Iterator for BreakpadInfos<'d> {
+    type Item = (&'d str, &'d str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(line) = self.records.next_line() {
+            let line = match str::from_utf8(line) {
+                Ok(line) => line,
+                Err(_) => continue,
+            };
+
+            let record = line["INFO ".len()..].trim();
+            return Some(match record.find(char::is_whitespace) {
+                Some(index) => (&record[..index], record[index..].trim_start()),
+                None => (record, ""),
+            });
+        }
+
+        None
+    }
+}
+
 /// A [file record], specifying the path to a source code file.
 ///
 /// The ID of this record is referenced by [`BreakpadLineRecord`]. File records are not necessarily
@@ -308,21 +729,77 @@ impl<'d> Iterator for BreakpadInfoRecords<'d> {
 ///
 /// Example: `FILE 2 /home/jimb/mc/in/browser/app/nsBrowserApp.cpp`
 ///
+/// Some producers append a checksum of the file's contents after the path. Since paths may contain
+/// spaces, the checksum cannot be told apart from the path reliably. The last whitespace-separated
+/// token is treated as a checksum if it is a lowercase hex string of 32 to 64 digits, optionally
+/// prefixed with an algorithm name and a colon, such as `sha256:`. A path whose last component is
+/// separated by a space and happens to look like a checksum is split incorrectly.
+///
 /// [file record]: https://github.com/google/breakpad/blob/master/docs/symbol_files.md#file-records
 /// [`LineRecord`]: struct.BreakpadLineRecord.html
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BreakpadFileRecord<'d> {
     /// Breakpad-internal identifier of the file.
     pub id: u64,
     /// The path to the source file, usually relative to the compilation directory.
     pub name: &'d str,
+    /// The checksum of the source file, if present.
+    ///
+    /// When formatting the record, the checksum is omitted if the name is empty, since it would
+    /// otherwise be read back as the name.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub checksum: Option<&'d str>,
 }
 
 impl<'d> BreakpadFileRecord<'d> {
+    /// Converts this record into an owned record.
+    pub fn into_owned(self) -> BreakpadFileRecordOwned {
+        BreakpadFileRecordOwned {
+            id: self.id,
+            name: self.name.to_owned(),
+            checksum: self.checksum.map(str::to_owned),
+        }
+    }
+
     /// Parses a file record from a single line.
     pub fn parse(data: &'d [u8]) -> Result<Self, BreakpadError> {
-        let string = str::from_utf8(data)?;
-        Ok(parsing::file_record_final(string.trim())?)
+        let string = record_line(data)?;
+        Ok(parsing::file_record_final(string)?)
+    }
+}
+
+impl fmt::Display for BreakpadFileRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FILE {}", self.id)?;
+        write_name(f, self.name)?;
+        match self.checksum {
+            Some(checksum) if !self.name.is_empty() && self.name != UNKNOWN_NAME => {
+                write!(f, " {}", checksum)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A file record not borrowing from the symbol file.
+///
+/// This is created by [`BreakpadFileRecord::into_owned`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BreakpadFileRecordOwned {
+    /// Breakpad-internal identifier of the file.
+    pub id: u64,
+    /// The path to the source file, usually relative to the compilation directory.
+    pub name: String,
+    /// The checksum of the source file, if present.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub checksum: Option<String>,
+}
+
+impl From<BreakpadFileRecord<'_>> for BreakpadFileRecordOwned {
+    fn from(record: BreakpadFileRecord<'_>) -> Self {
+        record.into_owned()
     }
 }
 
@@ -331,6 +808,18 @@ impl<'d> BreakpadFileRecord<'d> {
 pub struct BreakpadFileRecords<'d> {
     lines: Lines<'d>,
     finished: bool,
+    full_scan: bool,
+    raw: Option<&'d [u8]>,
+}
+
+impl<'d> BreakpadFileRecords<'d> {
+    /// Returns the line of the record last returned by this iterator, without the line terminator.
+    ///
+    /// This allows copying records verbatim, including formatting that is lost when parsing.
+    /// Returns `None` before the first record.
+    pub fn raw(&self) -> Option<&'d [u8]> {
+        self.raw
+    }
 }
 
 impl<'d> Iterator for BreakpadFileRecords<'d> {
@@ -346,12 +835,15 @@ impl<'d> Iterator for BreakpadFileRecords<'d> {
                 continue;
             }
 
+            if line.starts_with(b"FILE ") {
+                self.raw = Some(line);
+                return Some(BreakpadFileRecord::parse(line));
+            }
+
             // Fast path: FILE records come right after the header.
-            if !line.starts_with(b"FILE ") {
+            if !self.full_scan {
                 break;
             }
-
-            return Some(BreakpadFileRecord::parse(line));
         }
 
         self.finished = true;
@@ -362,15 +854,223 @@ impl<'d> Iterator for BreakpadFileRecords<'d> {
 /// A map of file paths by their file ID.
 pub type BreakpadFileMap<'d> = BTreeMap<u64, &'d str>;
 
+/// Assigns non-conflicting file IDs when combining file records from multiple sources.
+///
+/// When symbol data from two Breakpad files is merged, their `FILE` records may use the same IDs
+/// for different paths. The remapper starts from an existing [`BreakpadFileMap`] and assigns an ID
+/// to every incoming file record:
+///
+///  - If the path is already known, its existing ID is reused.
+///  - If the incoming ID is still free, it is kept.
+///  - Otherwise, the file is assigned the ID following the highest one in use. If that exceeds
+///    `u64::MAX`, an error is returned.
+///
+/// The resulting [`BreakpadFileIdMapping`] translates IDs of the incoming source, which can be used
+/// to rewrite the `file_id` of its [`BreakpadLineRecord`]s.
+///
+/// # Example
+///
+/// ```
+/// use symbolic_debuginfo::breakpad::{BreakpadFileIdRemapper, BreakpadFileRecord};
+///
+/// let mut remapper = BreakpadFileIdRemapper::new(Default::default());
+/// remapper.add_files(vec![BreakpadFileRecord { id: 1, name: "a.c", checksum: None }]).unwrap();
+///
+/// let mapping = remapper.add_files(vec![
+///     BreakpadFileRecord { id: 1, name: "b.c", checksum: None },
+///     BreakpadFileRecord { id: 2, name: "a.c", checksum: None },
+/// ]).unwrap();
+///
+/// assert_eq!(mapping.get(1), Some(2));
+/// assert_eq!(mapping.get(2), Some(1));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BreakpadFileIdRemapper<'d> {
+    files: BreakpadFileMap<'d>,
+    ids: HashMap<&'d str, u64>,
+}
+
+impl<'d> BreakpadFileIdRemapper<'d> {
+    /// Creates a new remapper starting from an existing file map.
+    pub fn new(files: BreakpadFileMap<'d>) -> Self {
+        let mut ids = HashMap::with_capacity(files.len());
+        for (&id, &name) in &files {
+            ids.entry(name).or_insert(id);
+        }
+
+        Self { files, ids }
+    }
+
+    /// Adds a single file record and returns the ID assigned to it.
+    ///
+    /// Fails if the record needs a new ID, but the highest ID is already in use.
+    pub fn add_file(&mut self, record: &BreakpadFileRecord<'d>) -> Result<u64, BreakpadError> {
+        if let Some(&id) = self.ids.get(record.name) {
+            return Ok(id);
+        }
+
+        let id = if self.files.contains_key(&record.id) {
+            match self.files.keys().next_back() {
+                Some(id) => id.checked_add(1).ok_or(BreakpadErrorKind::FileIdOverflow)?,
+                None => 0,
+            }
+        } else {
+            record.id
+        };
+
+        self.files.insert(id, record.name);
+        self.ids.insert(record.name, id);
+        Ok(id)
+    }
+
+    /// Adds all file records of a source and returns the mapping of their IDs.
+    ///
+    /// Fails if a record needs a new ID, but the highest ID is already in use. Records before it
+    /// have been added in that case.
+    pub fn add_files<I>(&mut self, records: I) -> Result<BreakpadFileIdMapping, BreakpadError>
+    where
+        I: IntoIterator<Item = BreakpadFileRecord<'d>>,
+    {
+        let ids = records
+            .into_iter()
+            .map(|record| Ok((record.id, self.add_file(&record)?)))
+            .collect::<Result<_, BreakpadError>>()?;
+
+        Ok(BreakpadFileIdMapping { ids })
+    }
+
+    /// Returns the combined map of all files added so far.
+    pub fn file_map(&self) -> &BreakpadFileMap<'d> {
+        &self.files
+    }
+
+    /// Returns the combined map of all files, consuming the remapper.
+    pub fn into_file_map(self) -> BreakpadFileMap<'d> {
+        self.files
+    }
+}
+
+/// A mapping from file IDs of a source to the IDs assigned by a [`BreakpadFileIdRemapper`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BreakpadFileIdMapping {
+    ids: BTreeMap<u64, u64>,
+}
+
+impl BreakpadFileIdMapping {
+    /// Returns the new ID for the given original file ID.
+    pub fn get(&self, id: u64) -> Option<u64> {
+        self.ids.get(&id).copied()
+    }
+
+    /// Rewrites the file ID of a line record.
+    ///
+    /// Returns `None` if the line record references a file that was not part of the source.
+    pub fn remap_line(&self, record: &BreakpadLineRecord) -> Option<BreakpadLineRecord> {
+        Some(BreakpadLineRecord {
+            file_id: self.get(record.file_id)?,
+            ..record.clone()
+        })
+    }
+}
+
+/// Renumbers the file IDs of a Breakpad symbol file sequentially.
+///
+/// This normalizes files that were combined from multiple sources. All `FILE` records are assigned
+/// new IDs starting at `0` in the order they appear, and the `file_id` of every line record is
+/// rewritten accordingly. `FILE` and line records are written back in their canonical format,
+/// keeping checksums and columns. All other lines are copied verbatim, although line endings are
+/// normalized to `\n`.
+///
+/// Duplicate `FILE` records with the same ID and path are only kept once. If a `FILE` record reuses
+/// an ID with a different path, as happens when concatenating symbol files, it is assigned a fresh
+/// ID. Line records always refer to the most recent `FILE` record with their ID, or to the first one
+/// if the file is only declared further down. A line record referencing an ID without a `FILE`
+/// record results in an error of kind [`BreakpadErrorKind::UnknownFileId`].
+pub fn rewrite_file_ids(data: &[u8]) -> Result<Vec<u8>, BreakpadError> {
+    // New IDs are assigned per distinct combination of original ID and path.
+    let mut new_ids = HashMap::new();
+    // Maps original IDs to the new ID of their most recent `FILE` record.
+    let mut ids = HashMap::new();
+    for line in Lines::new(data) {
+        if line.starts_with(b"FILE ") {
+            let record = BreakpadFileRecord::parse(line)?;
+            let next_id = new_ids.len() as u64;
+            let new_id = *new_ids.entry((record.id, record.name)).or_insert(next_id);
+            ids.entry(record.id).or_insert(new_id);
+        }
+    }
+
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(&data[..data.len() - strip_bom(data).len()]);
+
+    let mut emitted = HashSet::new();
+    let mut function_address = None;
+    for (index, line) in Lines::new(data).enumerate() {
+        if index > 0 {
+            output.push(b'\n');
+        }
+
+        if line.starts_with(b"FILE ") {
+            let record = BreakpadFileRecord::parse(line)?;
+            let new_id = new_ids[&(record.id, record.name)];
+            ids.insert(record.id, new_id);
+            if emitted.insert(new_id) {
+                let record = BreakpadFileRecord {
+                    id: new_id,
+                    ..record
+                };
+                write!(output, "{}", record).ok();
+            } else if output.last() == Some(&b'\n') {
+                // Drop the duplicate record along with its line break.
+                output.pop();
+            }
+            continue;
+        }
+
+        if line.starts_with(b"FUNC ") {
+            let record = BreakpadFuncRecord::parse(line, Lines::default())?;
+            function_address = Some(record.address);
+        } else if line.starts_with(b"PUBLIC ")
+            || line.starts_with(b"STACK ")
+            || line.starts_with(b"INFO ")
+            || line.starts_with(b"MODULE ")
+        {
+            function_address = None;
+        } else if let (Some(function_address), false) = (function_address, line.is_empty()) {
+            let record = BreakpadLineRecord::parse(line)?;
+            let file_id = *ids
+                .get(&record.file_id)
+                .ok_or(BreakpadErrorKind::UnknownFileId {
+                    function_address,
+                    line_address: record.address,
+                    file_id: record.file_id,
+                })?;
+
+            write!(output, "{}", BreakpadLineRecord { file_id, ..record }).ok();
+            continue;
+        }
+
+        output.extend_from_slice(line);
+    }
+
+    Ok(output)
+}
+
 /// A [public function symbol record].
 ///
 /// Example: `PUBLIC m 2160 0 Public2_1`
 ///
 /// [public function symbol record]: https://github.com/google/breakpad/blob/master/docs/symbol_files.md#public-records
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BreakpadPublicRecord<'d> {
     /// Whether this symbol was referenced multiple times.
     pub multiple: bool,
+    /// Flags preceding the address that are not known to this parser.
+    ///
+    /// These are reported by [`BreakpadObject::validate`].
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub unknown_flags: Vec<char>,
     /// The address of this symbol relative to the image base (load address).
     pub address: u64,
     /// The size of the parameters on the runtime stack.
@@ -382,8 +1082,83 @@ pub struct BreakpadPublicRecord<'d> {
 impl<'d> BreakpadPublicRecord<'d> {
     /// Parses a public record from a single line.
     pub fn parse(data: &'d [u8]) -> Result<Self, BreakpadError> {
-        let string = str::from_utf8(data)?;
-        Ok(parsing::public_record_final(string.trim())?)
+        let string = record_line(data)?;
+        Ok(parsing::public_record_final(string)?)
+    }
+
+    /// Returns the demangled name of this symbol.
+    ///
+    /// The mangling scheme is detected from the name. Returns `None` if the name is not mangled or
+    /// cannot be demangled.
+    #[cfg(feature = "demangle")]
+    pub fn demangled_name(&self) -> Option<String> {
+        demangle_name(self.name)
+    }
+
+    /// Converts this record into an owned record.
+    pub fn into_owned(self) -> BreakpadPublicRecordOwned {
+        BreakpadPublicRecordOwned {
+            multiple: self.multiple,
+            unknown_flags: self.unknown_flags,
+            address: self.address,
+            parameter_size: self.parameter_size,
+            name: self.name.to_owned(),
+        }
+    }
+}
+
+/// Orders public records by address.
+///
+/// Records at the same address are ordered by their remaining fields, so that only equal records
+/// compare equal.
+impl PartialOrd for BreakpadPublicRecord<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BreakpadPublicRecord<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.address
+            .cmp(&other.address)
+            .then_with(|| self.parameter_size.cmp(&other.parameter_size))
+            .then_with(|| self.name.cmp(other.name))
+            .then_with(|| self.multiple.cmp(&other.multiple))
+            .then_with(|| self.unknown_flags.cmp(&other.unknown_flags))
+    }
+}
+
+impl fmt::Display for BreakpadPublicRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PUBLIC ")?;
+        write_flags(f, self.multiple, &self.unknown_flags)?;
+        write!(f, "{:x} {:x}", self.address, self.parameter_size)?;
+        write_name(f, self.name)
+    }
+}
+
+/// A public function symbol record not borrowing from the symbol file.
+///
+/// This is created by [`BreakpadPublicRecord::into_owned`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BreakpadPublicRecordOwned {
+    /// Whether this symbol was referenced multiple times.
+    pub multiple: bool,
+    /// Flags preceding the address that are not known to this parser.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub unknown_flags: Vec<char>,
+    /// The address of this symbol relative to the image base (load address).
+    pub address: u64,
+    /// The size of the parameters on the runtime stack.
+    pub parameter_size: u64,
+    /// The demangled function name of the symbol.
+    pub name: String,
+}
+
+impl From<BreakpadPublicRecord<'_>> for BreakpadPublicRecordOwned {
+    fn from(record: BreakpadPublicRecord<'_>) -> Self {
+        record.into_owned()
     }
 }
 
@@ -392,6 +1167,27 @@ impl<'d> BreakpadPublicRecord<'d> {
 pub struct BreakpadPublicRecords<'d> {
     lines: Lines<'d>,
     finished: bool,
+    full_scan: bool,
+    offset: Option<usize>,
+    raw: Option<&'d [u8]>,
+}
+
+impl<'d> BreakpadPublicRecords<'d> {
+    /// Returns the byte offset of the record last returned by this iterator.
+    ///
+    /// The offset refers to the data of the Breakpad object, so the record can be parsed again
+    /// from [`BreakpadObject::data`] at this offset. Returns `None` before the first record.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Returns the line of the record last returned by this iterator, without the line terminator.
+    ///
+    /// This allows copying records verbatim, including formatting that is lost when parsing.
+    /// Returns `None` before the first record.
+    pub fn raw(&self) -> Option<&'d [u8]> {
+        self.raw
+    }
 }
 
 impl<'d> Iterator for BreakpadPublicRecords<'d> {
@@ -402,10 +1198,10 @@ impl<'d> Iterator for BreakpadPublicRecords<'d> {
             return None;
         }
 
-        for line in &mut self.lines {
+        while let Some((offset, line)) = self.lines.next_with_offset() {
             // Fast path: PUBLIC records are always before stack records. Once we encounter the
             // first stack record, we can therefore exit.
-            if line.starts_with(b"STACK ") {
+            if !self.full_scan && line.starts_with(b"STACK ") {
                 break;
             }
 
@@ -413,6 +1209,8 @@ impl<'d> Iterator for BreakpadPublicRecords<'d> {
                 continue;
             }
 
+            self.offset = Some(offset);
+            self.raw = Some(line);
             return Some(BreakpadPublicRecord::parse(line));
         }
 
@@ -421,15 +1219,35 @@ impl<'d> Iterator for BreakpadPublicRecords<'d> {
     }
 }
 
+/// Ensures that the address range `start..start + size` of a record does not overflow.
+fn check_record_range(start: u64, size: u64) -> Result<(), BreakpadError> {
+    match start.checked_add(size) {
+        Some(_) => Ok(()),
+        None => Err(BreakpadError::new(
+            BreakpadErrorKind::FuncRecordOverflow,
+            format!("address {:#x} with size {:#x} overflows", start, size),
+        )),
+    }
+}
+
 /// A [function record] including line information.
 ///
 /// Example: `FUNC m c184 30 0 nsQueryInterfaceWithError::operator()(nsID const&, void**) const`
 ///
+/// With the `serde` feature, the record serializes its line records as a `lines` sequence. Since
+/// they are parsed during serialization, serialization fails if a line record is malformed.
+///
 /// [function record]: https://github.com/google/breakpad/blob/master/docs/symbol_files.md#func-records
 #[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BreakpadFuncRecord<'d> {
     /// Whether this function was referenced multiple times.
     pub multiple: bool,
+    /// Flags preceding the address that are not known to this parser.
+    ///
+    /// These are reported by [`BreakpadObject::validate`].
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub unknown_flags: Vec<char>,
     /// The start address of this function relative to the image base (load address).
     pub address: u64,
     /// The size of the code covered by this function's line records.
@@ -438,6 +1256,7 @@ pub struct BreakpadFuncRecord<'d> {
     pub parameter_size: u64,
     /// The demangled function name.
     pub name: &'d str,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_line_records"))]
     lines: Lines<'d>,
 }
 
@@ -448,8 +1267,9 @@ impl<'d> BreakpadFuncRecord<'d> {
     /// records for this function, which are read until another record isencountered or the file
     /// ends.
     pub fn parse(data: &'d [u8], lines: Lines<'d>) -> Result<Self, BreakpadError> {
-        let string = str::from_utf8(data)?;
-        let mut record = parsing::func_record_final(string.trim())?;
+        let string = record_line(data)?;
+        let mut record = parsing::func_record_final(string)?;
+        check_record_range(record.address, record.size)?;
 
         record.lines = lines;
         Ok(record)
@@ -460,18 +1280,114 @@ impl<'d> BreakpadFuncRecord<'d> {
         BreakpadLineRecords {
             lines: self.lines.clone(),
             finished: false,
+            raw: None,
         }
     }
 
+    /// Converts this record into an owned record with all of its line records parsed.
+    ///
+    /// Fails if any of the line records cannot be parsed.
+    pub fn into_owned(self) -> Result<BreakpadFuncRecordOwned, BreakpadError> {
+        let lines = self.lines().collect::<Result<Vec<_>, _>>()?;
+
+        Ok(BreakpadFuncRecordOwned {
+            multiple: self.multiple,
+            unknown_flags: self.unknown_flags,
+            address: self.address,
+            size: self.size,
+            parameter_size: self.parameter_size,
+            name: self.name.to_owned(),
+            lines,
+        })
+    }
+
+    /// Returns the end address of this record, or `None` if it overflows.
+    pub fn end_address(&self) -> Option<u64> {
+        self.address.checked_add(self.size)
+    }
+
     /// Returns the range of addresses covered by this record.
+    ///
+    /// If the end address overflows, the range ends at `u64::MAX`.
     pub fn range(&self) -> Range<u64> {
-        self.address..self.address + self.size
+        self.address..self.end_address().unwrap_or(u64::MAX)
+    }
+
+    /// Computes statistics over the line records of this function.
+    ///
+    /// This iterates the line records once. Like [`lines`](Self::lines), it skips line records
+    /// covering no code. Fails if a line record cannot be parsed.
+    pub fn summary(&self) -> Result<BreakpadFuncSummary, BreakpadError> {
+        let mut line_count = 0;
+        let mut total_line_bytes = 0u64;
+        let mut file_ids = BTreeSet::new();
+
+        for line in self.lines() {
+            let line = line?;
+            line_count += 1;
+            total_line_bytes = total_line_bytes.saturating_add(line.size);
+            file_ids.insert(line.file_id);
+        }
+
+        Ok(BreakpadFuncSummary {
+            address: self.address,
+            size: self.size,
+            name: self.name.to_owned(),
+            line_count,
+            total_line_bytes,
+            file_ids: file_ids.into_iter().collect(),
+        })
+    }
+
+    /// Returns the byte range of the line records associated to this function.
+    ///
+    /// The range starts right after the `FUNC` line and extends up to the next record that ends
+    /// the line records, or the end of the data. For records returned by [`BreakpadFuncRecords`],
+    /// it refers to the data of the Breakpad object. Otherwise, it refers to the data of the lines
+    /// passed to [`parse`](Self::parse).
+    pub fn line_block_range(&self) -> Range<usize> {
+        let start = self.lines.position();
+        let mut lines = self.lines.clone();
+        while let Some((offset, line)) = lines.next_with_offset() {
+            if ends_line_block(line) {
+                return start..offset;
+            }
+        }
+
+        start..lines.position()
+    }
+
+    /// Returns the demangled name of this function.
+    ///
+    /// The mangling scheme is detected from the name. Returns `None` if the name is not mangled or
+    /// cannot be demangled.
+    #[cfg(feature = "demangle")]
+    pub fn demangled_name(&self) -> Option<String> {
+        demangle_name(self.name)
     }
 }
 
+/// Statistics over the line records of a function, returned by [`BreakpadFuncRecord::summary`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BreakpadFuncSummary {
+    /// The start address of the function relative to the image base (load address).
+    pub address: u64,
+    /// The size of the code covered by the function.
+    pub size: u64,
+    /// The name of the function as written in the record, which may be mangled.
+    pub name: String,
+    /// The number of line records.
+    pub line_count: usize,
+    /// The sum of the sizes of all line records.
+    pub total_line_bytes: u64,
+    /// The sorted, unique file ids referenced by the line records.
+    pub file_ids: Vec<u64>,
+}
+
 impl PartialEq for BreakpadFuncRecord<'_> {
     fn eq(&self, other: &BreakpadFuncRecord<'_>) -> bool {
         self.multiple == other.multiple
+            && self.unknown_flags == other.unknown_flags
             && self.address == other.address
             && self.size == other.size
             && self.parameter_size == other.parameter_size
@@ -481,10 +1397,47 @@ impl PartialEq for BreakpadFuncRecord<'_> {
 
 impl Eq for BreakpadFuncRecord<'_> {}
 
+/// Orders function records by address.
+///
+/// Records at the same address are ordered by the remaining fields compared for equality, so that
+/// only equal records compare equal. Line records are not compared.
+impl PartialOrd for BreakpadFuncRecord<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BreakpadFuncRecord<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.address
+            .cmp(&other.address)
+            .then_with(|| self.size.cmp(&other.size))
+            .then_with(|| self.parameter_size.cmp(&other.parameter_size))
+            .then_with(|| self.name.cmp(other.name))
+            .then_with(|| self.multiple.cmp(&other.multiple))
+            .then_with(|| self.unknown_flags.cmp(&other.unknown_flags))
+    }
+}
+
+/// Formats the `FUNC` line of this record, without its line records.
+impl fmt::Display for BreakpadFuncRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FUNC ")?;
+        write_flags(f, self.multiple, &self.unknown_flags)?;
+        write!(
+            f,
+            "{:x} {:x} {:x}",
+            self.address, self.size, self.parameter_size
+        )?;
+        write_name(f, self.name)
+    }
+}
+
 impl fmt::Debug for BreakpadFuncRecord<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("BreakpadFuncRecord")
             .field("multiple", &self.multiple)
+            .field("unknown_flags", &self.unknown_flags)
             .field("address", &self.address)
             .field("size", &self.size)
             .field("parameter_size", &self.parameter_size)
@@ -493,11 +1446,82 @@ impl fmt::Debug for BreakpadFuncRecord<'_> {
     }
 }
 
+/// A function record together with its parsed line records, not borrowing from the symbol file.
+///
+/// This is created by [`BreakpadFuncRecord::into_owned`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BreakpadFuncRecordOwned {
+    /// Whether this function was referenced multiple times.
+    pub multiple: bool,
+    /// Flags preceding the address that are not known to this parser.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub unknown_flags: Vec<char>,
+    /// The start address of this function relative to the image base (load address).
+    pub address: u64,
+    /// The size of the code covered by this function's line records.
+    pub size: u64,
+    /// The size of the parameters on the runtime stack.
+    pub parameter_size: u64,
+    /// The demangled function name.
+    pub name: String,
+    /// The line records of this function, in the order they appear in the file.
+    pub lines: Vec<BreakpadLineRecord>,
+}
+
+impl TryFrom<BreakpadFuncRecord<'_>> for BreakpadFuncRecordOwned {
+    type Error = BreakpadError;
+
+    fn try_from(record: BreakpadFuncRecord<'_>) -> Result<Self, Self::Error> {
+        record.into_owned()
+    }
+}
+
 /// An iterator over function records in a Breakpad object.
 #[derive(Clone, Debug)]
 pub struct BreakpadFuncRecords<'d> {
     lines: Lines<'d>,
     finished: bool,
+    full_scan: bool,
+    offset: Option<usize>,
+    record_lines: Lines<'d>,
+    raw: Option<&'d [u8]>,
+}
+
+impl<'d> BreakpadFuncRecords<'d> {
+    /// Returns the byte offset of the record last returned by this iterator.
+    ///
+    /// The offset refers to the data of the Breakpad object, so the record can be parsed again
+    /// from [`BreakpadObject::data`] at this offset. Returns `None` before the first record.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Returns the `FUNC` line of the record last returned by this iterator, without the line
+    /// terminator.
+    ///
+    /// This allows copying records verbatim, including formatting that is lost when parsing. Line
+    /// records can be copied through [`BreakpadLineRecords::raw`]. Returns `None` before the first
+    /// record.
+    pub fn raw(&self) -> Option<&'d [u8]> {
+        self.raw
+    }
+
+    /// Returns the byte range of the record last returned by this iterator.
+    ///
+    /// The range covers the `FUNC` line and all of its line records, including the trailing line
+    /// break. It refers to the data of the Breakpad object, so the record can be copied verbatim
+    /// from [`BreakpadObject::data`]. Returns `None` before the first record.
+    pub fn byte_range(&self) -> Option<Range<usize>> {
+        let start = self.offset?;
+        let mut lines = self.record_lines.clone();
+        while let Some((offset, line)) = lines.next_with_offset() {
+            if ends_line_block(line) {
+                return Some(start..offset);
+            }
+        }
+        Some(start..lines.position())
+    }
 }
 
 impl<'d> Iterator for BreakpadFuncRecords<'d> {
@@ -508,10 +1532,10 @@ impl<'d> Iterator for BreakpadFuncRecords<'d> {
             return None;
         }
 
-        for line in &mut self.lines {
+        while let Some((offset, line)) = self.lines.next_with_offset() {
             // Fast path: FUNC records are always before stack records. Once we encounter the
             // first stack record, we can therefore exit.
-            if line.starts_with(b"STACK ") {
+            if !self.full_scan && line.starts_with(b"STACK ") {
                 break;
             }
 
@@ -519,6 +1543,9 @@ impl<'d> Iterator for BreakpadFuncRecords<'d> {
                 continue;
             }
 
+            self.offset = Some(offset);
+            self.record_lines = self.lines.clone();
+            self.raw = Some(line);
             return Some(BreakpadFuncRecord::parse(line, self.lines.clone()));
         }
 
@@ -527,6 +1554,38 @@ impl<'d> Iterator for BreakpadFuncRecords<'d> {
     }
 }
 
+/// The number of records a [`BreakpadFuncRecordStream`] yields before returning to the executor.
+#[cfg(feature = "async")]
+const STREAM_YIELD_INTERVAL: usize = 100;
+
+/// An asynchronous stream over function records in a Breakpad object.
+///
+/// Records are parsed lazily when the stream is polled. After every 100 records, the stream
+/// returns `Poll::Pending` once and immediately wakes its task, so that parsing a large file does
+/// not block the executor. This works with any executor.
+#[cfg(feature = "async")]
+#[derive(Clone, Debug)]
+pub struct BreakpadFuncRecordStream<'d> {
+    records: BreakpadFuncRecords<'d>,
+    budget: usize,
+}
+
+#[cfg(feature = "async")]
+impl<'d> futures_core::Stream for BreakpadFuncRecordStream<'d> {
+    type Item = Result<BreakpadFuncRecord<'d>, BreakpadError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.budget == 0 {
+            self.budget = STREAM_YIELD_INTERVAL;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        self.budget -= 1;
+        Poll::Ready(self.records.next())
+    }
+}
+
 /// A [line record] associated to a `BreakpadFunctionRecord`.
 ///
 /// Line records are so frequent in a Breakpad symbol file that they do not have a record
@@ -538,6 +1597,7 @@ impl<'d> Iterator for BreakpadFuncRecords<'d> {
 /// [line record]: https://github.com/google/breakpad/blob/master/docs/symbol_files.md#line-records
 /// [`BreakpadFuncRecord`]: struct.BreakpadFuncRecord.html
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BreakpadLineRecord {
     /// The start address for this line relative to the image base (load address).
     pub address: u64,
@@ -547,13 +1607,59 @@ pub struct BreakpadLineRecord {
     pub line: u64,
     /// Identifier of the [`BreakpadFileRecord`] specifying the file name.
     pub file_id: u64,
+    /// The column number, if the record has one.
+    ///
+    /// This is an optional fifth field emitted by some compilers, such as LLVM with column info.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub column: Option<u64>,
 }
 
 impl BreakpadLineRecord {
+    /// Creates a new line record without a column, validating its fields.
+    ///
+    /// This fails with [`BreakpadErrorKind::InvalidLineRecord`] if the covered address range exceeds
+    /// the address space, or if a file map is given and does not contain `file_id`.
+    pub fn new(
+        address: u64,
+        size: u64,
+        line: u64,
+        file_id: u64,
+        file_map: Option<&BreakpadFileMap<'_>>,
+    ) -> Result<Self, BreakpadError> {
+        if address.checked_add(size).is_none() {
+            let message = format!("size {:#x} at address {:#x} overflows", size, address);
+            return Err(BreakpadError::new(
+                BreakpadErrorKind::InvalidLineRecord,
+                message,
+            ));
+        }
+
+        if let Some(file_map) = file_map {
+            if !file_map.contains_key(&file_id) {
+                let message = format!("unknown file id {}", file_id);
+                return Err(BreakpadError::new(
+                    BreakpadErrorKind::InvalidLineRecord,
+                    message,
+                ));
+            }
+        }
+
+        Ok(Self {
+            address,
+            size,
+            line,
+            file_id,
+            column: None,
+        })
+    }
+
     /// Parses a line record from a single line.
     pub fn parse(data: &[u8]) -> Result<Self, BreakpadError> {
-        let string = str::from_utf8(data)?;
-        Ok(parsing::line_record_final(string.trim())?)
+        let string = record_line(data)?;
+        Ok(parsing::line_record_final(string)?)
     }
 
     /// Resolves the filename for this record in the file map.
@@ -561,17 +1667,105 @@ impl BreakpadLineRecord {
         file_map.get(&self.file_id).cloned()
     }
 
+    /// Returns the end address of this record, or `None` if it overflows.
+    pub fn end_address(&self) -> Option<u64> {
+        self.address.checked_add(self.size)
+    }
+
     /// Returns the range of addresses covered by this record.
+    ///
+    /// If the end address overflows, the range ends at `u64::MAX`.
     pub fn range(&self) -> Range<u64> {
-        self.address..self.address + self.size
+        self.address..self.end_address().unwrap_or(u64::MAX)
+    }
+}
+
+impl fmt::Display for BreakpadLineRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:x} {:x} {} {}",
+            self.address, self.size, self.line, self.file_id
+        )?;
+
+        if let Some(column) = self.column {
+            write!(f, " {}", column)?;
+        }
+
+        Ok(())
     }
 }
 
+/// Serializes records as a sequence, failing at the first record that cannot be parsed.
+#[cfg(feature = "serde")]
+fn serialize_records<S, I, T>(records: I, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    I: Iterator<Item = Result<T, BreakpadError>>,
+    T: serde::Serialize,
+{
+    use serde::ser::{Error as _, SerializeSeq};
+
+    let mut seq = serializer.serialize_seq(None)?;
+    for record in records {
+        seq.serialize_element(&record.map_err(S::Error::custom)?)?;
+    }
+    seq.end()
+}
+
+/// Serializes the line records of a [`BreakpadFuncRecord`].
+#[cfg(feature = "serde")]
+fn serialize_line_records<S>(lines: &Lines<'_>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let records = BreakpadLineRecords {
+        lines: lines.clone(),
+        finished: false,
+        raw: None,
+    };
+    serialize_records(records, serializer)
+}
+
+/// Serializes the `STACK CFI` records of a [`BreakpadStackCfiRecord`].
+#[cfg(feature = "serde")]
+fn serialize_delta_records<S>(deltas: &Lines<'_>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let records = BreakpadStackCfiDeltaRecords {
+        lines: deltas.clone(),
+        raw: None,
+    };
+    serialize_records(records, serializer)
+}
+
+/// Returns whether the line is a record that ends the line records of a function.
+fn ends_line_block(line: &[u8]) -> bool {
+    line.starts_with(b"FUNC ")
+        || line.starts_with(b"PUBLIC ")
+        || line.starts_with(b"STACK ")
+        || line.starts_with(b"FILE ")
+        || line.starts_with(b"INFO ")
+        || line.starts_with(b"INLINE_ORIGIN ")
+}
+
 /// An iterator over line records in a `BreakpadFunctionRecord`.
 #[derive(Clone, Debug)]
 pub struct BreakpadLineRecords<'d> {
     lines: Lines<'d>,
     finished: bool,
+    raw: Option<&'d [u8]>,
+}
+
+impl<'d> BreakpadLineRecords<'d> {
+    /// Returns the line of the record last returned by this iterator, without the line terminator.
+    ///
+    /// This allows copying records verbatim, including formatting that is lost when parsing.
+    /// Returns `None` before the first record.
+    pub fn raw(&self) -> Option<&'d [u8]> {
+        self.raw
+    }
 }
 
 impl<'d> Iterator for BreakpadLineRecords<'d> {
@@ -584,10 +1778,7 @@ impl<'d> Iterator for BreakpadLineRecords<'d> {
 
         for line in &mut self.lines {
             // Stop parsing LINE records once other expected records are encountered.
-            if line.starts_with(b"FUNC ")
-                || line.starts_with(b"PUBLIC ")
-                || line.starts_with(b"STACK ")
-            {
+            if ends_line_block(line) {
                 break;
             }
 
@@ -598,6 +1789,12 @@ impl<'d> Iterator for BreakpadLineRecords<'d> {
                 continue;
             }
 
+            // Inlinee records are interleaved with line records, but are not line records.
+            if line.starts_with(b"INLINE ") {
+                continue;
+            }
+
+            self.raw = Some(line);
             let record = match BreakpadLineRecord::parse(line) {
                 Ok(record) => record,
                 Err(error) => return Some(Err(error)),
@@ -614,8 +1811,174 @@ impl<'d> Iterator for BreakpadLineRecords<'d> {
     }
 }
 
+/// A single rule of a `STACK CFI` record, such as `.cfa: $rsp 8 +`.
+///
+/// The rule assigns the value of a postfix expression to a register. The expression is validated
+/// syntactically when parsing, but not evaluated.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BreakpadCfiRule<'d> {
+    /// The register the rule applies to, without the trailing colon, such as `.cfa` or `$rip`.
+    pub register: &'d str,
+    /// The postfix expression computing the register's value, such as `$rsp 8 +`.
+    pub expression: &'d str,
+}
+
+/// Returns the byte ranges of all whitespace-separated tokens in a string.
+fn token_ranges(string: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut start = None;
+    string
+        .char_indices()
+        .chain(std::iter::once((string.len(), ' ')))
+        .filter_map(move |(index, c)| match (c.is_whitespace(), start) {
+            (true, Some(token_start)) => {
+                start = None;
+                Some(token_start..index)
+            }
+            (false, None) => {
+                start = Some(index);
+                None
+            }
+            _ => None,
+        })
+}
+
+/// Checks whether a token is a register or constant name in a CFI rule.
+///
+/// Names are either `$` followed by ASCII letters and digits, such as `$rsp`, or an optional `.`
+/// followed by an ASCII letter and more letters and digits, such as `.cfa` or `x29`.
+fn is_cfi_identifier(token: &str) -> bool {
+    let alphanumeric = |s: &str| s.bytes().all(|b| b.is_ascii_alphanumeric());
+    match token.strip_prefix('$') {
+        Some(name) => !name.is_empty() && alphanumeric(name),
+        None => {
+            let name = token.strip_prefix('.').unwrap_or(token);
+            name.starts_with(|c: char| c.is_ascii_alphabetic()) && alphanumeric(name)
+        }
+    }
+}
+
+/// Parses the rules of a `STACK CFI` or `STACK CFI INIT` record.
+///
+/// This is a lightweight syntax check that does not evaluate the rules. It accepts a subset of the
+/// grammar of `symbolic_unwind::evaluator::parsing::rules_complete`:
+///
+///  - Rules start with a register name followed by a colon, such as `.cfa:`, and contain a postfix
+///    expression over names, numbers and operators.
+///  - Names have the form `$[a-zA-Z0-9]+` or `\.?[a-zA-Z][a-zA-Z0-9]*`.
+///  - Numbers are decimal and must fit into 64 bits.
+///  - Operators are the binary `+`, `-`, `*`, `/`, `%`, `@` and the dereference operator `^`.
+///  - A `-` directly preceding a name or number negates it. Negated operands are only allowed as
+///    the second operand of `+`, such as in `.cfa -8 +`.
+///  - Every expression must evaluate to exactly one value.
+///
+/// Unlike the unwinder, this requires all tokens to be separated by whitespace, which Breakpad's
+/// `dump_syms` always does.
+fn parse_cfi_rules(rules: &str) -> Result<Vec<BreakpadCfiRule<'_>>, BreakpadError> {
+    let invalid = |message: &str| {
+        BreakpadError::new(
+            BreakpadErrorKind::InvalidCfiRules,
+            format!("{} in `{}`", message, rules),
+        )
+    };
+
+    // Finishes the current rule. Its expression must leave a single operand on the stack, which
+    // must not be negated.
+    let finish =
+        |register, expression: Option<Range<usize>>, stack: &[bool]| match (expression, stack) {
+            (Some(range), [false]) => Ok(BreakpadCfiRule {
+                register,
+                expression: &rules[range],
+            }),
+            (_, [true]) => Err(invalid("unexpected negative operand")),
+            _ => Err(invalid("unbalanced expression")),
+        };
+
+    let mut parsed = Vec::new();
+    // The register of the current rule and the byte range of its expression.
+    let mut current: Option<(&str, Option<Range<usize>>)> = None;
+    // Whether the operands on the evaluation stack are negated.
+    let mut stack = Vec::new();
+
+    for range in token_ranges(rules) {
+        let token = &rules[range.clone()];
+
+        if let Some(register) = token.strip_suffix(':') {
+            if !is_cfi_identifier(register) {
+                return Err(invalid("invalid register name"));
+            }
+            if let Some((register, expression)) = current.take() {
+                parsed.push(finish(register, expression, &stack)?);
+            }
+            current = Some((register, None));
+            stack.clear();
+            continue;
+        }
+
+        let (_, expression) = current
+            .as_mut()
+            .ok_or_else(|| invalid("expression without register"))?;
+        let start = expression.as_ref().map_or(range.start, |e| e.start);
+        *expression = Some(start..range.end);
+
+        match token {
+            "+" | "-" | "*" | "/" | "%" | "@" => {
+                let second = stack.pop();
+                let first = stack.pop();
+                match (first, second) {
+                    (Some(false), Some(false)) => {}
+                    (Some(false), Some(true)) if token == "+" => {}
+                    (Some(_), Some(_)) => return Err(invalid("unexpected negative operand")),
+                    _ => return Err(invalid("missing operand")),
+                }
+                stack.push(false);
+            }
+            "^" => match stack.last() {
+                Some(false) => {}
+                Some(true) => return Err(invalid("unexpected negative operand")),
+                None => return Err(invalid("missing operand")),
+            },
+            _ => {
+                let (negated, operand) = match token.strip_prefix('-') {
+                    Some(operand) => (true, operand),
+                    None => (false, token),
+                };
+                let is_number =
+                    operand.bytes().all(|b| b.is_ascii_digit()) && operand.parse::<u64>().is_ok();
+                if !is_number && !is_cfi_identifier(operand) {
+                    return Err(invalid("invalid operand"));
+                }
+                stack.push(negated);
+            }
+        }
+    }
+
+    match current {
+        Some((register, expression)) => parsed.push(finish(register, expression, &stack)?),
+        None => return Err(invalid("missing rules")),
+    }
+
+    Ok(parsed)
+}
+
+/// Checks the syntax of the rules of a CFI record at the given address.
+///
+/// The error states the address and the rules, so that the offending record can be found.
+fn check_cfi_rules(address: u64, rules: &str) -> Result<(), BreakpadError> {
+    match parse_cfi_rules(rules) {
+        Ok(_) => Ok(()),
+        Err(error) => Err(BreakpadError::new(
+            BreakpadErrorKind::InvalidCfiRules,
+            match error.source {
+                Some(source) => format!("record at {:#x}: {}", address, source),
+                None => format!("record at {:#x}: invalid rules `{}`", address, rules),
+            },
+        )),
+    }
+}
+
 /// A `STACK CFI` record. Usually associated with a [BreakpadStackCfiRecord].
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BreakpadStackCfiDeltaRecord<'d> {
     /// The address covered by the record.
     pub address: u64,
@@ -627,16 +1990,32 @@ pub struct BreakpadStackCfiDeltaRecord<'d> {
 impl<'d> BreakpadStackCfiDeltaRecord<'d> {
     /// Parses a single `STACK CFI` record.
     pub fn parse(data: &'d [u8]) -> Result<Self, BreakpadError> {
-        let string = str::from_utf8(data)?;
-        Ok(parsing::stack_cfi_delta_record_final(string.trim())?)
+        let string = record_line(data)?;
+        Ok(parsing::stack_cfi_delta_record_final(string)?)
+    }
+
+    /// Parses the unwind program rules of this record.
+    ///
+    /// Fails with [`BreakpadErrorKind::InvalidCfiRules`] if the rules are malformed.
+    pub fn parsed_rules(&self) -> Result<Vec<BreakpadCfiRule<'d>>, BreakpadError> {
+        parse_cfi_rules(self.rules)
+    }
+}
+
+impl fmt::Display for BreakpadStackCfiDeltaRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "STACK CFI {:x} {}", self.address, self.rules)
     }
 }
 
 /// A [call frame information record](https://github.com/google/breakpad/blob/master/docs/symbol_files.md#stack-cfi-records)
 /// for platforms other than Windows x86.
 ///
-/// This bundles together a `STACK CFI INIT` record and its associated `STACK CFI` records.
+/// This bundles together a `STACK CFI INIT` record and its associated `STACK CFI` records. With the
+/// `serde` feature, the record serializes its `STACK CFI` records as a `deltas` sequence, and
+/// serialization fails if one of them is malformed.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BreakpadStackCfiRecord<'d> {
     /// The starting address covered by this record.
     pub start: u64,
@@ -648,26 +2027,78 @@ pub struct BreakpadStackCfiRecord<'d> {
     pub init_rules: &'d str,
 
     /// The `STACK CFI` records belonging to a single `STACK CFI INIT record.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_delta_records"))]
     deltas: Lines<'d>,
 }
 
 impl<'d> BreakpadStackCfiRecord<'d> {
     /// Parses a `STACK CFI INIT` record from a single line.
     pub fn parse(data: &'d [u8]) -> Result<Self, BreakpadError> {
-        let string = str::from_utf8(data)?;
-        Ok(parsing::stack_cfi_record_final(string.trim())?)
+        let string = record_line(data)?;
+        let record = parsing::stack_cfi_record_final(string)?;
+        check_record_range(record.start, record.size)?;
+        Ok(record)
+    }
+
+    /// Creates a `STACK CFI INIT` record without delta records from its address range and rules.
+    ///
+    /// Fails if the address range overflows or the rules are malformed. To encode rules that change
+    /// within the address range, use [`BreakpadStackCfiBuilder`].
+    pub fn from_rules(start: u64, size: u64, rules: &'d str) -> Result<Self, BreakpadError> {
+        check_record_range(start, size)?;
+        parse_cfi_rules(rules)?;
+
+        Ok(Self {
+            start,
+            size,
+            init_rules: rules.trim(),
+            deltas: Lines::default(),
+        })
+    }
+
+    /// Parses the unwind program rules of the `STACK CFI INIT` record.
+    ///
+    /// Fails with [`BreakpadErrorKind::InvalidCfiRules`] if the rules are malformed.
+    pub fn parsed_init_rules(&self) -> Result<Vec<BreakpadCfiRule<'d>>, BreakpadError> {
+        parse_cfi_rules(self.init_rules)
     }
 
     /// Returns an iterator over this record's delta records.
     pub fn deltas(&self) -> BreakpadStackCfiDeltaRecords<'d> {
         BreakpadStackCfiDeltaRecords {
             lines: self.deltas.clone(),
+            raw: None,
         }
     }
 
+    /// Returns the end address of this record, or `None` if it overflows.
+    pub fn end_address(&self) -> Option<u64> {
+        self.start.checked_add(self.size)
+    }
+
     /// Returns the range of addresses covered by this record.
+    ///
+    /// If the end address overflows, the range ends at `u64::MAX`.
     pub fn range(&self) -> Range<u64> {
-        self.start..self.start + self.size
+        self.start..self.end_address().unwrap_or(u64::MAX)
+    }
+
+    /// Converts this record into an owned record with all of its delta records parsed.
+    ///
+    /// Fails if any of the delta records cannot be parsed.
+    pub fn into_owned(self) -> Result<BreakpadStackCfiRecordOwned, BreakpadError> {
+        let mut deltas = self
+            .deltas()
+            .map(|delta| delta.map(|delta| (delta.address, delta.rules.to_owned())))
+            .collect::<Result<Vec<_>, _>>()?;
+        deltas.sort_by_key(|(address, _)| *address);
+
+        Ok(BreakpadStackCfiRecordOwned {
+            start: self.start,
+            size: self.size,
+            init_rules: self.init_rules.to_owned(),
+            deltas,
+        })
     }
 }
 
@@ -679,61 +2110,299 @@ impl<'d> PartialEq for BreakpadStackCfiRecord<'d> {
 
 impl<'d> Eq for BreakpadStackCfiRecord<'d> {}
 
-/// An iterator over stack cfi delta records associated with a particular
-/// [`BreakpadStackCfiRecord`].
-#[derive(Clone, Debug, Default)]
-pub struct BreakpadStackCfiDeltaRecords<'d> {
-    lines: Lines<'d>,
+/// Orders CFI records by start address.
+///
+/// Records at the same address are ordered by their size and initial rules, so that only equal
+/// records compare equal. Delta records are not compared.
+impl PartialOrd for BreakpadStackCfiRecord<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-impl<'d> Iterator for BreakpadStackCfiDeltaRecords<'d> {
-    type Item = Result<BreakpadStackCfiDeltaRecord<'d>, BreakpadError>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(line) = self.lines.next() {
-            if line.starts_with(b"STACK CFI INIT") || !line.starts_with(b"STACK CFI") {
-                self.lines = Lines::default();
-            } else {
-                return Some(BreakpadStackCfiDeltaRecord::parse(line));
-            }
-        }
+impl Ord for BreakpadStackCfiRecord<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.start
+            .cmp(&other.start)
+            .then_with(|| self.size.cmp(&other.size))
+            .then_with(|| self.init_rules.cmp(other.init_rules))
+    }
+}
 
-        None
+/// Formats the `STACK CFI INIT` line of this record, without its delta records.
+impl fmt::Display for BreakpadStackCfiRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "STACK CFI INIT {:x} {:x} {}",
+            self.start, self.size, self.init_rules
+        )
     }
 }
 
-/// Possible types of data held by a [`BreakpadStackWinRecord`], as listed in
-/// <http://msdn.microsoft.com/en-us/library/bc5207xw%28VS.100%29.aspx>. Breakpad only deals with
-/// types 0 (`FPO`) and 4 (`FrameData`).
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub enum BreakpadStackWinRecordType {
-    /// Frame pointer omitted; FPO info available.
-    Fpo = 0,
+/// A `STACK CFI INIT` record together with its parsed `STACK CFI` records, not borrowing from the
+/// symbol file.
+///
+/// This is created by [`BreakpadStackCfiRecord::into_owned`] and is suited for long-lived unwind
+/// indexes that would otherwise parse delta records again on every lookup.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BreakpadStackCfiRecordOwned {
+    /// The starting address covered by this record.
+    pub start: u64,
 
-    /// Kernel Trap frame.
-    Trap = 1,
+    /// The number of bytes covered by this record.
+    pub size: u64,
 
-    /// Kernel Trap frame.
-    Tss = 2,
+    /// The unwind program rules in the `STACK CFI INIT` record.
+    pub init_rules: String,
 
-    /// Standard EBP stack frame.
-    Standard = 3,
+    /// The addresses and rules of the `STACK CFI` records, sorted by address.
+    ///
+    /// Records with the same address retain the order in which they appear in the file.
+    pub deltas: Vec<(u64, String)>,
+}
 
-    /// Frame pointer omitted; Frame data info available.
-    FrameData = 4,
+impl TryFrom<BreakpadStackCfiRecord<'_>> for BreakpadStackCfiRecordOwned {
+    type Error = BreakpadError;
 
-    /// Frame that does not have any debug info.
-    Unknown = -1,
+    fn try_from(record: BreakpadStackCfiRecord<'_>) -> Result<Self, Self::Error> {
+        record.into_owned()
+    }
 }
 
-/// A [Windows stack frame record], used on x86.
-///
-/// Example: `STACK WIN 4 2170 14 1 0 0 0 0 0 1 $eip 4 + ^ = $esp $ebp 8 + = $ebp $ebp ^ =`
-///
-/// [Windows stack frame record]: https://github.com/google/breakpad/blob/master/docs/symbol_files.md#stack-win-records
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct BreakpadStackWinRecord<'d> {
-    /// The type of frame data this record holds.
+impl BreakpadStackCfiRecordOwned {
+    /// Returns the range of addresses covered by this record.
+    ///
+    /// If the end address overflows, the range ends at `u64::MAX`.
+    pub fn range(&self) -> Range<u64> {
+        self.start..self.start.saturating_add(self.size)
+    }
+
+    /// Returns the CFI rules that apply at the given address.
+    ///
+    /// The rules consist of the `STACK CFI INIT` rules, followed by the rules of all delta records
+    /// up to and including the address. Rules for a register that occur later override earlier
+    /// ones. Returns `None` if the address is not covered by this record.
+    pub fn rules_at(&self, address: u64) -> Option<String> {
+        if !self.range().contains(&address) {
+            return None;
+        }
+
+        let end = self.deltas.partition_point(|(delta, _)| *delta <= address);
+        let mut rules = self.init_rules.clone();
+        for (_, delta_rules) in &self.deltas[..end] {
+            rules.push(' ');
+            rules.push_str(delta_rules);
+        }
+
+        Some(rules)
+    }
+}
+
+/// Formats CFI rules with `.cfa` first and `.ra` second, followed by all other registers.
+fn format_cfi_rules<'a, I>(rules: I) -> String
+where
+    I: IntoIterator<Item = (&'a String, &'a String)>,
+{
+    let mut rules: Vec<_> = rules.into_iter().collect();
+    rules.sort_by_key(|(register, _)| match register.as_str() {
+        ".cfa" => (0, ""),
+        ".ra" => (1, ""),
+        other => (2, other),
+    });
+
+    let mut formatted = String::new();
+    for (register, expression) in rules {
+        if !formatted.is_empty() {
+            formatted.push(' ');
+        }
+        formatted.push_str(register);
+        formatted.push_str(": ");
+        formatted.push_str(expression);
+    }
+    formatted
+}
+
+/// Encodes CFI rules computed per address as `STACK CFI INIT` and `STACK CFI` records.
+///
+/// Every row added with [`add_row`](Self::add_row) holds the complete set of rules that applies
+/// from its address on. The first row must start at the beginning of the address range and becomes
+/// the `STACK CFI INIT` record. For every following row, a `STACK CFI` record is written with only
+/// the rules that differ from the rules in effect at that point, and rows without changes are
+/// skipped entirely.
+///
+/// Breakpad cannot express that a register has no rule anymore, so registers missing from a row
+/// keep their previous rule.
+///
+/// # Example
+///
+/// ```
+/// use symbolic_debuginfo::breakpad::BreakpadStackCfiBuilder;
+///
+/// let mut builder = BreakpadStackCfiBuilder::new(0x1000, 0x10);
+/// builder.add_row(0x1000, [(".cfa", "$rsp 8 +"), (".ra", ".cfa -8 + ^")]);
+/// builder.add_row(0x1004, [(".cfa", "$rsp 16 +"), (".ra", ".cfa -8 + ^")]);
+///
+/// assert_eq!(
+///     builder.build().unwrap(),
+///     b"STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^\nSTACK CFI 1004 .cfa: $rsp 16 +\n"
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BreakpadStackCfiBuilder {
+    start: u64,
+    size: u64,
+    rows: BTreeMap<u64, BTreeMap<String, String>>,
+}
+
+impl BreakpadStackCfiBuilder {
+    /// Creates a builder for the given address range.
+    pub fn new(start: u64, size: u64) -> Self {
+        Self {
+            start,
+            size,
+            rows: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the rules that apply from the given address on, as pairs of register and expression.
+    ///
+    /// Adding a row at the same address again replaces the previous row.
+    pub fn add_row<'a, I>(&mut self, address: u64, rules: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let rules = rules
+            .into_iter()
+            .map(|(register, expression)| (register.to_owned(), expression.to_owned()))
+            .collect();
+        self.rows.insert(address, rules);
+        self
+    }
+
+    /// Writes the `STACK CFI INIT` record followed by its `STACK CFI` records.
+    ///
+    /// Every record is terminated by a newline. Fails if the address range overflows, if there are
+    /// no rules at the start address, if a row lies outside the address range, or if any of the
+    /// rules are malformed.
+    pub fn build(&self) -> Result<Vec<u8>, BreakpadError> {
+        check_record_range(self.start, self.size)?;
+        let invalid = |message| BreakpadError::new(BreakpadErrorKind::InvalidCfiRules, message);
+
+        let mut rows = self.rows.iter();
+        let mut current = match rows.next() {
+            Some((&address, rules)) if address == self.start && !rules.is_empty() => rules.clone(),
+            _ => return Err(invalid("missing rules at start address")),
+        };
+
+        let init_rules = format_cfi_rules(&current);
+        parse_cfi_rules(&init_rules)?;
+
+        let mut output = Vec::new();
+        writeln!(
+            output,
+            "STACK CFI INIT {:x} {:x} {}",
+            self.start, self.size, init_rules
+        )
+        .ok();
+
+        for (&address, rules) in rows {
+            // Rows are sorted and the first one is at the start address, so this cannot underflow.
+            if address - self.start >= self.size {
+                return Err(invalid("rules outside of address range"));
+            }
+
+            let changed: BTreeMap<_, _> = rules
+                .iter()
+                .filter(|(register, expression)| current.get(*register) != Some(expression))
+                .collect();
+            if changed.is_empty() {
+                continue;
+            }
+
+            let delta_rules = format_cfi_rules(changed.iter().map(|(r, e)| (*r, *e)));
+            parse_cfi_rules(&delta_rules)?;
+            writeln!(output, "STACK CFI {:x} {}", address, delta_rules).ok();
+
+            for (register, expression) in changed {
+                current.insert(register.clone(), expression.clone());
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// An iterator over stack cfi delta records associated with a particular
+/// [`BreakpadStackCfiRecord`].
+#[derive(Clone, Debug, Default)]
+pub struct BreakpadStackCfiDeltaRecords<'d> {
+    lines: Lines<'d>,
+    raw: Option<&'d [u8]>,
+}
+
+impl<'d> BreakpadStackCfiDeltaRecords<'d> {
+    /// Returns the line of the record last returned by this iterator, without the line terminator.
+    ///
+    /// This allows copying records verbatim, including formatting that is lost when parsing.
+    /// Returns `None` before the first record.
+    pub fn raw(&self) -> Option<&'d [u8]> {
+        self.raw
+    }
+}
+
+impl<'d> Iterator for BreakpadStackCfiDeltaRecords<'d> {
+    type Item = Result<BreakpadStackCfiDeltaRecord<'d>, BreakpadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(line) = self.lines.next() {
+            if line.starts_with(b"STACK CFI INIT") || !line.starts_with(b"STACK CFI") {
+                self.lines = Lines::default();
+            } else {
+                self.raw = Some(line);
+                return Some(BreakpadStackCfiDeltaRecord::parse(line));
+            }
+        }
+
+        None
+    }
+}
+
+/// Possible types of data held by a [`BreakpadStackWinRecord`], as listed in
+/// <http://msdn.microsoft.com/en-us/library/bc5207xw%28VS.100%29.aspx>. Breakpad only deals with
+/// types 0 (`FPO`) and 4 (`FrameData`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BreakpadStackWinRecordType {
+    /// Frame pointer omitted; FPO info available.
+    Fpo = 0,
+
+    /// Kernel Trap frame.
+    Trap = 1,
+
+    /// Kernel Trap frame.
+    Tss = 2,
+
+    /// Standard EBP stack frame.
+    Standard = 3,
+
+    /// Frame pointer omitted; Frame data info available.
+    FrameData = 4,
+
+    /// Frame that does not have any debug info.
+    Unknown = -1,
+}
+
+/// A [Windows stack frame record], used on x86.
+///
+/// Example: `STACK WIN 4 2170 14 1 0 0 0 0 0 1 $eip 4 + ^ = $esp $ebp 8 + = $ebp $ebp ^ =`
+///
+/// [Windows stack frame record]: https://github.com/google/breakpad/blob/master/docs/symbol_files.md#stack-win-records
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BreakpadStackWinRecord<'d> {
+    /// The type of frame data this record holds.
     pub ty: BreakpadStackWinRecordType,
 
     /// The starting address covered by this record, relative to the module's load address.
@@ -774,18 +2443,44 @@ pub struct BreakpadStackWinRecord<'d> {
 impl<'d> BreakpadStackWinRecord<'d> {
     /// Parses a Windows stack record from a single line.
     pub fn parse(data: &'d [u8]) -> Result<Self, BreakpadError> {
-        let string = str::from_utf8(data)?;
-        Ok(parsing::stack_win_record_final(string.trim())?)
+        let string = record_line(data)?;
+        Ok(parsing::stack_win_record_final(string)?)
     }
 
     /// Returns the range of addresses covered by this record.
+    ///
+    /// If the end address overflows, the range ends at `u32::MAX`.
     pub fn code_range(&self) -> Range<u32> {
-        self.code_start..self.code_start + self.code_size
+        self.code_start..self.code_start.saturating_add(self.code_size)
+    }
+}
+
+impl fmt::Display for BreakpadStackWinRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "STACK WIN {} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {:x}",
+            self.ty as i32,
+            self.code_start,
+            self.code_size,
+            self.prolog_size,
+            self.epilog_size,
+            self.params_size,
+            self.saved_regs_size,
+            self.locals_size,
+            self.max_stack_size,
+        )?;
+
+        match self.program_string {
+            Some(program_string) => write!(f, " 1 {}", program_string),
+            None => write!(f, " 0 {}", self.uses_base_pointer as u8),
+        }
     }
 }
 
 /// Stack frame information record used for stack unwinding and stackwalking.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum BreakpadStackRecord<'d> {
     /// CFI stack record, used for all platforms other than Windows x86.
     Cfi(BreakpadStackCfiRecord<'d>),
@@ -796,8 +2491,21 @@ pub enum BreakpadStackRecord<'d> {
 impl<'d> BreakpadStackRecord<'d> {
     /// Parses a stack frame information record from a single line.
     pub fn parse(data: &'d [u8]) -> Result<Self, BreakpadError> {
-        let string = str::from_utf8(data)?;
-        Ok(parsing::stack_record_final(string.trim())?)
+        let string = record_line(data)?;
+        let record = parsing::stack_record_final(string)?;
+        if let Self::Cfi(ref cfi) = record {
+            check_record_range(cfi.start, cfi.size)?;
+        }
+        Ok(record)
+    }
+}
+
+impl fmt::Display for BreakpadStackRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cfi(record) => record.fmt(f),
+            Self::Win(record) => record.fmt(f),
+        }
     }
 }
 
@@ -806,6 +2514,9 @@ impl<'d> BreakpadStackRecord<'d> {
 pub struct BreakpadStackRecords<'d> {
     lines: Lines<'d>,
     finished: bool,
+    offset: Option<usize>,
+    raw: Option<&'d [u8]>,
+    validate_rules: bool,
 }
 
 impl<'d> BreakpadStackRecords<'d> {
@@ -814,8 +2525,51 @@ impl<'d> BreakpadStackRecords<'d> {
         Self {
             lines: Lines::new(data),
             finished: false,
+            offset: None,
+            raw: None,
+            validate_rules: false,
         }
     }
+
+    /// Checks the syntax of CFI rules as records are yielded.
+    ///
+    /// For every `STACK CFI INIT` record, the rules of the record and all of its `STACK CFI` delta
+    /// records are checked. If any of them are malformed, an error of kind
+    /// [`BreakpadErrorKind::InvalidCfiRules`] is yielded instead of the record, stating the address
+    /// of the offending record and its rules. This only validates the syntax and does not evaluate
+    /// the rules. The program strings of `STACK WIN` records are not checked.
+    pub fn validate_rules(self) -> Self {
+        Self {
+            validate_rules: true,
+            ..self
+        }
+    }
+
+    /// Returns the byte offset of the record last returned by this iterator.
+    ///
+    /// The offset refers to the data this iterator was created from, so the record can be parsed
+    /// again from the data at this offset. Returns `None` before the first record.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Returns the line of the record last returned by this iterator, without the line terminator.
+    ///
+    /// This allows copying records verbatim, including formatting that is lost when parsing.
+    /// Returns `None` before the first record.
+    pub fn raw(&self) -> Option<&'d [u8]> {
+        self.raw
+    }
+
+    /// Checks the rules of a `STACK CFI INIT` record and all of its delta records.
+    fn check_rules(record: &BreakpadStackCfiRecord<'d>) -> Result<(), BreakpadError> {
+        check_cfi_rules(record.start, record.init_rules)?;
+        for delta in record.deltas() {
+            let delta = delta?;
+            check_cfi_rules(delta.address, delta.rules)?;
+        }
+        Ok(())
+    }
 }
 
 impl<'d> Iterator for BreakpadStackRecords<'d> {
@@ -826,16 +2580,27 @@ impl<'d> Iterator for BreakpadStackRecords<'d> {
             return None;
         }
 
-        while let Some(line) = self.lines.next() {
+        while let Some((offset, line)) = self.lines.next_with_offset() {
             if line.starts_with(b"STACK WIN") {
+                self.offset = Some(offset);
+                self.raw = Some(line);
                 return Some(BreakpadStackRecord::parse(line));
             }
 
             if line.starts_with(b"STACK CFI INIT") {
-                return Some(BreakpadStackCfiRecord::parse(line).map(|mut r| {
+                self.offset = Some(offset);
+                self.raw = Some(line);
+                let record = BreakpadStackCfiRecord::parse(line).map(|mut r| {
                     r.deltas = self.lines.clone();
-                    BreakpadStackRecord::Cfi(r)
-                }));
+                    r
+                });
+
+                return Some(match record {
+                    Ok(record) if self.validate_rules => {
+                        Self::check_rules(&record).map(|()| BreakpadStackRecord::Cfi(record))
+                    }
+                    record => record.map(BreakpadStackRecord::Cfi),
+                });
             }
         }
 
@@ -844,1140 +2609,8296 @@ impl<'d> Iterator for BreakpadStackRecords<'d> {
     }
 }
 
-/// A Breakpad object file.
-///
-/// To process minidump crash reports without having to understand all sorts of native symbol
-/// formats, the Breakpad processor uses a text-based symbol file format. It comprises records
-/// describing the object file, functions and lines, public symbols, as well as unwind information
-/// for stackwalking.
-///
-/// > The platform-specific symbol dumping tools parse the debugging information the compiler
-/// > provides (whether as DWARF or STABS sections in an ELF file or as stand-alone PDB files), and
-/// > write that information back out in the Breakpad symbol file format. This format is much
-/// > simpler and less detailed than compiler debugging information, and values legibility over
-/// > compactness.
-///
-/// The full documentation resides [here](https://chromium.googlesource.com/breakpad/breakpad/+/refs/heads/master/docs/symbol_files.md).
-pub struct BreakpadObject<'data> {
-    id: DebugId,
-    arch: Arch,
-    module: BreakpadModuleRecord<'data>,
-    data: &'data [u8],
-}
+/// An inconsistency in a Breakpad object reported by [`BreakpadObject::validate`].
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BreakpadValidationIssue {
+    /// A line record references a file id that has no corresponding `FILE` record.
+    UnknownFileId {
+        /// The address of the function containing the line record.
+        function_address: u64,
+        /// The address of the line record.
+        line_address: u64,
+        /// The file id that could not be resolved.
+        file_id: u64,
+    },
 
-impl<'data> BreakpadObject<'data> {
-    /// Tests whether the buffer could contain a Breakpad object.
-    pub fn test(data: &[u8]) -> bool {
-        data.starts_with(b"MODULE ")
-    }
+    /// A record appears after the point where the Breakpad format expects it to end.
+    ///
+    /// Such records are skipped by the default record iterators and can only be found with their
+    /// full-scan counterparts, such as [`BreakpadObject::public_records_full`].
+    OutOfOrderRecord {
+        /// The one-based line number of the record.
+        line_number: usize,
+        /// The record type, such as `"PUBLIC"`.
+        record: &'static str,
+    },
 
-    /// Tries to parse a Breakpad object from the given slice.
-    pub fn parse(data: &'data [u8]) -> Result<Self, BreakpadError> {
-        // Ensure that we do not read the entire file at once.
-        let header = if data.len() > BREAKPAD_HEADER_CAP {
-            match str::from_utf8(&data[..BREAKPAD_HEADER_CAP]) {
-                Ok(_) => &data[..BREAKPAD_HEADER_CAP],
-                Err(e) => match e.error_len() {
-                    None => &data[..e.valid_up_to()],
-                    Some(_) => return Err(e.into()),
-                },
-            }
-        } else {
-            data
-        };
+    /// The rules of a `STACK CFI` or `STACK CFI INIT` record cannot be parsed.
+    InvalidCfiRules {
+        /// The one-based line number of the record.
+        line_number: usize,
+    },
 
-        let first_line = header.split(|b| *b == b'\n').next().unwrap_or_default();
-        let module = BreakpadModuleRecord::parse(first_line)?;
+    /// A `FUNC` or `PUBLIC` record has flags that are not known to this parser.
+    UnknownFlags {
+        /// The one-based line number of the record.
+        line_number: usize,
+        /// The unknown flags in the order they appear.
+        flags: Vec<char>,
+    },
 
-        Ok(BreakpadObject {
-            id: module
-                .id
-                .parse()
-                .map_err(|_| BreakpadErrorKind::InvalidModuleId)?,
-            arch: module
-                .arch
-                .parse()
-                .map_err(|_| BreakpadErrorKind::InvalidArchitecture)?,
-            module,
-            data,
-        })
-    }
+    /// A line record covers addresses outside of the function containing it.
+    ///
+    /// This includes line records starting before the function and line records extending past
+    /// its end. Functions with a size of zero are not checked.
+    LineOutOfRange {
+        /// The address of the function containing the line record.
+        function_address: u64,
+        /// The address of the line record.
+        line_address: u64,
+        /// The size of the line record.
+        line_size: u64,
+    },
 
-    /// The container file format, which is always `FileFormat::Breakpad`.
-    pub fn file_format(&self) -> FileFormat {
-        FileFormat::Breakpad
-    }
+    /// A line does not match any known record type.
+    ///
+    /// Such lines are skipped by all record iterators. See [`BreakpadObject::unknown_records`].
+    UnknownRecord {
+        /// The one-based line number of the record.
+        line_number: usize,
+    },
 
-    /// The code identifier of this object.
-    pub fn code_id(&self) -> Option<CodeId> {
-        for result in self.info_records().flatten() {
-            if let BreakpadInfoRecord::CodeId { code_id, .. } = result {
-                if !code_id.is_empty() {
-                    return Some(CodeId::new(code_id.into()));
-                }
+    /// A record cannot be parsed.
+    ///
+    /// The record iterators return an error for such records.
+    InvalidRecord {
+        /// The one-based line number of the record.
+        line_number: usize,
+        /// The record type, such as `"FUNC"`, or `"line"` for line records.
+        record: &'static str,
+    },
+}
+
+impl fmt::Display for BreakpadValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFileId {
+                function_address,
+                line_address,
+                file_id,
+            } => write!(
+                f,
+                "line record at {:#x} in function at {:#x} references unknown file id {}",
+                line_address, function_address, file_id
+            ),
+            Self::OutOfOrderRecord {
+                line_number,
+                record,
+            } => write!(
+                f,
+                "{} record on line {} is out of order",
+                record, line_number
+            ),
+            Self::InvalidCfiRules { line_number } => {
+                write!(
+                    f,
+                    "STACK CFI record on line {} has invalid rules",
+                    line_number
+                )
+            }
+            Self::UnknownFlags { line_number, flags } => {
+                let flags: String = flags.iter().collect();
+                write!(
+                    f,
+                    "record on line {} has unknown flags {:?}",
+                    line_number, flags
+                )
             }
+            Self::LineOutOfRange {
+                function_address,
+                line_address,
+                line_size,
+            } => write!(
+                f,
+                "line record at {:#x} with size {:#x} exceeds function at {:#x}",
+                line_address, line_size, function_address
+            ),
+            Self::UnknownRecord { line_number } => {
+                write!(f, "record on line {} has an unknown type", line_number)
+            }
+            Self::InvalidRecord {
+                line_number,
+                record,
+            } => write!(f, "{} record on line {} is invalid", record, line_number),
         }
-
-        None
     }
+}
 
-    /// The debug information identifier of this object.
-    pub fn debug_id(&self) -> DebugId {
-        self.id
+/// A line record referencing a file id without a corresponding `FILE` record.
+///
+/// Returned by [`BreakpadObject::check_file_id_consistency`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BreakpadFileIdError {
+    /// The file id that could not be resolved.
+    pub file_id: u64,
+    /// The address of the function containing the line record.
+    pub func_address: u64,
+    /// The source line number of the line record.
+    pub line_number: u64,
+}
+
+impl BreakpadFileIdError {
+    /// Checks that the file id of a line record has a corresponding entry in the file map.
+    ///
+    /// This is shared by [`BreakpadObject::check_file_id_consistency`] and
+    /// [`BreakpadObject::validate`].
+    fn check(
+        file_map: &BreakpadFileMap<'_>,
+        func_address: u64,
+        line: &BreakpadLineRecord,
+    ) -> Option<Self> {
+        if file_map.contains_key(&line.file_id) {
+            return None;
+        }
+
+        Some(Self {
+            file_id: line.file_id,
+            func_address,
+            line_number: line.line,
+        })
     }
+}
 
-    /// The CPU architecture of this object.
-    pub fn arch(&self) -> Arch {
-        self.arch
+impl fmt::Display for BreakpadFileIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {} in function at {:#x} references unknown file id {}",
+            self.line_number, self.func_address, self.file_id
+        )
     }
+}
 
-    /// The debug file name of this object.
+/// A range of instructions within a function that is not covered by any stack record.
+///
+/// Returned by [`BreakpadObject::verify_cfi_coverage`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BreakpadCfiGap {
+    /// The start address of the function containing the gap.
+    pub func_addr: u64,
+    /// The size of the function containing the gap.
+    pub func_size: u64,
+    /// The start address of the uncovered range.
+    pub gap_start: u64,
+    /// The size of the uncovered range in bytes.
+    pub gap_size: u64,
+}
+
+/// An iterator over `STACK CFI` records in a Breakpad object.
+#[derive(Clone, Debug)]
+pub struct BreakpadStackCfiRecords<'d> {
+    lines: Lines<'d>,
+    finished: bool,
+    offset: Option<usize>,
+    record_lines: Lines<'d>,
+    raw: Option<&'d [u8]>,
+}
+
+impl<'d> BreakpadStackCfiRecords<'d> {
+    /// Returns the byte offset of the record last returned by this iterator.
     ///
-    /// This is the name of the original debug file that was used to create the Breakpad file. On
-    /// Windows, this will have a `.pdb` extension, on other platforms that name is likely
-    /// equivalent to the name of the code file (shared library or executable).
-    pub fn name(&self) -> &'data str {
-        self.module.name
+    /// The offset refers to the data of the Breakpad object, so the record can be parsed again
+    /// from [`BreakpadObject::data`] at this offset. Returns `None` before the first record.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
     }
 
-    /// The kind of this object.
-    pub fn kind(&self) -> ObjectKind {
-        ObjectKind::Debug
+    /// Returns the `STACK CFI INIT` line of the record last returned by this iterator, without the
+    /// line terminator.
+    ///
+    /// This allows copying records verbatim, including formatting that is lost when parsing. Delta
+    /// records can be copied through [`BreakpadStackCfiDeltaRecords::raw`]. Returns `None` before
+    /// the first record.
+    pub fn raw(&self) -> Option<&'d [u8]> {
+        self.raw
     }
 
-    /// The address at which the image prefers to be loaded into memory.
+    /// Returns the byte range of the record last returned by this iterator.
     ///
-    /// When Breakpad symbols are written, all addresses are rebased relative to the load address.
-    /// Since the original load address is not stored in the file, it is assumed as zero.
-    pub fn load_address(&self) -> u64 {
-        0 // Breakpad rebases all addresses when dumping symbols
+    /// The range covers the `STACK CFI INIT` line and all of its delta records, including the
+    /// trailing line break. It refers to the data of the Breakpad object, so the record can be
+    /// copied verbatim from [`BreakpadObject::data`]. Returns `None` before the first record.
+    pub fn byte_range(&self) -> Option<Range<usize>> {
+        let start = self.offset?;
+        let mut lines = self.record_lines.clone();
+        while let Some((offset, line)) = lines.next_with_offset() {
+            // Mirrors `BreakpadStackCfiDeltaRecords::next`.
+            if line.starts_with(b"STACK CFI INIT") || !line.starts_with(b"STACK CFI") {
+                return Some(start..offset);
+            }
+        }
+        Some(start..lines.position())
     }
+}
 
-    /// Determines whether this object exposes a public symbol table.
-    pub fn has_symbols(&self) -> bool {
-        self.public_records().next().is_some()
-    }
+impl<'d> Iterator for BreakpadStackCfiRecords<'d> {
+    type Item = Result<BreakpadStackCfiRecord<'d>, BreakpadError>;
 
-    /// Returns an iterator over symbols in the public symbol table.
-    pub fn symbols(&self) -> BreakpadSymbolIterator<'data> {
-        BreakpadSymbolIterator {
-            records: self.public_records(),
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
         }
-    }
-
-    /// Returns an ordered map of symbols in the symbol table.
-    pub fn symbol_map(&self) -> SymbolMap<'data> {
-        self.symbols().collect()
-    }
 
-    /// Determines whether this object contains debug information.
-    pub fn has_debug_info(&self) -> bool {
-        self.func_records().next().is_some()
-    }
+        while let Some((offset, line)) = self.lines.next_with_offset() {
+            if line.starts_with(b"STACK CFI INIT") {
+                self.offset = Some(offset);
+                self.record_lines = self.lines.clone();
+                self.raw = Some(line);
+                return Some(BreakpadStackCfiRecord::parse(line).map(|mut r| {
+                    r.deltas = self.lines.clone();
+                    r
+                }));
+            }
+        }
 
-    /// Constructs a debugging session.
-    ///
-    /// A debugging session loads certain information from the object file and creates caches for
-    /// efficient access to various records in the debug information. Since this can be quite a
-    /// costly process, try to reuse the debugging session as long as possible.
-    ///
-    /// Constructing this session will also work if the object does not contain debugging
-    /// information, in which case the session will be a no-op. This can be checked via
-    /// [`has_debug_info`](struct.BreakpadObject.html#method.has_debug_info).
-    pub fn debug_session(&self) -> Result<BreakpadDebugSession<'data>, BreakpadError> {
-        Ok(BreakpadDebugSession {
-            file_map: self.file_map(),
-            func_records: self.func_records(),
-        })
+        self.finished = true;
+        None
     }
+}
 
-    /// Determines whether this object contains stack unwinding information.
-    pub fn has_unwind_info(&self) -> bool {
-        self.stack_records().next().is_some()
-    }
+/// An iterator over `STACK WIN` records in a Breakpad object.
+#[derive(Clone, Debug)]
+pub struct BreakpadStackWinRecords<'d> {
+    lines: Lines<'d>,
+    finished: bool,
+    raw: Option<&'d [u8]>,
+}
 
-    /// Determines whether this object contains embedded source.
-    pub fn has_sources(&self) -> bool {
-        false
+impl<'d> BreakpadStackWinRecords<'d> {
+    /// Returns the line of the record last returned by this iterator, without the line terminator.
+    ///
+    /// This allows copying records verbatim, including formatting that is lost when parsing.
+    /// Returns `None` before the first record.
+    pub fn raw(&self) -> Option<&'d [u8]> {
+        self.raw
     }
+}
 
-    /// Determines whether this object is malformed and was only partially parsed
-    pub fn is_malformed(&self) -> bool {
-        false
-    }
+impl<'d> Iterator for BreakpadStackWinRecords<'d> {
+    type Item = Result<BreakpadStackWinRecord<'d>, BreakpadError>;
 
-    /// Returns an iterator over info records.
-    pub fn info_records(&self) -> BreakpadInfoRecords<'data> {
-        BreakpadInfoRecords {
-            lines: Lines::new(self.data),
-            finished: false,
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
         }
-    }
 
-    /// Returns an iterator over file records.
-    pub fn file_records(&self) -> BreakpadFileRecords<'data> {
-        BreakpadFileRecords {
-            lines: Lines::new(self.data),
-            finished: false,
+        for line in &mut self.lines {
+            if line.starts_with(b"STACK WIN") {
+                self.raw = Some(line);
+                return Some(BreakpadStackWinRecord::parse(line));
+            }
         }
-    }
 
-    /// Returns a map for file name lookups by id.
-    pub fn file_map(&self) -> BreakpadFileMap<'data> {
-        self.file_records()
-            .filter_map(Result::ok)
-            .map(|file| (file.id, file.name))
-            .collect()
+        self.finished = true;
+        None
     }
+}
 
-    /// Returns an iterator over public symbol records.
-    pub fn public_records(&self) -> BreakpadPublicRecords<'data> {
-        BreakpadPublicRecords {
-            lines: Lines::new(self.data),
-            finished: false,
-        }
-    }
+/// Determines how [`BreakpadObject::merge_with_strategy`] resolves conflicting records.
+///
+/// Two records conflict if they start at the same address but differ in any way, including their
+/// line or delta records. Identical records are always merged into one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BreakpadMergeStrategy {
+    /// Keeps the record of the first object.
+    KeepFirst,
+    /// Keeps the record of the second object.
+    KeepSecond,
+    /// Fails with [`BreakpadErrorKind::MergeConflict`].
+    Error,
+    /// Combines the line records of conflicting `FUNC` records.
+    ///
+    /// The `FUNC` record itself is taken from the first object. All other conflicting records keep
+    /// the record of the first object, like [`KeepFirst`](Self::KeepFirst).
+    MergeLines,
+}
 
-    /// Returns an iterator over function records.
-    pub fn func_records(&self) -> BreakpadFuncRecords<'data> {
-        BreakpadFuncRecords {
-            lines: Lines::new(self.data),
-            finished: false,
+/// A record rendered into its canonical form, along with its nested line or delta records.
+type MergeBlock = (String, Vec<(u64, String)>);
+
+/// Inserts a record of the second object into the records of the first one.
+///
+/// If `nested` is set, [`BreakpadMergeStrategy::MergeLines`] combines nested records of conflicting
+/// blocks. Otherwise, it keeps the first block.
+fn merge_block(
+    blocks: &mut BTreeMap<u64, MergeBlock>,
+    address: u64,
+    block: MergeBlock,
+    strategy: BreakpadMergeStrategy,
+    nested: bool,
+) -> Result<(), BreakpadError> {
+    use std::collections::btree_map::Entry;
+
+    let mut entry = match blocks.entry(address) {
+        Entry::Vacant(entry) => {
+            entry.insert(block);
+            return Ok(());
         }
-    }
+        Entry::Occupied(entry) if *entry.get() == block => return Ok(()),
+        Entry::Occupied(entry) => entry,
+    };
 
-    /// Returns an iterator over stack frame records.
-    pub fn stack_records(&self) -> BreakpadStackRecords<'data> {
-        BreakpadStackRecords {
-            lines: Lines::new(self.data),
-            finished: false,
+    match strategy {
+        BreakpadMergeStrategy::KeepSecond => {
+            entry.insert(block);
+        }
+        BreakpadMergeStrategy::Error => {
+            return Err(BreakpadErrorKind::MergeConflict { address }.into());
         }
+        BreakpadMergeStrategy::MergeLines if nested => {
+            let lines = &mut entry.get_mut().1;
+            lines.extend(block.1);
+            lines.sort();
+            lines.dedup();
+        }
+        BreakpadMergeStrategy::KeepFirst | BreakpadMergeStrategy::MergeLines => {}
     }
 
-    /// Returns the raw data of the Breakpad file.
-    pub fn data(&self) -> &'data [u8] {
-        self.data
-    }
+    Ok(())
 }
 
-impl fmt::Debug for BreakpadObject<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("BreakpadObject")
-            .field("code_id", &self.code_id())
-            .field("debug_id", &self.debug_id())
-            .field("arch", &self.arch())
-            .field("name", &self.name())
-            .field("has_symbols", &self.has_symbols())
-            .field("has_debug_info", &self.has_debug_info())
-            .field("has_unwind_info", &self.has_unwind_info())
-            .field("is_malformed", &self.is_malformed())
-            .finish()
-    }
+/// Assigns the id of a `FILE` or `INLINE_ORIGIN` record in the output of
+/// [`BreakpadObject::merge_with_strategy`].
+///
+/// Records with the same name share an id. Otherwise, a record keeps its original id unless that id
+/// is already taken, in which case it receives the next free id.
+fn merge_id<'d, T>(
+    records: &mut BTreeMap<u64, T>,
+    names: &mut HashMap<&'d str, u64>,
+    id: u64,
+    name: &'d str,
+    record: T,
+) -> u64 {
+    let id = match names.get(name) {
+        Some(&id) => id,
+        None if records.contains_key(&id) => records.keys().next_back().map_or(0, |id| id + 1),
+        None => id,
+    };
+    records.entry(id).or_insert(record);
+    names.entry(name).or_insert(id);
+    id
 }
 
-impl<'slf, 'data: 'slf> AsSelf<'slf> for BreakpadObject<'data> {
-    type Ref = BreakpadObject<'slf>;
-
-    fn as_self(&'slf self) -> &Self::Ref {
-        self
-    }
+/// Parses an `INLINE_ORIGIN` record into its id and name.
+fn parse_inline_origin(line: &[u8]) -> Result<(u64, &str), BreakpadError> {
+    let record = record_line(line)?["INLINE_ORIGIN".len()..].trim_start();
+    let (id, name) = match record.find(char::is_whitespace) {
+        Some(index) => (&record[..index], record[index..].trim_start()),
+        None => (record, ""),
+    };
+    let id = id.parse().map_err(|_| {
+        BreakpadError::new(BreakpadErrorKind::Parse(""), "invalid INLINE_ORIGIN record")
+    })?;
+    Ok((id, name))
 }
 
-impl<'data> Parse<'data> for BreakpadObject<'data> {
-    type Error = BreakpadError;
-
-    fn test(data: &[u8]) -> bool {
-        Self::test(data)
-    }
-
-    fn parse(data: &'data [u8]) -> Result<Self, BreakpadError> {
-        Self::parse(data)
-    }
+/// Rewrites the call site file id and origin id of an `INLINE` record.
+///
+/// The record has the form `INLINE <depth> <call_site_line> <call_site_file_id> <origin_id>
+/// [<address> <size>]+`. Returns the address of its first range along with the rewritten record.
+/// Ids missing from the maps are kept.
+fn remap_inline_record(
+    line: &[u8],
+    file_ids: &HashMap<u64, u64>,
+    origin_ids: &HashMap<u64, u64>,
+) -> Result<(u64, String), BreakpadError> {
+    let invalid = || BreakpadError::new(BreakpadErrorKind::Parse(""), "invalid INLINE record");
+    let remap = |field: &str, ids: &HashMap<u64, u64>| {
+        let id = field.parse::<u64>().map_err(|_| invalid())?;
+        Ok::<_, BreakpadError>(ids.get(&id).copied().unwrap_or(id).to_string())
+    };
+
+    let mut fields = record_line(line)?.split_whitespace().collect::<Vec<_>>();
+    if fields.len() < 7 || fields.len() % 2 == 0 {
+        return Err(invalid());
+    }
+
+    let address = u64::from_str_radix(fields[5], 16).map_err(|_| invalid())?;
+    let file_id = remap(fields[3], file_ids)?;
+    let origin_id = remap(fields[4], origin_ids)?;
+    fields[3] = &file_id;
+    fields[4] = &origin_id;
+    Ok((address, fields.join(" ")))
 }
 
-impl<'data: 'object, 'object> ObjectLike<'data, 'object> for BreakpadObject<'data> {
-    type Error = BreakpadError;
-    type Session = BreakpadDebugSession<'data>;
-    type SymbolIterator = BreakpadSymbolIterator<'data>;
+/// Logs a record that is skipped by a [`LenientBreakpadRecords`] iterator.
+#[cfg(feature = "log")]
+fn warn_skipped(error: &BreakpadError) {
+    log::warn!("skipping invalid breakpad record: {}", error);
+}
 
-    fn file_format(&self) -> FileFormat {
-        self.file_format()
-    }
+#[cfg(not(feature = "log"))]
+fn warn_skipped(_error: &BreakpadError) {}
 
-    fn code_id(&self) -> Option<CodeId> {
-        self.code_id()
-    }
+/// An iterator over Breakpad records that skips records failing to parse.
+///
+/// This is returned by the `lenient` method of all record iterators, such as
+/// [`BreakpadFuncRecords::lenient`], and yields records without a `Result` wrapper. With the `log`
+/// feature, every skipped record is logged as a warning.
+#[derive(Clone, Debug)]
+pub struct LenientBreakpadRecords<I> {
+    records: I,
+}
 
-    fn debug_id(&self) -> DebugId {
-        self.debug_id()
-    }
+impl<I, T> Iterator for LenientBreakpadRecords<I>
+where
+    I: Iterator<Item = Result<T, BreakpadError>>,
+{
+    type Item = T;
 
-    fn arch(&self) -> Arch {
-        self.arch()
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records
+            .find_map(|result| result.map_err(|error| warn_skipped(&error)).ok())
     }
+}
 
-    fn kind(&self) -> ObjectKind {
-        self.kind()
-    }
+macro_rules! impl_lenient {
+    ($records:ident, $lenient:ident, $name:literal) => {
+        #[doc = concat!("A lenient iterator over ", $name, " records, returned by [`", stringify!($records), "::lenient`].")]
+        pub type $lenient<'d> = LenientBreakpadRecords<$records<'d>>;
+
+        impl<'d> $records<'d> {
+            #[doc = concat!("Returns an iterator that skips ", $name, " records failing to parse.")]
+            ///
+            /// With the `log` feature, every skipped record is logged as a warning.
+            pub fn lenient(self) -> $lenient<'d> {
+                LenientBreakpadRecords { records: self }
+            }
+        }
+    };
+}
 
-    fn load_address(&self) -> u64 {
-        self.load_address()
-    }
+impl_lenient!(BreakpadInfoRecords, LenientBreakpadInfoRecords, "info");
+impl_lenient!(BreakpadFileRecords, LenientBreakpadFileRecords, "file");
+impl_lenient!(
+    BreakpadPublicRecords,
+    LenientBreakpadPublicRecords,
+    "public symbol"
+);
+impl_lenient!(BreakpadFuncRecords, LenientBreakpadFuncRecords, "function");
+impl_lenient!(BreakpadLineRecords, LenientBreakpadLineRecords, "line");
+impl_lenient!(
+    BreakpadStackCfiDeltaRecords,
+    LenientBreakpadStackCfiDeltaRecords,
+    "stack cfi delta"
+);
+impl_lenient!(
+    BreakpadStackRecords,
+    LenientBreakpadStackRecords,
+    "stack frame"
+);
+impl_lenient!(
+    BreakpadStackCfiRecords,
+    LenientBreakpadStackCfiRecords,
+    "`STACK CFI`"
+);
+impl_lenient!(
+    BreakpadStackWinRecords,
+    LenientBreakpadStackWinRecords,
+    "`STACK WIN`"
+);
+
+/// Returns whether the line starts with the prefix of a known record type.
+///
+/// Line records have no prefix and are therefore not included.
+fn is_known_record(line: &[u8]) -> bool {
+    ends_line_block(line) || line.starts_with(b"MODULE ") || line.starts_with(b"INLINE ")
+}
 
-    fn has_symbols(&self) -> bool {
-        self.has_symbols()
-    }
+/// Returns whether the line looks like a line record, which starts with a hexadecimal address.
+fn is_line_record(line: &[u8]) -> bool {
+    let address = line.split(|&b| b == b' ').next().unwrap_or_default();
+    !address.is_empty() && address.iter().all(u8::is_ascii_hexdigit)
+}
 
-    fn symbols(&self) -> Self::SymbolIterator {
-        self.symbols()
-    }
+/// An iterator over lines that do not match any known record type.
+///
+/// Returned by [`BreakpadObject::unknown_records`].
+#[derive(Clone, Debug)]
+pub struct BreakpadUnknownRecords<'d> {
+    lines: std::iter::Enumerate<Lines<'d>>,
+    in_func: bool,
+}
 
-    fn symbol_map(&self) -> SymbolMap<'data> {
-        self.symbol_map()
-    }
+impl<'d> Iterator for BreakpadUnknownRecords<'d> {
+    type Item = (usize, &'d str);
 
-    fn has_debug_info(&self) -> bool {
-        self.has_debug_info()
-    }
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, line) in &mut self.lines {
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
 
-    fn debug_session(&self) -> Result<Self::Session, Self::Error> {
-        self.debug_session()
-    }
+            if is_known_record(line) {
+                self.in_func = line.starts_with(b"FUNC ") || line.starts_with(b"INLINE ");
+                continue;
+            }
 
-    fn has_unwind_info(&self) -> bool {
-        self.has_unwind_info()
-    }
+            if self.in_func && is_line_record(line) {
+                continue;
+            }
 
-    fn has_sources(&self) -> bool {
-        self.has_sources()
-    }
+            let line = match str::from_utf8(line) {
+                Ok(line) => line,
+                Err(error) => str::from_utf8(&line[..error.valid_up_to()]).unwrap_or_default(),
+            };
+            return Some((index + 1, line.trim()));
+        }
 
-    fn is_malformed(&self) -> bool {
-        self.is_malformed()
+        None
     }
 }
 
-/// An iterator over symbols in the Breakpad object.
+/// Unwind information of a Breakpad object, indexed by address.
 ///
-/// Returned by [`BreakpadObject::symbols`](struct.BreakpadObject.html#method.symbols).
-pub struct BreakpadSymbolIterator<'data> {
-    records: BreakpadPublicRecords<'data>,
+/// This is created by [`BreakpadObject::unwind_session`] and resolves the stack records that apply
+/// to an instruction address. The returned rules and records can be passed to an evaluator for
+/// Breakpad unwind expressions, such as the one in `symbolic-unwind`.
+///
+/// This requires the `unwind` feature.
+#[cfg(feature = "unwind")]
+#[derive(Clone, Debug, Default)]
+pub struct BreakpadUnwindSession<'data> {
+    /// All `STACK CFI INIT` records, sorted by start address.
+    cfi_records: Vec<BreakpadStackCfiRecord<'data>>,
+    /// The highest end address of `cfi_records` up to and including the same index.
+    cfi_max_ends: Vec<u64>,
+    /// All `STACK WIN` records, sorted by start address.
+    win_records: Vec<BreakpadStackWinRecord<'data>>,
 }
 
-impl<'data> Iterator for BreakpadSymbolIterator<'data> {
-    type Item = Symbol<'data>;
+#[cfg(feature = "unwind")]
+impl<'data> BreakpadUnwindSession<'data> {
+    /// Returns the CFI rules that apply at the given address.
+    ///
+    /// The rules consist of the `STACK CFI INIT` record covering the address, followed by the rules
+    /// of all its `STACK CFI` delta records up to and including the address, in the order they
+    /// appear in the file. Rules for a register that occur later override earlier ones, so the
+    /// string can be passed as a whole to `Evaluator::add_cfi_rules_string` in `symbolic-unwind`.
+    ///
+    /// If records are nested, the innermost record covering the address is used. Returns `None` if
+    /// no `STACK CFI INIT` record covers the address. Delta records that fail to parse are skipped.
+    pub fn cfi_rules_for_address(&self, address: u64) -> Option<Cow<'data, str>> {
+        let index = self
+            .cfi_records
+            .partition_point(|record| record.start <= address);
+
+        // Records further back cannot cover the address once the running maximum end is below it.
+        let record = self.cfi_records[..index]
+            .iter()
+            .zip(&self.cfi_max_ends[..index])
+            .rev()
+            .take_while(|(_, &max_end)| max_end > address)
+            .map(|(record, _)| record)
+            .find(|record| record.range().contains(&address))?;
+
+        let mut rules = Cow::Borrowed(record.init_rules);
+        for delta in record.deltas().flatten() {
+            if delta.address > address {
+                break;
+            }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.records.find_map(Result::ok).map(|record| Symbol {
-            name: Some(Cow::Borrowed(record.name)),
-            address: record.address,
-            size: 0,
-        })
+            let rules = rules.to_mut();
+            rules.push(' ');
+            rules.push_str(delta.rules);
+        }
+
+        Some(rules)
+    }
+
+    /// Returns the `STACK WIN` record that applies at the given address.
+    ///
+    /// If multiple records start at the same address and cover it, a record of type
+    /// [`FrameData`](BreakpadStackWinRecordType::FrameData) is preferred, since it carries a
+    /// program string. Returns `None` if no record covers the address.
+    pub fn win_record_for_address(&self, address: u64) -> Option<&BreakpadStackWinRecord<'data>> {
+        let index = self
+            .win_records
+            .partition_point(|record| u64::from(record.code_start) <= address);
+        let start = self.win_records[..index].last()?.code_start;
+
+        let mut candidates = self.win_records[..index]
+            .iter()
+            .rev()
+            .take_while(|record| record.code_start == start)
+            .filter(|record| u64::from(record.code_start) + u64::from(record.code_size) > address);
+
+        let mut best = candidates.next()?;
+        for record in candidates {
+            if record.ty == BreakpadStackWinRecordType::FrameData {
+                best = record;
+            }
+        }
+
+        Some(best)
     }
 }
 
-/// Debug session for Breakpad objects.
-pub struct BreakpadDebugSession<'data> {
+/// An address index of a Breakpad object that can be shared between threads.
+///
+/// This is created by [`BreakpadObject::index`] in a single pass over the file. It stores the file
+/// map along with the addresses and byte offsets of all `FUNC`, `PUBLIC` and `STACK` records, and
+/// parses records only when they are looked up. Lookups take `&self` and do not lock, so the index
+/// can be shared behind a reference or an `Arc` to look up addresses from multiple threads.
+///
+/// Records that fail to parse are not indexed.
+#[derive(Clone, Debug, Default)]
+pub struct BreakpadIndex<'data> {
+    data: &'data [u8],
+    debug_id: DebugId,
     file_map: BreakpadFileMap<'data>,
-    func_records: BreakpadFuncRecords<'data>,
+    /// Offsets of all `FILE` records in the file map.
+    files: Vec<usize>,
+    /// `FUNC` records as `(address, size, offset)`, sorted by address.
+    functions: Vec<(u64, u64, usize)>,
+    /// `PUBLIC` records as `(address, offset)`, sorted by address.
+    publics: Vec<(u64, usize)>,
+    /// `STACK CFI INIT` and `STACK WIN` records as `(start, end, offset)`, sorted by start address.
+    stack_records: Vec<(u64, u64, usize)>,
 }
 
-impl<'data> BreakpadDebugSession<'data> {
-    /// Returns an iterator over all functions in this debug file.
-    pub fn functions(&self) -> BreakpadFunctionIterator<'_> {
-        BreakpadFunctionIterator {
-            file_map: &self.file_map,
-            func_records: self.func_records.clone(),
+impl<'data> BreakpadIndex<'data> {
+    fn new(data: &'data [u8], debug_id: DebugId) -> Self {
+        let mut index = Self {
+            data,
+            debug_id,
+            ..Self::default()
+        };
+
+        let mut lines = Lines::new(data);
+        while let Some((offset, line)) = lines.next_with_offset() {
+            if line.starts_with(b"FILE ") {
+                if let Ok(record) = BreakpadFileRecord::parse(line) {
+                    index.file_map.insert(record.id, record.name);
+                    index.files.push(offset);
+                }
+            } else if line.starts_with(b"FUNC ") {
+                if let Ok(record) = BreakpadFuncRecord::parse(line, Lines::default()) {
+                    index.functions.push((record.address, record.size, offset));
+                }
+            } else if line.starts_with(b"PUBLIC ") {
+                if let Ok(record) = BreakpadPublicRecord::parse(line) {
+                    index.publics.push((record.address, offset));
+                }
+            } else if line.starts_with(b"STACK CFI INIT ") {
+                if let Ok(record) = BreakpadStackCfiRecord::parse(line) {
+                    let range = record.range();
+                    index.stack_records.push((range.start, range.end, offset));
+                }
+            } else if line.starts_with(b"STACK WIN ") {
+                if let Ok(record) = BreakpadStackWinRecord::parse(line) {
+                    let start = u64::from(record.code_start);
+                    let end = start + u64::from(record.code_size);
+                    index.stack_records.push((start, end, offset));
+                }
+            }
         }
+
+        // The sorts are stable, which preserves the file order of records at the same address.
+        index.functions.sort_by_key(|&(address, _, _)| address);
+        index.publics.sort_by_key(|&(address, _)| address);
+        index.stack_records.sort_by_key(|&(start, _, _)| start);
+        index
     }
 
-    /// Returns an iterator over all source files in this debug file.
-    pub fn files(&self) -> BreakpadFileIterator<'_> {
-        BreakpadFileIterator {
-            files: self.file_map.values(),
-        }
+    /// Returns the map of all file records.
+    pub fn file_map(&self) -> &BreakpadFileMap<'data> {
+        &self.file_map
     }
 
-    /// Looks up a file's source contents by its full canonicalized path.
-    ///
-    /// The given path must be canonicalized.
-    pub fn source_by_path(&self, _path: &str) -> Result<Option<Cow<'_, str>>, BreakpadError> {
-        Ok(None)
+    /// Returns the lines of the data starting at the record with the given offset.
+    fn lines_at(&self, offset: usize) -> Lines<'data> {
+        Lines::new(&self.data[offset..])
     }
-}
 
-impl<'data, 'session> DebugSession<'session> for BreakpadDebugSession<'data> {
-    type Error = BreakpadError;
-    type FunctionIterator = BreakpadFunctionIterator<'session>;
-    type FileIterator = BreakpadFileIterator<'session>;
+    /// Returns the function record covering the given address, including its line records.
+    ///
+    /// Functions with a size of zero cover no addresses.
+    pub fn function_at(&self, address: u64) -> Option<BreakpadFuncRecord<'data>> {
+        let index = self
+            .functions
+            .partition_point(|&(start, _, _)| start <= address);
+        let (start, size, offset) = self.functions[..index].last().copied()?;
+        if address - start >= size {
+            return None;
+        }
 
-    fn functions(&'session self) -> Self::FunctionIterator {
-        self.functions()
+        let mut lines = self.lines_at(offset);
+        let line = lines.next()?;
+        BreakpadFuncRecord::parse(line, lines).ok()
     }
 
-    fn files(&'session self) -> Self::FileIterator {
-        self.files()
+    /// Returns the public symbol record at or preceding the given address.
+    ///
+    /// Since public symbols have no size, this returns the closest preceding symbol even if the
+    /// address lies beyond the end of the function it denotes.
+    pub fn public_at(&self, address: u64) -> Option<BreakpadPublicRecord<'data>> {
+        let index = self.publics.partition_point(|&(start, _)| start <= address);
+        let (_, offset) = self.publics[..index].last().copied()?;
+        BreakpadPublicRecord::parse(self.lines_at(offset).next()?).ok()
     }
 
-    fn source_by_path(&self, path: &str) -> Result<Option<Cow<'_, str>>, Self::Error> {
-        self.source_by_path(path)
-    }
-}
+    /// Looks up the function, file and line for the given address.
+    ///
+    /// Unlike [`BreakpadDebugSession::lookup`], this does not cache line records. They are scanned
+    /// on every lookup instead, which requires no synchronization between threads.
+    pub fn lookup(&self, address: u64) -> Option<BreakpadSourceLocation<'data>> {
+        let function = self.function_at(address)?;
+        let line = function
+            .lines()
+            .filter_map(Result::ok)
+            .find(|line| line.range().contains(&address));
 
-/// An iterator over source files in a Breakpad object.
-pub struct BreakpadFileIterator<'s> {
-    files: std::collections::btree_map::Values<'s, u64, &'s str>,
-}
+        Some(BreakpadSourceLocation {
+            function_address: function.address,
+            function_name: function.name,
+            file: line.as_ref().and_then(|line| line.filename(&self.file_map)),
+            line: line.map(|line| line.line),
+        })
+    }
 
-impl<'s> Iterator for BreakpadFileIterator<'s> {
-    type Item = Result<FileEntry<'s>, BreakpadError>;
+    /// Returns the stack record covering the given address.
+    ///
+    /// `STACK CFI INIT` records include their delta records. If records overlap, the one with the
+    /// closest start address is returned.
+    pub fn stack_record_at(&self, address: u64) -> Option<BreakpadStackRecord<'data>> {
+        let index = self
+            .stack_records
+            .partition_point(|&(start, _, _)| start <= address);
+        let (_, end, offset) = self.stack_records[..index].last().copied()?;
+        if address >= end {
+            return None;
+        }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let path = self.files.next()?;
-        Some(Ok(FileEntry {
-            compilation_dir: &[],
-            info: FileInfo::from_path(path.as_bytes()),
-        }))
+        let mut lines = self.lines_at(offset);
+        let line = lines.next()?;
+        if line.starts_with(b"STACK CFI INIT ") {
+            let mut record = BreakpadStackCfiRecord::parse(line).ok()?;
+            record.deltas = lines;
+            Some(BreakpadStackRecord::Cfi(record))
+        } else {
+            BreakpadStackRecord::parse(line).ok()
+        }
     }
 }
 
-/// An iterator over functions in a Breakpad object.
-pub struct BreakpadFunctionIterator<'s> {
-    file_map: &'s BreakpadFileMap<'s>,
-    func_records: BreakpadFuncRecords<'s>,
+/// A Breakpad object file.
+///
+/// To process minidump crash reports without having to understand all sorts of native symbol
+/// formats, the Breakpad processor uses a text-based symbol file format. It comprises records
+/// describing the object file, functions and lines, public symbols, as well as unwind information
+/// for stackwalking.
+///
+/// > The platform-specific symbol dumping tools parse the debugging information the compiler
+/// > provides (whether as DWARF or STABS sections in an ELF file or as stand-alone PDB files), and
+/// > write that information back out in the Breakpad symbol file format. This format is much
+/// > simpler and less detailed than compiler debugging information, and values legibility over
+/// > compactness.
+///
+/// The full documentation resides [here](https://chromium.googlesource.com/breakpad/breakpad/+/refs/heads/master/docs/symbol_files.md).
+pub struct BreakpadObject<'data> {
+    id: DebugId,
+    arch: Arch,
+    module: BreakpadModuleRecord<'data>,
+    data: &'data [u8],
+    /// Symbols as `(address, size, offset)` sorted by address, built by `symbol_by_address`.
+    symbol_index: OnceCell<Vec<(u64, u64, usize)>>,
 }
 
-impl<'s> BreakpadFunctionIterator<'s> {
-    fn convert(&self, record: BreakpadFuncRecord<'s>) -> Result<Function<'s>, BreakpadError> {
-        let mut lines = Vec::new();
-        for line in record.lines() {
-            let line = line?;
-            let filename = line.filename(self.file_map).unwrap_or_default();
-
-            lines.push(LineInfo {
-                address: line.address,
-                size: Some(line.size),
-                file: FileInfo::from_path(filename.as_bytes()),
-                line: line.line,
-            });
-        }
-
-        Ok(Function {
-            address: record.address,
-            size: record.size,
-            name: Name::new(record.name, NameMangling::Unmangled, Language::Unknown),
-            compilation_dir: &[],
-            lines,
-            inlinees: Vec::new(),
-            inline: false,
-        })
+impl<'data> BreakpadObject<'data> {
+    /// Tests whether the buffer could contain a Breakpad object.
+    ///
+    /// A leading UTF-8 byte order mark is ignored.
+    pub fn test(data: &[u8]) -> bool {
+        strip_bom(data).starts_with(b"MODULE ")
     }
-}
 
-impl<'s> Iterator for BreakpadFunctionIterator<'s> {
-    type Item = Result<Function<'s>, BreakpadError>;
+    /// Tries to parse a Breakpad object from the given slice.
+    ///
+    /// A leading UTF-8 byte order mark is ignored.
+    pub fn parse(data: &'data [u8]) -> Result<Self, BreakpadError> {
+        Self::parse_impl(data, false)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.func_records.next() {
-            Some(Ok(record)) => Some(self.convert(record)),
-            Some(Err(error)) => Some(Err(error)),
-            None => None,
-        }
+    /// Tries to parse a Breakpad object from the given slice, accepting unknown architectures.
+    ///
+    /// Unlike [`parse`](Self::parse), this does not fail if the architecture in the `MODULE` record
+    /// is not recognized. Instead, [`arch`](Self::arch) returns [`Arch::Unknown`], and the original
+    /// string is available through [`raw_arch`](Self::raw_arch). All records can be read as usual.
+    pub fn parse_lenient(data: &'data [u8]) -> Result<Self, BreakpadError> {
+        Self::parse_impl(data, true)
     }
-}
 
-impl std::iter::FusedIterator for BreakpadFunctionIterator<'_> {}
+    /// Parses a Breakpad object from shared data and stores it alongside the data.
+    ///
+    /// The returned cell keeps the allocation alive for as long as the object is in use, so that
+    /// one allocation can back multiple objects without copying. Use [`SelfCell::get`] to access
+    /// the object; records borrowed from it cannot outlive the cell. To share the object itself
+    /// between tasks, wrap the cell in an `Arc`.
+    pub fn from_arc(
+        data: Arc<[u8]>,
+    ) -> Result<SelfCell<Arc<[u8]>, BreakpadObject<'static>>, BreakpadError> {
+        SelfCell::try_new(data, |data| BreakpadObject::parse(unsafe { &*data }))
+    }
+
+    fn parse_impl(data: &'data [u8], lenient: bool) -> Result<Self, BreakpadError> {
+        let content = strip_bom(data);
+
+        // Ensure that we do not read the entire file at once. Only the first line is validated, so
+        // that invalid UTF-8 in later records does not prevent reading the header.
+        let window = &content[..content.len().min(BREAKPAD_HEADER_CAP)];
+        let first_line = match memchr::memchr(b'\n', window) {
+            Some(index) => &window[..index],
+            None => match str::from_utf8(window) {
+                Ok(_) => window,
+                // The cap may split a multi-byte character, which is read in full if possible.
+                Err(e) if e.error_len().is_none() => {
+                    let start = e.valid_up_to();
+                    (window.len() + 1..=content.len().min(start + 4))
+                        .map(|end| &content[..end])
+                        .find(|line| str::from_utf8(&line[start..]).is_ok())
+                        .unwrap_or(&window[..start])
+                }
+                Err(e) => return Err(e.into()),
+            },
+        };
 
-mod parsing {
-    use nom::branch::alt;
-    use nom::bytes::complete::take_while;
-    use nom::character::complete::{char, hex_digit1, multispace1};
-    use nom::combinator::{cond, eof, rest};
-    use nom::sequence::{pair, tuple};
-    use nom::{IResult, Parser};
-    use nom_supreme::error::ErrorTree;
-    use nom_supreme::final_parser::{Location, RecreateContext};
-    use nom_supreme::parser_ext::ParserExt;
-    use nom_supreme::tag::complete::tag;
+        let module = BreakpadModuleRecord::parse(first_line)?;
 
-    use super::*;
+        Ok(BreakpadObject {
+            id: module
+                .id
+                .parse()
+                .map_err(|_| BreakpadErrorKind::InvalidModuleId)?,
+            arch: match module.arch.parse() {
+                Ok(arch) => arch,
+                Err(_) if lenient => Arch::Unknown,
+                Err(_) => return Err(BreakpadErrorKind::InvalidArchitecture.into()),
+            },
+            module,
+            data,
+            symbol_index: OnceCell::new(),
+        })
+    }
 
-    type ParseResult<'a, T> = IResult<&'a str, T, ErrorTree<&'a str>>;
-    pub type ParseBreakpadError = ErrorTree<ErrorLine>;
+    /// The container file format, which is always `FileFormat::Breakpad`.
+    pub fn file_format(&self) -> FileFormat {
+        FileFormat::Breakpad
+    }
 
-    /// A line with a 1-based column position, used for displaying errors.
-    ///
-    /// With the default formatter, this prints the line followed by the column number.
-    /// With the alternate formatter (using `:#`), it prints the line and a caret
-    /// pointing at the column position.
-    ///
-    /// # Example
-    /// ```ignore
-    /// use symbolic_debuginfo::breakpad::parsing::ErrorLine;
-    ///
-    /// let error_line = ErrorLine {
-    ///     line: "This line cnotains a typo.".to_string(),
-    ///     column: 12,
-    /// };
-    ///
-    /// // "This line cnotains a typo.", column 12
-    /// println!("{}", error_line);
+    /// The code identifier of this object.
     ///
-    /// // "This line cnotains a typo."
-    /// //             ^
-    /// println!("{:#}", error_line);
-    /// ```
-    #[derive(Clone, Debug, PartialEq, Eq)]
-    pub struct ErrorLine {
-        /// A line of text containing an error.
-        pub line: String,
-
-        /// The position of the error, 1-based.
-        pub column: usize,
+    /// The identifier is normalized, so that it compares equal to code identifiers obtained from
+    /// other sources, such as minidumps: hex digits are lowercased and all other characters,
+    /// including separators between the timestamp and size of PE identifiers, are removed. Use
+    /// [`raw_code_id`](Self::raw_code_id) to obtain the identifier as written in the file.
+    pub fn code_id(&self) -> Option<CodeId> {
+        self.raw_code_id()
+            .map(|code_id| CodeId::new(code_id.into()))
     }
 
-    impl<'a> RecreateContext<&'a str> for ErrorLine {
-        fn recreate_context(original_input: &'a str, tail: &'a str) -> Self {
-            let Location { column, .. } = Location::recreate_context(original_input, tail);
-            Self {
-                line: original_input.to_string(),
-                column,
-            }
-        }
+    /// The code identifier of this object, exactly as stated by the `INFO CODE_ID` record.
+    pub fn raw_code_id(&self) -> Option<&'data str> {
+        self.info_records()
+            .flatten()
+            .find_map(|record| match record {
+                BreakpadInfoRecord::CodeId { code_id, .. } if !code_id.is_empty() => Some(code_id),
+                _ => None,
+            })
     }
 
-    impl fmt::Display for ErrorLine {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            if f.alternate() {
-                writeln!(f)?;
-            }
-
-            write!(f, "\"{}\"", self.line)?;
+    /// The name of the code file of this object, as stated by the `INFO CODE_ID` record.
+    ///
+    /// This may be present even if [`code_id`](Self::code_id) is `None`, since some dumpers emit
+    /// the code file without an identifier.
+    pub fn code_file(&self) -> Option<&'data str> {
+        self.info_records()
+            .flatten()
+            .find_map(|record| match record {
+                BreakpadInfoRecord::CodeId { code_file, .. }
+                    if !code_file.is_empty() && code_file != UNKNOWN_NAME =>
+                {
+                    Some(code_file)
+                }
+                _ => None,
+            })
+    }
 
-            if f.alternate() {
-                writeln!(f, "\n{:>width$}", "^", width = self.column + 1)?;
-            } else {
-                write!(f, ", column {}", self.column)?;
-            }
+    /// The debug information identifier of this object.
+    pub fn debug_id(&self) -> DebugId {
+        self.id
+    }
 
-            Ok(())
+    /// The age of the debug identifier, if the `MODULE` record states it.
+    ///
+    /// The age is stored in the [`appendix`](DebugId::appendix) of [`debug_id`](Self::debug_id).
+    /// Some dumpers omit the age, which is then implicitly zero. In this case, this function returns
+    /// `None` while the appendix is `0`.
+    pub fn debug_id_age(&self) -> Option<u32> {
+        let digits = self.module.id.chars().filter(|&c| c != '-').count();
+        if digits <= 32 && !self.id.is_pdb20() {
+            return None;
         }
+
+        Some(self.id.appendix())
     }
 
-    /// Parse a sequence of decimal digits as a number of the given type.
-    macro_rules! num_dec {
-        ($ty:ty) => {
-            nom::character::complete::digit1.map_res(|s: &str| s.parse::<$ty>())
-        };
+    /// The debug identifier of this object in the canonical Breakpad format.
+    ///
+    /// This is the uppercase representation without hyphens used by minidump module lists and
+    /// symbol servers, including the age. The module record may state the identifier differently,
+    /// for instance in lowercase, with hyphens or without the age.
+    pub fn breakpad_id(&self) -> String {
+        // `DebugId` formats the age in lowercase, while minidumps use uppercase throughout.
+        self.id.breakpad().to_string().to_ascii_uppercase()
     }
 
-    /// Parse a sequence of hexadecimal digits as a number of the given type.
-    macro_rules! num_hex {
-        ($ty:ty) => {
-            nom::character::complete::hex_digit1.map_res(|n| <$ty>::from_str_radix(n, 16))
-        };
+    /// The CPU architecture of this object.
+    pub fn arch(&self) -> Arch {
+        self.arch
     }
 
-    /// Parse a sequence of non-whitespace characters.
-    fn non_whitespace(input: &str) -> ParseResult<&str> {
-        take_while(|c: char| !c.is_whitespace())(input)
+    /// The name of the operating system as stated in the `MODULE` record.
+    pub fn raw_os(&self) -> &'data str {
+        self.module.os
     }
 
-    /// Parse to the end of input and return the resulting string.
+    /// The name of the CPU architecture as stated in the `MODULE` record.
     ///
-    /// If there is no input, return [`UNKNOWN_NAME`] instead.
-    fn name(input: &str) -> ParseResult<&str> {
-        rest.map(|name: &str| if name.is_empty() { UNKNOWN_NAME } else { name })
-            .parse(input)
+    /// This is useful for objects parsed with [`parse_lenient`](Self::parse_lenient), where
+    /// [`arch`](Self::arch) is [`Arch::Unknown`] for unrecognized architectures.
+    pub fn raw_arch(&self) -> &'data str {
+        self.module.arch
     }
 
-    /// Attempt to parse the character `m` followed by one or more spaces.
+    /// The debug file name of this object.
     ///
-    /// Returns true if the parse was successful.
-    fn multiple(input: &str) -> ParseResult<bool> {
-        let (mut input, multiple) = char('m').opt().parse(input)?;
-        let multiple = multiple.is_some();
-        if multiple {
-            input = multispace1(input)?.0;
-        }
-        Ok((input, multiple))
+    /// This is the name of the original debug file that was used to create the Breakpad file. On
+    /// Windows, this will have a `.pdb` extension, on other platforms that name is likely
+    /// equivalent to the name of the code file (shared library or executable).
+    pub fn name(&self) -> &'data str {
+        self.module.name
     }
 
-    /// Parse a line number as a signed decimal number and return `max(0, n)`.
-    fn line_num(input: &str) -> ParseResult<u64> {
-        pair(char('-').opt(), num_dec!(u64))
-            .map(|(sign, num)| if sign.is_some() { 0 } else { num })
-            .parse(input)
+    /// The `MODULE` record at the beginning of this object.
+    pub fn module_record(&self) -> &BreakpadModuleRecord<'data> {
+        &self.module
     }
 
-    /// Parse a [`BreakpadStackWinRecordType`].
-    fn stack_win_record_type(input: &str) -> ParseResult<BreakpadStackWinRecordType> {
-        alt((
-            char('0').value(BreakpadStackWinRecordType::Fpo),
-            char('1').value(BreakpadStackWinRecordType::Trap),
-            char('2').value(BreakpadStackWinRecordType::Tss),
-            char('3').value(BreakpadStackWinRecordType::Standard),
-            char('4').value(BreakpadStackWinRecordType::FrameData),
-            non_whitespace.value(BreakpadStackWinRecordType::Unknown),
-        ))(input)
+    /// The kind of this object.
+    pub fn kind(&self) -> ObjectKind {
+        ObjectKind::Debug
     }
 
-    /// Parse a [`BreakpadModuleRecord`].
+    /// The address at which the image prefers to be loaded into memory.
     ///
-    /// A module record has the form `MODULE <os> <arch> <id>( <name>)?`.
-    fn module_record(input: &str) -> ParseResult<BreakpadModuleRecord> {
-        let (input, _) = tag("MODULE")
-            .terminated(multispace1)
-            .context("module record prefix")
-            .parse(input)?;
-        let (input, (os, arch, id, name)) = tuple((
-            non_whitespace.terminated(multispace1).context("os"),
-            non_whitespace.terminated(multispace1).context("arch"),
-            hex_digit1
-                .terminated(multispace1.or(eof))
-                .context("module id"),
-            name.context("module name"),
-        ))
-        .cut()
-        .context("module record body")
-        .parse(input)?;
+    /// When Breakpad symbols are written, all addresses are rebased relative to the load address.
+    /// Since the original load address is not stored in the file, it is assumed as zero.
+    pub fn load_address(&self) -> u64 {
+        0 // Breakpad rebases all addresses when dumping symbols
+    }
 
-        Ok((input, BreakpadModuleRecord { os, arch, id, name }))
+    /// Determines whether this object exposes a public symbol table.
+    pub fn has_symbols(&self) -> bool {
+        self.public_records().next().is_some()
     }
 
-    /// Parse a [`BreakpadModuleRecord`].
+    /// Returns an iterator over symbols in the public symbol table.
+    pub fn symbols(&self) -> BreakpadSymbolIterator<'data> {
+        BreakpadSymbolIterator {
+            records: self.public_records(),
+        }
+    }
+
+    /// Returns an iterator over symbols in the public symbol table, including Breakpad metadata.
     ///
-    /// A module record has the form `MODULE <os> <arch> <id>( <name>)?`.
-    /// This will fail if there is any input left over after the record.
-    pub fn module_record_final(input: &str) -> Result<BreakpadModuleRecord, ErrorTree<ErrorLine>> {
-        nom_supreme::final_parser::final_parser(module_record)(input)
+    /// Unlike [`symbols`](Self::symbols), this yields [`BreakpadSymbol`]s, which retain the
+    /// parameter size and the `m` flag of each `PUBLIC` record. Records that fail to parse are
+    /// skipped.
+    pub fn public_symbols(&self) -> BreakpadPublicSymbolIterator<'data> {
+        BreakpadPublicSymbolIterator {
+            records: self.public_records(),
+        }
     }
 
-    /// Parse the `CodeId` variant of a [`BreakpadInfoRecord`].
+    /// Returns an ordered map of symbols in the symbol table.
+    pub fn symbol_map(&self) -> SymbolMap<'data> {
+        self.symbols().collect()
+    }
+
+    /// Returns all function and public symbols, sorted by address.
     ///
-    /// A `CodeId` record has the form `CODE_ID <code_id>( <code_file>)?`.
-    fn info_code_id_record(input: &str) -> ParseResult<BreakpadInfoRecord> {
-        let (input, _) = tag("CODE_ID")
-            .terminated(multispace1)
-            .context("info code_id record prefix")
-            .parse(input)?;
+    /// Function records take precedence over public symbols: a public symbol is omitted if a
+    /// function starts at the same address or covers its address. Symbols at the same address keep
+    /// the order in which they appear in the file. Unlike [`symbols`](Self::symbols), this fails if
+    /// a record cannot be parsed.
+    pub fn all_symbols(&self) -> Result<Vec<BreakpadSymbol<'data>>, BreakpadError> {
+        let mut funcs = self.func_records().collect::<Result<Vec<_>, _>>()?;
+        let mut publics = self.public_records().collect::<Result<Vec<_>, _>>()?;
+
+        // Both are usually sorted already, which makes these sorts cheap. The sorts must be stable
+        // to keep the file order of symbols at the same address.
+        funcs.sort_by_key(|func| func.address);
+        publics.sort_by_key(|public| public.address);
+
+        let mut symbols = Vec::with_capacity(funcs.len() + publics.len());
+        let mut funcs = funcs.into_iter().peekable();
+        let mut publics = publics.into_iter().peekable();
+
+        // The end of the code covered by functions so far, and the address of the last function.
+        let mut covered_end = 0;
+        let mut last_func = None;
+
+        loop {
+            let take_func = match (funcs.peek(), publics.peek()) {
+                (Some(func), Some(public)) => func.address <= public.address,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
 
-        let (input, (code_id, code_file)) = pair(
-            hex_digit1
-                .terminated(multispace1.or(eof))
-                .context("code id"),
-            name.context("file name"),
-        )
-        .cut()
-        .context("info code_id record body")
-        .parse(input)?;
+            if take_func {
+                let func = funcs.next().unwrap();
+                covered_end = covered_end.max(func.range().end);
+                last_func = Some(func.address);
+                symbols.push(func.into());
+            } else {
+                let public = publics.next().unwrap();
+                if public.address >= covered_end && last_func != Some(public.address) {
+                    symbols.push(public.into());
+                }
+            }
+        }
 
-        Ok((input, BreakpadInfoRecord::CodeId { code_id, code_file }))
+        Ok(symbols)
     }
 
-    /// Parse the `Other` variant of a [`BreakpadInfoRecord`].
+    /// Returns the `FUNC` or `PUBLIC` symbol with the highest address at or before `address`.
     ///
-    /// An `Other` record has the form `<scope>( <info>)?`.
-    fn info_other_record(input: &str) -> ParseResult<BreakpadInfoRecord> {
-        let (input, (scope, info)) = pair(
-            non_whitespace
-                .terminated(multispace1.or(eof))
-                .context("info scope"),
-            rest,
-        )
-        .cut()
-        .context("info other record body")
-        .parse(input)?;
+    /// The first call builds a sorted index of all function and public symbol records, which is
+    /// kept for subsequent calls. If a function and a public symbol share an address, the function
+    /// is preferred. Records that fail to parse are ignored.
+    ///
+    /// The size of the returned symbol is the size of the function record. For public symbols, and
+    /// functions with a size of zero, it is computed as the distance to the next symbol. The last
+    /// symbol has a size of zero in this case, since the end of the module is unknown.
+    ///
+    /// Returns `None` if the address is before the first symbol. An address after the end of the
+    /// module, or between two functions, resolves to the preceding symbol, even though it is not
+    /// covered by it. Compare the address to the symbol's size to detect this.
+    pub fn symbol_by_address(&self, address: u64) -> Option<Symbol<'data>> {
+        let (address, size, line) = self.symbol_line_at(address)?;
+        let name = if line.starts_with(b"FUNC ") {
+            BreakpadFuncRecord::parse(line, Lines::default()).ok()?.name
+        } else {
+            BreakpadPublicRecord::parse(line).ok()?.name
+        };
 
-        Ok((input, BreakpadInfoRecord::Other { scope, info }))
+        Some(Symbol {
+            name: Some(Cow::Borrowed(name)),
+            address,
+            size,
+        })
     }
 
-    /// Parse a [`BreakpadInfoRecord`].
+    /// Returns the size of the parameters on the stack of the function containing the address.
     ///
-    /// An INFO record has the form `INFO (<code_id_record> | <other_record>)`.
-    fn info_record(input: &str) -> ParseResult<BreakpadInfoRecord> {
-        let (input, _) = tag("INFO")
-            .terminated(multispace1)
-            .context("info record prefix")
-            .parse(input)?;
+    /// This is the `parameter_size` of the `FUNC` or `PUBLIC` record found by
+    /// [`symbol_by_address`](Self::symbol_by_address). On x86, unwinders use it to compute the
+    /// caller's stack pointer when walking over `STACK WIN` frames, since the callee pops its
+    /// parameters when returning.
+    ///
+    /// Unlike `symbol_by_address`, this returns `None` if the address lies past the end of the
+    /// symbol, as long as the symbol's size is known.
+    pub fn parameter_size_for_address(&self, address: u64) -> Option<u64> {
+        let (start, size, line) = self.symbol_line_at(address)?;
+        if size > 0 && address - start >= size {
+            return None;
+        }
 
-        info_code_id_record
-            .or(info_other_record)
-            .cut()
-            .context("info record body")
-            .parse(input)
+        if line.starts_with(b"FUNC ") {
+            Some(
+                BreakpadFuncRecord::parse(line, Lines::default())
+                    .ok()?
+                    .parameter_size,
+            )
+        } else {
+            Some(BreakpadPublicRecord::parse(line).ok()?.parameter_size)
+        }
     }
 
-    /// Parse a [`BreakpadInfoRecord`].
-    ///
-    /// An INFO record has the form `INFO (<code_id_record> | <other_record>)`.
-    /// This will fail if there is any input left over after the record.
-    pub fn info_record_final(input: &str) -> Result<BreakpadInfoRecord, ErrorTree<ErrorLine>> {
-        nom_supreme::final_parser::final_parser(info_record)(input)
+    /// Returns the address, size and record line of the symbol preceding the address.
+    fn symbol_line_at(&self, address: u64) -> Option<(u64, u64, &'data [u8])> {
+        let index = self.symbol_index.get_or_init(|| self.build_symbol_index());
+
+        let position = index.partition_point(|&(start, _, _)| start <= address);
+        let (address, size, offset) = index[..position].last().copied()?;
+        let line = Lines::new(&self.data[offset..]).next()?;
+        Some((address, size, line))
     }
 
-    /// Parse a [`BreakpadFileRecord`].
-    ///
-    /// A FILE record has the form `FILE <id>( <name>)?`.
-    fn file_record(input: &str) -> ParseResult<BreakpadFileRecord> {
-        let (input, _) = tag("FILE")
-            .terminated(multispace1)
-            .context("file record prefix")
-            .parse(input)?;
+    /// Builds the index used by [`symbol_by_address`](Self::symbol_by_address).
+    fn build_symbol_index(&self) -> Vec<(u64, u64, usize)> {
+        // Sort keys are `(address, is_public)`, so that functions come first.
+        let mut symbols = Vec::new();
 
-        let (input, (id, name)) = pair(
-            num_dec!(u64)
-                .terminated(multispace1.or(eof))
-                .context("file id"),
-            rest.context("file name"),
-        )
-        .cut()
-        .context("file record body")
-        .parse(input)?;
+        let mut funcs = self.func_records();
+        while let Some(result) = funcs.next() {
+            if let (Ok(func), Some(offset)) = (result, funcs.offset()) {
+                symbols.push(((func.address, false), func.size, offset));
+            }
+        }
 
-        Ok((input, BreakpadFileRecord { id, name }))
-    }
+        let mut publics = self.public_records();
+        while let Some(result) = publics.next() {
+            if let (Ok(public), Some(offset)) = (result, publics.offset()) {
+                symbols.push(((public.address, true), 0, offset));
+            }
+        }
 
-    /// Parse a [`BreakpadFileRecord`].
-    ///
-    /// A FILE record has the form `FILE <id>( <name>)?`.
-    /// This will fail if there is any input left over after the record.
-    pub fn file_record_final(input: &str) -> Result<BreakpadFileRecord, ErrorTree<ErrorLine>> {
-        nom_supreme::final_parser::final_parser(file_record)(input)
-    }
+        symbols.sort_by_key(|&(key, _, _)| key);
+        symbols.dedup_by_key(|&mut ((address, _), _, _)| address);
 
-    /// Parse a [`BreakpadPublicRecord`].
-    ///
-    /// A PUBLIC record has the form `PUBLIC (m )? <address> <parameter_size> ( <name>)?`.
-    fn public_record(input: &str) -> ParseResult<BreakpadPublicRecord> {
-        let (input, _) = tag("PUBLIC")
-            .terminated(multispace1)
-            .context("public record prefix")
-            .parse(input)?;
+        let mut index: Vec<_> = symbols
+            .into_iter()
+            .map(|((address, _), size, offset)| (address, size, offset))
+            .collect();
 
-        let (input, (multiple, address, parameter_size, name)) = tuple((
-            multiple.context("multiple flag"),
-            num_hex!(u64).terminated(multispace1).context("address"),
-            num_hex!(u64)
-                .terminated(multispace1.or(eof))
-                .context("param size"),
-            name.context("symbol name"),
-        ))
-        .cut()
-        .context("public record body")
-        .parse(input)?;
+        let mut next_address = None;
+        for (address, size, _) in index.iter_mut().rev() {
+            if let (0, Some(next_address)) = (*size, next_address) {
+                *size = next_address - *address;
+            }
+            next_address = Some(*address);
+        }
 
-        Ok((
-            input,
-            BreakpadPublicRecord {
-                multiple,
-                address,
-                parameter_size,
-                name,
-            },
-        ))
+        index
     }
 
-    /// Parse a [`BreakpadPublicRecord`].
+    /// Returns the fraction of function bytes that are covered by line records.
     ///
-    /// A PUBLIC record has the form `PUBLIC (m )? <address> <parameter_size> ( <name>)?`.
-    /// This will fail if there is any input left over after the record.
-    pub fn public_record_final(input: &str) -> Result<BreakpadPublicRecord, ErrorTree<ErrorLine>> {
-        nom_supreme::final_parser::final_parser(public_record)(input)
+    /// This is the sum of the sizes of all line records divided by the sum of the sizes of all
+    /// `FUNC` records. Values close to `1.0` indicate that most code can be attributed to source
+    /// lines, while values close to `0.0` indicate that line information was stripped. Overlapping
+    /// line records can result in values above `1.0`.
+    ///
+    /// Returns `None` if there are no `FUNC` records or all of them are empty.
+    pub fn code_coverage_percent(&self) -> Result<Option<f64>, BreakpadError> {
+        let mut func_bytes = 0u64;
+        let mut line_bytes = 0u64;
+
+        for func in self.func_records() {
+            let func = func?;
+            func_bytes = func_bytes.saturating_add(func.size);
+            for line in func.lines() {
+                line_bytes = line_bytes.saturating_add(line?.size);
+            }
+        }
+
+        if func_bytes == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(line_bytes as f64 / func_bytes as f64))
     }
 
-    /// Parse a [`BreakpadFuncRecord`].
+    /// Determines whether this object contains inline information.
     ///
-    /// A FUNC record has the form `FUNC (m )? <address> <size> <parameter_size> ( <name>)?`.
-    fn func_record(input: &str) -> ParseResult<BreakpadFuncRecord> {
-        let (input, _) = tag("FUNC")
-            .terminated(multispace1)
-            .context("func record prefix")
-            .parse(input)?;
-
-        let (input, (multiple, address, size, parameter_size, name)) = tuple((
-            multiple.context("multiple flag"),
-            num_hex!(u64).terminated(multispace1).context("address"),
-            num_hex!(u64).terminated(multispace1).context("size"),
-            num_hex!(u64)
-                .terminated(multispace1.or(eof))
-                .context("param size"),
-            name.context("symbol name"),
-        ))
-        .cut()
-        .context("func record body")
-        .parse(input)?;
+    /// This searches for an `INLINE_ORIGIN` record without parsing any records, and stops at the
+    /// first match.
+    pub fn has_inline_info(&self) -> bool {
+        memchr::memmem::find(self.data, b"\nINLINE_ORIGIN ").is_some()
+    }
 
-        Ok((
-            input,
-            BreakpadFuncRecord {
-                multiple,
-                address,
-                size,
-                parameter_size,
-                name,
-                lines: Lines::default(),
-            },
-        ))
+    /// Determines whether this object contains debug information.
+    pub fn has_debug_info(&self) -> bool {
+        self.func_records().next().is_some()
     }
 
-    /// Parse a [`BreakpadFuncRecord`].
+    /// Determines whether the functions of this object contain line information.
     ///
-    /// A FUNC record has the form `FUNC (m )? <address> <size> <parameter_size> ( <name>)?`.
-    /// This will fail if there is any input left over after the record.
-    pub fn func_record_final(input: &str) -> Result<BreakpadFuncRecord, ErrorTree<ErrorLine>> {
-        nom_supreme::final_parser::final_parser(func_record)(input)
+    /// Unlike [`has_debug_info`](Self::has_debug_info), this requires a `FUNC` record with at least
+    /// one line record, which is needed to resolve file names and line numbers. Only the first
+    /// `FUNC` record is checked, so this reads no further than its first line record. If the first
+    /// function has no line records, this returns `false` even if later functions have some.
+    pub fn has_complete_debug_info(&self) -> bool {
+        match self.func_records().next() {
+            Some(Ok(func)) => func.lines().next().is_some(),
+            _ => false,
+        }
     }
 
-    /// Parse a [`BreakpadLineRecord`].
+    /// Constructs a debugging session.
     ///
-    /// A LINE record has the form `<address> <size> <line> <file_id>`.
-    fn line_record(input: &str) -> ParseResult<BreakpadLineRecord> {
-        let (input, (address, size, line, file_id)) = tuple((
-            num_hex!(u64).terminated(multispace1).context("address"),
-            num_hex!(u64).terminated(multispace1).context("size"),
-            line_num.terminated(multispace1).context("line number"),
-            num_dec!(u64).context("file id"),
-        ))
-        .context("line record")
-        .parse(input)?;
+    /// A debugging session loads certain information from the object file and creates caches for
+    /// efficient access to various records in the debug information. Since this can be quite a
+    /// costly process, try to reuse the debugging session as long as possible.
+    ///
+    /// Constructing this session will also work if the object does not contain debugging
+    /// information, in which case the session will be a no-op. This can be checked via
+    /// [`has_debug_info`](struct.BreakpadObject.html#method.has_debug_info).
+    pub fn debug_session(&self) -> Result<BreakpadDebugSession<'data>, BreakpadError> {
+        let mut file_map = BreakpadFileMap::new();
+        let mut checksums = BTreeMap::new();
+        for file in self.file_records().filter_map(Result::ok) {
+            file_map.insert(file.id, file.name);
+            if let Some(checksum) = file.checksum {
+                checksums.insert(file.id, checksum);
+            }
+        }
 
-        Ok((
-            input,
-            BreakpadLineRecord {
-                address,
-                size,
-                line,
-                file_id,
-            },
-        ))
+        Ok(BreakpadDebugSession {
+            file_map,
+            checksums,
+            func_records: self.func_records(),
+            public_records: self.public_records(),
+            inferred_sizes: BTreeMap::new(),
+            canonicalize: false,
+            path_mappings: Vec::new(),
+            rewritten_paths: BTreeMap::new(),
+            lookup_cache: Mutex::default(),
+        })
     }
 
-    /// Parse a [`BreakpadLineRecord`].
+    /// Constructs an unwind session for this object.
     ///
-    /// A LINE record has the form `<address> <size> <line> <file_id>`.
-    /// This will fail if there is any input left over after the record.
-    pub fn line_record_final(input: &str) -> Result<BreakpadLineRecord, ErrorTree<ErrorLine>> {
-        nom_supreme::final_parser::final_parser(line_record)(input)
+    /// The session indexes all stack records by address, which requires parsing all of them. Use it
+    /// to find the unwind information for instruction addresses during stackwalking.
+    ///
+    /// This requires the `unwind` feature.
+    #[cfg(feature = "unwind")]
+    pub fn unwind_session(&self) -> Result<BreakpadUnwindSession<'data>, BreakpadError> {
+        let mut cfi_records = self.cfi_records().collect::<Result<Vec<_>, _>>()?;
+        let mut win_records = self.win_records().collect::<Result<Vec<_>, _>>()?;
+
+        // The sorts are stable, which preserves the file order of records at the same address.
+        cfi_records.sort_by_key(|record| record.start);
+        win_records.sort_by_key(|record| record.code_start);
+
+        let mut max_end = 0;
+        let cfi_max_ends = cfi_records
+            .iter()
+            .map(|record| {
+                max_end = max_end.max(record.range().end);
+                max_end
+            })
+            .collect();
+
+        Ok(BreakpadUnwindSession {
+            cfi_records,
+            cfi_max_ends,
+            win_records,
+        })
     }
 
-    /// Parse a [`BreakpadStackCfiDeltaRecord`].
+    /// Builds an address index of this object that can be shared between threads.
     ///
-    /// A STACK CFI Delta record has the form `STACK CFI <address> <rules>`.
+    /// See [`BreakpadIndex`] for more information.
+    pub fn index(&self) -> BreakpadIndex<'data> {
+        BreakpadIndex::new(self.data, self.debug_id())
+    }
+
+    /// Determines whether this object contains stack unwinding information.
+    pub fn has_unwind_info(&self) -> bool {
+        self.stack_records().next().is_some()
+    }
+
+    /// Determines whether this object contains `STACK CFI` unwind information.
+    pub fn has_cfi_info(&self) -> bool {
+        self.cfi_records().next().is_some()
+    }
+
+    /// Determines whether this object contains `STACK WIN` unwind information.
+    pub fn has_win_info(&self) -> bool {
+        self.win_records().next().is_some()
+    }
+
+    /// Determines whether this object contains embedded source.
+    pub fn has_sources(&self) -> bool {
+        false
+    }
+
+    /// Determines whether this object is malformed and was only partially parsed
+    pub fn is_malformed(&self) -> bool {
+        false
+    }
+
+    /// Returns the byte range of the `MODULE` record and the `INFO` records following it.
+    ///
+    /// The range starts at the beginning of the data, including a byte order mark, and ends before
+    /// the first other record.
+    pub fn header_range(&self) -> Range<usize> {
+        let mut lines = Lines::new(self.data);
+        while let Some((offset, line)) = lines.next_with_offset() {
+            if !line.is_empty() && !line.starts_with(b"MODULE ") && !line.starts_with(b"INFO ") {
+                return 0..offset;
+            }
+        }
+        0..lines.position()
+    }
+
+    /// Returns the byte range of the consecutive records starting with the given prefix.
+    ///
+    /// The block starts at the first such record and ends after the last record of the prefix
+    /// before any other record. Records of the same type further down in the file are not included.
+    fn block_range(&self, prefix: &[u8]) -> Option<Range<usize>> {
+        let mut range: Option<Range<usize>> = None;
+        let mut lines = Lines::new(self.data);
+        while let Some((offset, line)) = lines.next_with_offset() {
+            if line.starts_with(prefix) {
+                let start = range.map_or(offset, |range| range.start);
+                range = Some(start..lines.position());
+            } else if range.is_some() && !line.is_empty() {
+                break;
+            }
+        }
+        range
+    }
+
+    /// Returns the byte range of the block of `FILE` records.
+    ///
+    /// Returns `None` if there are no file records.
+    pub fn file_block_range(&self) -> Option<Range<usize>> {
+        self.block_range(b"FILE ")
+    }
+
+    /// Returns the byte range of the block of `PUBLIC` records.
+    ///
+    /// Returns `None` if there are no public records.
+    pub fn public_block_range(&self) -> Option<Range<usize>> {
+        self.block_range(b"PUBLIC ")
+    }
+
+    /// Returns an iterator over info records.
+    pub fn info_records(&self) -> BreakpadInfoRecords<'data> {
+        BreakpadInfoRecords {
+            lines: Lines::new(self.data),
+            finished: false,
+            full_scan: false,
+        }
+    }
+
+    /// Returns an iterator over info records, scanning the entire file.
+    ///
+    /// Unlike [`info_records`](Self::info_records), this does not stop at the first record that is
+    /// not an INFO record, so it also finds INFO records further down in the file. This requires
+    /// reading the entire file and is considerably slower for large files.
+    pub fn info_records_full(&self) -> BreakpadInfoRecords<'data> {
+        BreakpadInfoRecords {
+            full_scan: true,
+            ..self.info_records()
+        }
+    }
+
+    /// Returns an iterator over the `(scope, info)` pairs of all info records.
+    pub fn infos(&self) -> BreakpadInfos<'data> {
+        BreakpadInfos {
+            records: self.info_records(),
+        }
+    }
+
+    /// Returns the info string of the first info record with the given scope.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_debuginfo::breakpad::BreakpadObject;
+    ///
+    /// let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash\nINFO RELEASE 1.2.3\n";
+    /// let object = BreakpadObject::parse(data).unwrap();
+    /// assert_eq!(object.info("RELEASE"), Some("1.2.3"));
+    /// ```
+    pub fn info(&self, scope: &str) -> Option<&'data str> {
+        self.infos()
+            .find(|&(record_scope, _)| record_scope == scope)
+            .map(|(_, info)| info)
+    }
+
+    /// Returns the info strings of all info records with the given scope.
+    ///
+    /// This is like [`info`](Self::info) for scopes that may occur multiple times.
+    pub fn info_records_for_scope<'s>(
+        &self,
+        scope: &'s str,
+    ) -> impl Iterator<Item = &'data str> + 's
+    where
+        'data: 's,
+    {
+        self.infos()
+            .filter(move |&(record_scope, _)| record_scope == scope)
+            .map(|(_, info)| info)
+    }
+
+    /// Returns an iterator over file records.
+    pub fn file_records(&self) -> BreakpadFileRecords<'data> {
+        BreakpadFileRecords {
+            lines: Lines::new(self.data),
+            finished: false,
+            full_scan: false,
+            raw: None,
+        }
+    }
+
+    /// Returns an iterator over file records, scanning the entire file.
+    ///
+    /// Unlike [`file_records`](Self::file_records), this does not stop after the header, so it also
+    /// finds FILE records placed between functions. This requires reading the entire file and is
+    /// considerably slower for large files.
+    pub fn file_records_full(&self) -> BreakpadFileRecords<'data> {
+        BreakpadFileRecords {
+            full_scan: true,
+            ..self.file_records()
+        }
+    }
+
+    /// Returns a map for file name lookups by id.
+    pub fn file_map(&self) -> BreakpadFileMap<'data> {
+        self.file_records()
+            .filter_map(Result::ok)
+            .map(|file| (file.id, file.name))
+            .collect()
+    }
+
+    /// Returns an iterator over public symbol records.
+    pub fn public_records(&self) -> BreakpadPublicRecords<'data> {
+        BreakpadPublicRecords {
+            lines: Lines::new(self.data),
+            finished: false,
+            full_scan: false,
+            offset: None,
+            raw: None,
+        }
+    }
+
+    /// Returns an iterator over public symbol records starting at the given byte offset into the
+    /// data.
+    ///
+    /// This works like [`func_records_from_offset`](Self::func_records_from_offset). The offset
+    /// should point to the start of a line, usually one previously returned by
+    /// [`BreakpadPublicRecords::offset`], and offsets reported by the returned iterator remain
+    /// relative to the start of the data.
+    pub fn public_records_from_offset(&self, byte_offset: usize) -> BreakpadPublicRecords<'data> {
+        BreakpadPublicRecords {
+            lines: Lines::from_offset(self.data, byte_offset),
+            finished: false,
+            full_scan: false,
+            offset: None,
+            raw: None,
+        }
+    }
+
+    /// Returns an iterator over public symbol records, scanning the entire file.
+    ///
+    /// Unlike [`public_records`](Self::public_records), this does not stop at the first STACK
+    /// record, so it also finds PUBLIC records that follow stack records. Since stack records make up
+    /// a large part of most files, this is noticeably slower.
+    pub fn public_records_full(&self) -> BreakpadPublicRecords<'data> {
+        BreakpadPublicRecords {
+            full_scan: true,
+            ..self.public_records()
+        }
+    }
+
+    /// Returns an iterator over public symbol records with an address in `[start, end)`.
+    ///
+    /// Records are yielded in the order they appear in the file. This does not rely on the records
+    /// being sorted by address, so it checks all records returned by
+    /// [`public_records`](Self::public_records). Errors are passed through.
+    pub fn public_symbols_in_range(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> impl Iterator<Item = Result<BreakpadPublicRecord<'data>, BreakpadError>> {
+        self.public_records().filter(move |result| match result {
+            Ok(record) => (start..end).contains(&record.address),
+            Err(_) => true,
+        })
+    }
+
+    /// Returns all public symbol records sorted by address.
+    ///
+    /// Records at the same address keep their order in the file. This collects all records into
+    /// a vector and fails if a record cannot be parsed. To check whether the records are sorted
+    /// without allocating, use [`validate_public_sorted`](Self::validate_public_sorted).
+    pub fn public_records_sorted(&self) -> Result<Vec<BreakpadPublicRecord<'data>>, BreakpadError> {
+        let mut records = self.public_records().collect::<Result<Vec<_>, _>>()?;
+        records.sort_by_key(|record| record.address);
+        Ok(records)
+    }
+
+    /// Checks whether public symbol records appear in the file sorted by address.
+    ///
+    /// Breakpad requires this order. Records that fail to parse are ignored.
+    pub fn validate_public_sorted(&self) -> bool {
+        let mut previous = 0;
+        for record in self.public_records().flatten() {
+            if record.address < previous {
+                return false;
+            }
+            previous = record.address;
+        }
+        true
+    }
+
+    /// Returns an iterator over function records.
+    pub fn func_records(&self) -> BreakpadFuncRecords<'data> {
+        BreakpadFuncRecords {
+            lines: Lines::new(self.data),
+            finished: false,
+            full_scan: false,
+            offset: None,
+            record_lines: Lines::default(),
+            raw: None,
+        }
+    }
+
+    /// Returns an iterator over function records starting at the given byte offset into the data.
+    ///
+    /// The offset should point to the start of a line, usually one previously returned by
+    /// [`BreakpadFuncRecords::offset`]. Offsets reported by the returned iterator remain relative
+    /// to the start of the data. Combined with an index of function addresses and offsets, this
+    /// allows to binary search for a function and then iterate sequentially from there, without
+    /// scanning the file from the start.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_debuginfo::breakpad::BreakpadObject;
+    ///
+    /// let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+    /// FUNC 1000 10 0 first
+    /// FUNC 2000 10 0 second
+    /// ";
+    /// let object = BreakpadObject::parse(data)?;
+    ///
+    /// let mut records = object.func_records();
+    /// records.next();
+    /// records.next();
+    /// let offset = records.offset().unwrap();
+    ///
+    /// let mut records = object.func_records_from_offset(offset);
+    /// assert_eq!(records.next().unwrap()?.name, "second");
+    /// assert_eq!(records.offset(), Some(offset));
+    /// # Ok::<(), symbolic_debuginfo::breakpad::BreakpadError>(())
+    /// ```
+    pub fn func_records_from_offset(&self, byte_offset: usize) -> BreakpadFuncRecords<'data> {
+        BreakpadFuncRecords {
+            lines: Lines::from_offset(self.data, byte_offset),
+            finished: false,
+            full_scan: false,
+            offset: None,
+            record_lines: Lines::default(),
+            raw: None,
+        }
+    }
+
+    /// Returns an iterator over function records with a size of at least `min_size`.
+    ///
+    /// Records that fail to parse are passed through as errors.
+    pub fn large_functions(
+        &self,
+        min_size: u64,
+    ) -> impl Iterator<Item = Result<BreakpadFuncRecord<'data>, BreakpadError>> {
+        self.func_records().filter(move |result| match result {
+            Ok(func) => func.size >= min_size,
+            Err(_) => true,
+        })
+    }
+
+    /// Returns all function records sorted by descending size.
+    ///
+    /// Functions of equal size keep their order in the file. Fails if a record cannot be parsed.
+    pub fn functions_by_size(&self) -> Result<Vec<BreakpadFuncRecord<'data>>, BreakpadError> {
+        let mut funcs = self.func_records().collect::<Result<Vec<_>, _>>()?;
+        funcs.sort_by_key(|func| std::cmp::Reverse(func.size));
+        Ok(funcs)
+    }
+
+    /// Returns all function records sorted by address.
+    ///
+    /// Records at the same address keep their order in the file. This collects all records into
+    /// a vector and fails if a record cannot be parsed.
+    pub fn func_records_sorted(&self) -> Result<Vec<BreakpadFuncRecord<'data>>, BreakpadError> {
+        let mut records = self.func_records().collect::<Result<Vec<_>, _>>()?;
+        records.sort_by_key(|record| record.address);
+        Ok(records)
+    }
+
+    /// Returns an iterator over function records, scanning the entire file.
+    ///
+    /// Unlike [`func_records`](Self::func_records), this does not stop at the first STACK record, so
+    /// it also finds FUNC records that follow stack records. Since stack records make up a large part
+    /// of most files, this is noticeably slower.
+    pub fn func_records_full(&self) -> BreakpadFuncRecords<'data> {
+        BreakpadFuncRecords {
+            full_scan: true,
+            ..self.func_records()
+        }
+    }
+
+    /// Returns an iterator over the line records of all functions.
+    ///
+    /// Each line record is paired with the address of the function that contains it. Errors when
+    /// parsing function records or line records are passed through. Like
+    /// [`BreakpadFuncRecord::lines`], this skips line records covering no code.
+    pub fn all_line_records(
+        &self,
+    ) -> impl Iterator<Item = Result<(BreakpadLineRecord, u64), BreakpadError>> + 'data {
+        self.func_records().flat_map(|result| {
+            let (func, error) = match result {
+                Ok(func) => (Some(func), None),
+                Err(error) => (None, Some(error)),
+            };
+
+            let lines = func.into_iter().flat_map(|func| {
+                let address = func.address;
+                func.lines()
+                    .map(move |result| result.map(|line| (line, address)))
+            });
+
+            error.map(Err).into_iter().chain(lines)
+        })
+    }
+
+    /// Returns an iterator over all line records that reference the given file id.
+    ///
+    /// This scans the line records of all functions and is therefore `O(n)` in the number of line
+    /// records. Errors are passed through.
+    pub fn line_records_for_file(
+        &self,
+        file_id: u64,
+    ) -> impl Iterator<Item = Result<BreakpadLineRecord, BreakpadError>> + 'data {
+        self.all_line_records()
+            .map(|result| result.map(|(line, _)| line))
+            .filter(move |result| !matches!(result, Ok(line) if line.file_id != file_id))
+    }
+
+    /// Returns an asynchronous stream over function records.
+    ///
+    /// This yields the same records as [`func_records`](Self::func_records), but periodically
+    /// returns control to the executor. See [`BreakpadFuncRecordStream`] for more information.
+    #[cfg(feature = "async")]
+    pub fn func_records_stream(&self) -> BreakpadFuncRecordStream<'data> {
+        BreakpadFuncRecordStream {
+            records: self.func_records(),
+            budget: STREAM_YIELD_INTERVAL,
+        }
+    }
+
+    /// Returns the first function record with exactly the given name.
+    ///
+    /// This scans all function records and is therefore `O(n)`.
+    pub fn func_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<BreakpadFuncRecord<'data>>, BreakpadError> {
+        for record in self.func_records() {
+            let record = record?;
+            if record.name == name {
+                return Ok(Some(record));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the first public symbol record with exactly the given name.
+    ///
+    /// This scans all public symbol records and is therefore `O(n)`.
+    pub fn public_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<BreakpadPublicRecord<'data>>, BreakpadError> {
+        for record in self.public_records() {
+            let record = record?;
+            if record.name == name {
+                return Ok(Some(record));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns an iterator over stack frame records.
+    pub fn stack_records(&self) -> BreakpadStackRecords<'data> {
+        BreakpadStackRecords::new(self.data)
+    }
+
+    /// Returns a copy of this symbol file with its `MODULE` record replaced.
+    ///
+    /// The new record is validated with the same rules as [`parse`](Self::parse): the operating
+    /// system and architecture must be single words, the architecture must be known, and the
+    /// identifier must be a valid debug identifier. The name may contain spaces, but no line breaks.
+    /// Malformed records result in an error of kind [`BreakpadErrorKind::InvalidModuleRecord`],
+    /// [`BreakpadErrorKind::InvalidModuleId`] or [`BreakpadErrorKind::InvalidArchitecture`].
+    /// All other data is copied verbatim, including the line ending of the original header.
+    pub fn with_module_record(
+        &self,
+        module: &BreakpadModuleRecord<'_>,
+    ) -> Result<Vec<u8>, BreakpadError> {
+        let fields = [module.os, module.arch, module.id];
+        if fields
+            .iter()
+            .any(|field| field.contains(char::is_whitespace))
+            || module.name.contains(['\r', '\n'])
+        {
+            return Err(BreakpadError::new(
+                BreakpadErrorKind::InvalidModuleRecord,
+                "module record fields contain invalid whitespace",
+            ));
+        }
+
+        let header = module.to_string();
+        BreakpadModuleRecord::parse(header.as_bytes())
+            .map_err(|error| BreakpadError::new(BreakpadErrorKind::InvalidModuleRecord, error))?;
+        module
+            .id
+            .parse::<DebugId>()
+            .map_err(|_| BreakpadErrorKind::InvalidModuleId)?;
+        module
+            .arch
+            .parse::<Arch>()
+            .map_err(|_| BreakpadErrorKind::InvalidArchitecture)?;
+
+        let content = strip_bom(self.data);
+        let bom = &self.data[..self.data.len() - content.len()];
+        let (line_ending, rest) = match content.iter().position(|&b| b == b'\n') {
+            Some(index) if index > 0 && content[index - 1] == b'\r' => {
+                (&b"\r\n"[..], &content[index + 1..])
+            }
+            Some(index) => (&b"\n"[..], &content[index + 1..]),
+            None => (&b""[..], &b""[..]),
+        };
+
+        let mut output = Vec::with_capacity(self.data.len() + header.len());
+        output.extend_from_slice(bom);
+        output.extend_from_slice(header.as_bytes());
+        output.extend_from_slice(line_ending);
+        output.extend_from_slice(rest);
+        Ok(output)
+    }
+
+    /// Returns a copy of this symbol file with a `FUNC` record synthesized for every `PUBLIC` record.
+    ///
+    /// Stripped binaries often produce symbol files with only `PUBLIC` records, which carry no debug
+    /// information. The synthesized functions take the size of the gap to the next `PUBLIC` or
+    /// `FUNC` address, while the last one gets a size of zero. Their parameter size is zero and they
+    /// have **no line information**. Public symbols at an address that already has a `FUNC` record
+    /// are skipped, as are duplicate public symbols at the same address.
+    ///
+    /// The output lists all `FUNC` records in order of their address. Existing `FUNC` records
+    /// before the first `PUBLIC` or `STACK` record are copied verbatim together with their line
+    /// records and interleaved with the new records. Other records in between them are moved ahead
+    /// of the functions, and all remaining data is copied verbatim.
+    pub fn convert_public_to_func(&self) -> Result<Vec<u8>, BreakpadError> {
+        let insert_at = LineOffsets::new(self.data)
+            .find(|(_, line)| line.starts_with(b"PUBLIC ") || line.starts_with(b"STACK "))
+            .map_or(self.data.len(), |(offset, _)| offset);
+
+        let mut functions = BTreeMap::new();
+        let mut blocks = Vec::new();
+        let mut records = self.func_records();
+        while let Some(record) = records.next() {
+            let address = record?.address;
+            functions.insert(address, None);
+            match records.byte_range() {
+                Some(range) if range.start < insert_at => blocks.push((address, range)),
+                _ => (),
+            }
+        }
+
+        let mut publics = Vec::new();
+        for record in self.public_records() {
+            let record = record?;
+            if functions.contains_key(&record.address) {
+                continue;
+            }
+            functions.insert(record.address, Some(publics.len()));
+            publics.push(record);
+        }
+
+        let mut output = Vec::with_capacity(self.data.len() + publics.len() * 32);
+        let push_line_break = |output: &mut Vec<u8>| {
+            if !output.is_empty() && !output.ends_with(b"\n") {
+                output.push(b'\n');
+            }
+        };
+
+        // Copy everything up to the insertion point except for the function blocks.
+        let mut position = 0;
+        for (_, range) in &blocks {
+            output.extend_from_slice(&self.data[position..range.start]);
+            position = range.end;
+        }
+        output.extend_from_slice(&self.data[position..insert_at]);
+        push_line_break(&mut output);
+
+        // The sort is stable, so functions at the same address keep their order.
+        dmsort::sort_by_key(&mut blocks, |(address, _)| *address);
+        let mut blocks = blocks.into_iter().peekable();
+
+        let mut addresses = functions.iter().peekable();
+        while let Some((&address, index)) = addresses.next() {
+            let record = match index {
+                Some(index) => &publics[*index],
+                None => {
+                    while let Some((_, range)) = blocks.next_if(|(next, _)| *next == address) {
+                        output.extend_from_slice(&self.data[range]);
+                        push_line_break(&mut output);
+                    }
+                    continue;
+                }
+            };
+
+            let size = addresses.peek().map_or(0, |(&next, _)| next - address);
+            let multiple = if record.multiple { "m " } else { "" };
+            writeln!(
+                output,
+                "FUNC {}{:x} {:x} 0 {}",
+                multiple, address, size, record.name
+            )
+            .ok();
+        }
+
+        output.extend_from_slice(&self.data[insert_at..]);
+        Ok(output)
+    }
+
+    /// Checks this object for inconsistencies between its records.
+    ///
+    /// Unlike iterating the records, which silently tolerates most inconsistencies, this reports
+    /// every issue found, including records that cannot be parsed. The data is scanned once, and
+    /// issues are returned in the order they appear in the file.
+    pub fn validate(&self) -> Vec<BreakpadValidationIssue> {
+        let file_map = self.file_map();
+        let mut issues = Vec::new();
+
+        // Mirror the early exits of the record iterators to find records they would skip.
+        let mut past_info = false;
+        let mut past_files = false;
+        let mut past_symbols = false;
+
+        // Mirror `unknown_records` to tell line records from unknown records.
+        let mut in_func = false;
+        let mut func: Option<BreakpadFuncRecord<'_>> = None;
+
+        for (index, line) in Lines::new(self.data).enumerate() {
+            let line_number = index + 1;
+
+            let out_of_order = if line.starts_with(b"MODULE ") {
+                None
+            } else if line.starts_with(b"INFO ") {
+                Some("INFO").filter(|_| past_info)
+            } else if line.starts_with(b"FILE ") {
+                past_info = true;
+                Some("FILE").filter(|_| past_files)
+            } else if line.starts_with(b"FUNC ") || line.starts_with(b"PUBLIC ") {
+                past_info = true;
+                past_files = true;
+                let record = if line.starts_with(b"FUNC ") {
+                    "FUNC"
+                } else {
+                    "PUBLIC"
+                };
+                Some(record).filter(|_| past_symbols)
+            } else {
+                past_info = true;
+                past_files = true;
+                past_symbols |= line.starts_with(b"STACK ");
+                None
+            };
+
+            if let Some(record) = out_of_order {
+                issues.push(BreakpadValidationIssue::OutOfOrderRecord {
+                    line_number,
+                    record,
+                });
+            }
+
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+
+            if !is_known_record(line) {
+                if !in_func || !is_line_record(line) {
+                    issues.push(BreakpadValidationIssue::UnknownRecord { line_number });
+                    continue;
+                }
+
+                let record = match BreakpadLineRecord::parse(line) {
+                    Ok(record) => record,
+                    Err(_) => {
+                        issues.push(BreakpadValidationIssue::InvalidRecord {
+                            line_number,
+                            record: "line",
+                        });
+                        continue;
+                    }
+                };
+
+                // Like `BreakpadFuncRecord::lines`, skip line records covering no code.
+                let func = match &func {
+                    Some(func) if record.size > 0 => func,
+                    _ => continue,
+                };
+
+                if let Some(error) = BreakpadFileIdError::check(&file_map, func.address, &record) {
+                    issues.push(BreakpadValidationIssue::UnknownFileId {
+                        function_address: error.func_address,
+                        line_address: record.address,
+                        file_id: error.file_id,
+                    });
+                }
+
+                let func_end = func.range().end;
+                let line_in_range = matches!(record.end_address(), Some(end) if end <= func_end);
+                if func.size > 0 && (record.address < func.address || !line_in_range) {
+                    issues.push(BreakpadValidationIssue::LineOutOfRange {
+                        function_address: func.address,
+                        line_address: record.address,
+                        line_size: record.size,
+                    });
+                }
+
+                continue;
+            }
+
+            in_func = line.starts_with(b"FUNC ") || line.starts_with(b"INLINE ");
+            if !in_func {
+                func = None;
+            }
+
+            let invalid = |record| BreakpadValidationIssue::InvalidRecord {
+                line_number,
+                record,
+            };
+            let unknown_flags = |flags: &[char]| BreakpadValidationIssue::UnknownFlags {
+                line_number,
+                flags: flags.to_vec(),
+            };
+
+            if line.starts_with(b"FUNC ") {
+                match BreakpadFuncRecord::parse(line, Lines::default()) {
+                    Ok(record) => {
+                        if !record.unknown_flags.is_empty() {
+                            issues.push(unknown_flags(&record.unknown_flags));
+                        }
+                        // Line records of skipped functions are not resolved by the iterators.
+                        func = Some(record).filter(|_| out_of_order.is_none());
+                    }
+                    Err(_) => {
+                        issues.push(invalid("FUNC"));
+                        func = None;
+                    }
+                }
+            } else if line.starts_with(b"PUBLIC ") {
+                match BreakpadPublicRecord::parse(line) {
+                    Ok(record) if !record.unknown_flags.is_empty() => {
+                        issues.push(unknown_flags(&record.unknown_flags));
+                    }
+                    Ok(_) => (),
+                    Err(_) => issues.push(invalid("PUBLIC")),
+                }
+            } else if line.starts_with(b"STACK CFI INIT ") {
+                match BreakpadStackCfiRecord::parse(line) {
+                    Ok(record) if record.parsed_init_rules().is_err() => {
+                        issues.push(BreakpadValidationIssue::InvalidCfiRules { line_number });
+                    }
+                    Ok(_) => (),
+                    Err(_) => issues.push(invalid("STACK CFI INIT")),
+                }
+            } else if line.starts_with(b"STACK CFI ") {
+                match BreakpadStackCfiDeltaRecord::parse(line) {
+                    Ok(record) if record.parsed_rules().is_err() => {
+                        issues.push(BreakpadValidationIssue::InvalidCfiRules { line_number });
+                    }
+                    Ok(_) => (),
+                    Err(_) => issues.push(invalid("STACK CFI")),
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Returns all line records that reference a file id without a corresponding `FILE` record.
+    ///
+    /// This is the most common inconsistency in generated symbol files. It is also reported by
+    /// [`validate`](Self::validate), but this check skips all other records and is therefore
+    /// faster. Returns an error if a function or line record cannot be parsed.
+    pub fn check_file_id_consistency(&self) -> Result<Vec<BreakpadFileIdError>, BreakpadError> {
+        let file_map = self.file_map();
+        let mut errors = Vec::new();
+
+        for func in self.func_records() {
+            let func = func?;
+            for line in func.lines() {
+                errors.extend(BreakpadFileIdError::check(&file_map, func.address, &line?));
+            }
+        }
+
+        Ok(errors)
+    }
+
+    /// Returns all ranges within functions that are not covered by a stack record.
+    ///
+    /// Both `STACK CFI` and `STACK WIN` records count towards coverage. Functions with a size of
+    /// zero cannot be checked and are skipped, as are records that fail to parse. The gaps are
+    /// sorted by function and start address.
+    pub fn verify_cfi_coverage(&self) -> Vec<BreakpadCfiGap> {
+        let mut functions: Vec<_> = self
+            .func_records()
+            .flatten()
+            .filter(|func| func.size > 0)
+            .map(|func| func.range())
+            .collect();
+        dmsort::sort_by_key(&mut functions, |range| range.start);
+
+        let mut stack_ranges: Vec<_> = self
+            .stack_records()
+            .flatten()
+            .map(|record| match record {
+                BreakpadStackRecord::Cfi(cfi) => cfi.range(),
+                BreakpadStackRecord::Win(win) => {
+                    let range = win.code_range();
+                    u64::from(range.start)..u64::from(range.end)
+                }
+            })
+            .filter(|range| !range.is_empty())
+            .collect();
+        dmsort::sort_by_key(&mut stack_ranges, |range| range.start);
+
+        // Merge overlapping and adjacent stack ranges so that each function only needs to be
+        // checked against a sorted list of disjoint ranges.
+        let mut covered: Vec<Range<u64>> = Vec::with_capacity(stack_ranges.len());
+        for range in stack_ranges {
+            match covered.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => covered.push(range),
+            }
+        }
+
+        let mut gaps = Vec::new();
+        for func in functions {
+            let mut cursor = func.start;
+            let first = covered.partition_point(|range| range.end <= func.start);
+
+            for range in &covered[first..] {
+                if range.start >= func.end {
+                    break;
+                }
+
+                if range.start > cursor {
+                    gaps.push(BreakpadCfiGap {
+                        func_addr: func.start,
+                        func_size: func.end - func.start,
+                        gap_start: cursor,
+                        gap_size: range.start - cursor,
+                    });
+                }
+
+                cursor = cursor.max(range.end);
+            }
+
+            if cursor < func.end {
+                gaps.push(BreakpadCfiGap {
+                    func_addr: func.start,
+                    func_size: func.end - func.start,
+                    gap_start: cursor,
+                    gap_size: func.end - cursor,
+                });
+            }
+        }
+
+        gaps
+    }
+
+    /// Writes a minimal symbol file containing only the `MODULE`, `INFO` and `PUBLIC` records.
+    ///
+    /// This is the smallest valid symbol file for consumers that only need symbol names, such as
+    /// crash attribution without line information or stack unwinding. Records are copied verbatim,
+    /// and all other records are omitted.
+    pub fn write_minimal<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        for line in Lines::new(self.data) {
+            if line.starts_with(b"MODULE ")
+                || line.starts_with(b"INFO ")
+                || line.starts_with(b"PUBLIC ")
+            {
+                writer.write_all(line)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes this symbol file in a canonical form.
+    ///
+    /// The output contains the `MODULE` record first, followed by `INFO` records in their original
+    /// order, `FILE` records sorted by id, `FUNC` records sorted by address along with their line
+    /// records, `PUBLIC` records sorted by address, and finally `STACK` records sorted by their start
+    /// address. Exact duplicate records are removed, fields are separated by single spaces, and
+    /// line records covering no code are dropped.
+    ///
+    /// Normalizing the output again yields identical bytes, and parsing it yields the same records
+    /// as parsing the original file. Use [`normalize_lossy`](Self::normalize_lossy) to skip records
+    /// that fail to parse instead of returning an error.
+    pub fn normalize(&self) -> Result<Vec<u8>, BreakpadError> {
+        self.normalize_impl(false).map(|(output, _)| output)
+    }
+
+    /// Writes this symbol file in a canonical form, dropping records that fail to parse.
+    ///
+    /// This works like [`normalize`](Self::normalize), but instead of failing, it omits invalid
+    /// records. If a `FUNC` or `STACK CFI INIT` record is invalid, its line or delta records are
+    /// omitted as well. Returns the output along with the number of omitted records.
+    pub fn normalize_lossy(&self) -> (Vec<u8>, usize) {
+        // In lossy mode, all errors are counted instead of returned.
+        self.normalize_impl(true).unwrap_or_default()
+    }
+
+    fn normalize_impl(&self, lossy: bool) -> Result<(Vec<u8>, usize), BreakpadError> {
+        /// A record rendered into its canonical form, along with its nested records.
+        type Block = (u64, String, Vec<(u64, String)>);
+
+        enum Current {
+            None,
+            Func(usize),
+            Cfi(usize),
+            Dropped,
+        }
+
+        let mut dropped = 0;
+        let mut check = |result: Result<(), BreakpadError>| match result {
+            Err(_) if lossy => {
+                dropped += 1;
+                Ok(false)
+            }
+            Err(error) => Err(error),
+            Ok(()) => Ok(true),
+        };
+
+        let mut infos = Vec::new();
+        let mut files = Vec::new();
+        let mut funcs: Vec<Block> = Vec::new();
+        let mut publics = Vec::new();
+        let mut stacks: Vec<Block> = Vec::new();
+        let mut current = Current::None;
+
+        for line in Lines::new(self.data) {
+            if line.iter().all(u8::is_ascii_whitespace) || line.starts_with(b"MODULE ") {
+                continue;
+            }
+
+            let result = if line.starts_with(b"INFO ") {
+                current = Current::None;
+                BreakpadInfoRecord::parse(line).map(|record| infos.push(record.to_string()))
+            } else if line.starts_with(b"FILE ") {
+                current = Current::None;
+                BreakpadFileRecord::parse(line)
+                    .map(|record| files.push((record.id, record.to_string())))
+            } else if line.starts_with(b"PUBLIC ") {
+                current = Current::None;
+                BreakpadPublicRecord::parse(line)
+                    .map(|record| publics.push((record.address, record.to_string())))
+            } else if line.starts_with(b"FUNC ") {
+                current = Current::Dropped;
+                BreakpadFuncRecord::parse(line, Lines::default()).map(|record| {
+                    current = Current::Func(funcs.len());
+                    funcs.push((record.address, record.to_string(), Vec::new()));
+                })
+            } else if line.starts_with(b"STACK CFI ") && !line.starts_with(b"STACK CFI INIT ") {
+                let result = BreakpadStackCfiDeltaRecord::parse(line);
+                match (&current, result) {
+                    (Current::Cfi(index), Ok(record)) => {
+                        stacks[*index].2.push((record.address, record.to_string()));
+                        Ok(())
+                    }
+                    // Delta records outside of a CFI record cannot be reached by any parser.
+                    (Current::Cfi(_) | Current::Dropped, Err(error)) => Err(error),
+                    (Current::Dropped, Ok(_)) => Err(BreakpadErrorKind::OrphanedRecord.into()),
+                    (_, result) => {
+                        current = Current::None;
+                        result.map(|_| ())
+                    }
+                }
+            } else if line.starts_with(b"STACK ") {
+                current = Current::Dropped;
+                BreakpadStackRecord::parse(line).map(|record| match record {
+                    BreakpadStackRecord::Cfi(record) => {
+                        current = Current::Cfi(stacks.len());
+                        stacks.push((record.start, record.to_string(), Vec::new()));
+                    }
+                    BreakpadStackRecord::Win(record) => {
+                        current = Current::None;
+                        let start = record.code_start.into();
+                        stacks.push((start, record.to_string(), Vec::new()));
+                    }
+                })
+            } else {
+                match (&current, BreakpadLineRecord::parse(line)) {
+                    (Current::Func(index), Ok(record)) => {
+                        // Skip line records for empty ranges, like `BreakpadLineRecords` does.
+                        if record.size > 0 {
+                            funcs[*index].2.push((record.address, record.to_string()));
+                        }
+                        Ok(())
+                    }
+                    (Current::Dropped, Ok(_)) => Err(BreakpadErrorKind::OrphanedRecord.into()),
+                    // Line records outside of a function cannot be reached by any parser.
+                    (_, result) => {
+                        if let Current::Cfi(_) = current {
+                            current = Current::None;
+                        }
+                        result.map(|_| ())
+                    }
+                }
+            };
+
+            check(result)?;
+        }
+
+        let mut output = Vec::with_capacity(self.data.len());
+        writeln!(output, "{}", self.module).ok();
+
+        let mut seen = HashSet::new();
+        for info in &infos {
+            if seen.insert(info) {
+                writeln!(output, "{}", info).ok();
+            }
+        }
+
+        files.sort();
+        files.dedup();
+        publics.sort();
+        publics.dedup();
+
+        for blocks in [&mut funcs, &mut stacks].iter_mut() {
+            for (_, _, nested) in blocks.iter_mut() {
+                nested.sort();
+                nested.dedup();
+            }
+            blocks.sort();
+            blocks.dedup();
+        }
+
+        for (_, file) in &files {
+            writeln!(output, "{}", file).ok();
+        }
+
+        let write_blocks = |output: &mut Vec<u8>, blocks: &[Block]| {
+            for (_, record, nested) in blocks {
+                writeln!(output, "{}", record).ok();
+                for (_, record) in nested {
+                    writeln!(output, "{}", record).ok();
+                }
+            }
+        };
+
+        write_blocks(&mut output, &funcs);
+        for (_, public) in &publics {
+            writeln!(output, "{}", public).ok();
+        }
+        write_blocks(&mut output, &stacks);
+
+        Ok((output, dropped))
+    }
+
+    /// Merges the records of this and another symbol file of the same module.
+    ///
+    /// The output starts with the `MODULE` record of this object, followed by the `INFO` records of
+    /// both objects, and `FILE`, `INLINE_ORIGIN`, `FUNC`, `PUBLIC` and `STACK` records sorted by id
+    /// or address, like the output of [`normalize`](Self::normalize). Records are collected from
+    /// the entire files, regardless of their position. File and inline origin ids of the other
+    /// object are renumbered if they clash with a different file or origin in this object. `INLINE`
+    /// records are kept with their function and treated like its line records. Records at the same
+    /// address are resolved with the given `strategy`. If either object contains several records at
+    /// the same address, only the first one is kept.
+    ///
+    /// Fails if the objects have a different debug id or architecture, or if a record cannot be
+    /// parsed.
+    pub fn merge_with_strategy(
+        &self,
+        other: &BreakpadObject<'_>,
+        strategy: BreakpadMergeStrategy,
+    ) -> Result<Vec<u8>, BreakpadError> {
+        if self.debug_id() != other.debug_id() || self.arch() != other.arch() {
+            return Err(BreakpadErrorKind::ModuleMismatch.into());
+        }
+
+        let mut infos = Vec::new();
+        let mut files = BTreeMap::new();
+        let mut file_names = HashMap::new();
+        let mut origins = BTreeMap::new();
+        let mut origin_names = HashMap::new();
+        let mut funcs = BTreeMap::new();
+        let mut publics = BTreeMap::new();
+        let mut stacks = BTreeMap::new();
+
+        for (index, object) in [self as &BreakpadObject<'_>, other].iter().enumerate() {
+            for info in object.info_records_full() {
+                let info = info?.to_string();
+                if !infos.contains(&info) {
+                    infos.push(info);
+                }
+            }
+
+            // Reuse the id of a file with the same name, or assign a new one on clashes.
+            let mut file_ids = HashMap::new();
+            for file in object.file_records_full() {
+                let file = file?;
+                let record = (file.name, file.checksum);
+                let id = merge_id(&mut files, &mut file_names, file.id, file.name, record);
+                file_ids.insert(file.id, id);
+            }
+
+            let mut origin_ids = HashMap::new();
+            for line in Lines::new(object.data) {
+                if line.starts_with(b"INLINE_ORIGIN ") {
+                    let (origin_id, name) = parse_inline_origin(line)?;
+                    let id = merge_id(&mut origins, &mut origin_names, origin_id, name, name);
+                    origin_ids.insert(origin_id, id);
+                }
+            }
+
+            let mut object_funcs = BTreeMap::new();
+            for func in object.func_records_full() {
+                let func = func?;
+                let mut lines = Vec::new();
+                for line in func.lines() {
+                    let mut line = line?;
+                    line.file_id = file_ids.get(&line.file_id).copied().unwrap_or(line.file_id);
+                    lines.push((line.address, line.to_string()));
+                }
+                for line in func.lines.clone() {
+                    if ends_line_block(line) {
+                        break;
+                    } else if line.starts_with(b"INLINE ") {
+                        lines.push(remap_inline_record(line, &file_ids, &origin_ids)?);
+                    }
+                }
+                object_funcs
+                    .entry(func.address)
+                    .or_insert((func.to_string(), lines));
+            }
+
+            let mut object_publics = BTreeMap::new();
+            for public in object.public_records_full() {
+                let public = public?;
+                object_publics
+                    .entry(public.address)
+                    .or_insert((public.to_string(), Vec::new()));
+            }
+
+            let mut object_stacks = BTreeMap::new();
+            for stack in object.stack_records() {
+                let (address, block) = match stack? {
+                    BreakpadStackRecord::Cfi(record) => {
+                        let deltas = record
+                            .deltas()
+                            .map(|delta| delta.map(|delta| (delta.address, delta.to_string())))
+                            .collect::<Result<_, _>>()?;
+                        (record.start, (record.to_string(), deltas))
+                    }
+                    BreakpadStackRecord::Win(record) => {
+                        (record.code_start.into(), (record.to_string(), Vec::new()))
+                    }
+                };
+                object_stacks.entry(address).or_insert(block);
+            }
+
+            if index == 0 {
+                funcs = object_funcs;
+                publics = object_publics;
+                stacks = object_stacks;
+                continue;
+            }
+
+            for (address, block) in object_funcs {
+                merge_block(&mut funcs, address, block, strategy, true)?;
+            }
+            for (address, block) in object_publics {
+                merge_block(&mut publics, address, block, strategy, false)?;
+            }
+            for (address, block) in object_stacks {
+                merge_block(&mut stacks, address, block, strategy, false)?;
+            }
+        }
+
+        let mut output = Vec::with_capacity(self.data.len() + other.data.len());
+        writeln!(output, "{}", self.module).ok();
+        for info in &infos {
+            writeln!(output, "{}", info).ok();
+        }
+        for (&id, &(name, checksum)) in &files {
+            writeln!(output, "{}", BreakpadFileRecord { id, name, checksum }).ok();
+        }
+        for (id, name) in &origins {
+            writeln!(output, "INLINE_ORIGIN {} {}", id, name).ok();
+        }
+        for blocks in [&funcs, &publics, &stacks].iter() {
+            for (record, nested) in blocks.values() {
+                writeln!(output, "{}", record).ok();
+                for (_, record) in nested {
+                    writeln!(output, "{}", record).ok();
+                }
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Returns the number of bytes taken up by each record type in this symbol file.
+    ///
+    /// Records are classified by their prefix without parsing them. The keys are `"MODULE"`,
+    /// `"INFO"`, `"FILE"`, `"FUNC"`, `"PUBLIC"`, `"STACK CFI INIT"`, `"STACK CFI"` and `"STACK WIN"`,
+    /// as well as `"<LINE>"` for line records and `"<OTHER>"` for all other lines, including empty
+    /// ones. Record types that do not occur are omitted.
+    ///
+    /// Sizes include line breaks, so they add up to the size of the data without a byte order mark.
+    pub fn estimate_size_breakdown(&self) -> BTreeMap<&'static str, usize> {
+        const PREFIXES: &[(&[u8], &str)] = &[
+            (b"MODULE ", "MODULE"),
+            (b"INFO ", "INFO"),
+            (b"FILE ", "FILE"),
+            (b"FUNC ", "FUNC"),
+            (b"PUBLIC ", "PUBLIC"),
+            (b"STACK CFI INIT ", "STACK CFI INIT"),
+            (b"STACK CFI ", "STACK CFI"),
+            (b"STACK WIN ", "STACK WIN"),
+        ];
+
+        let mut breakdown = BTreeMap::new();
+        let mut lines = Lines::new(self.data);
+        while let Some((offset, line)) = lines.next_with_offset() {
+            let size = lines.position() - offset;
+            if size == 0 {
+                continue;
+            }
+
+            let record = match PREFIXES.iter().find(|(prefix, _)| line.starts_with(prefix)) {
+                Some((_, record)) => record,
+                None if matches!(line.first(), Some(b) if b.is_ascii_hexdigit()) => "<LINE>",
+                None => "<OTHER>",
+            };
+            *breakdown.entry(record).or_default() += size;
+        }
+
+        breakdown
+    }
+
+    /// Estimates the heap memory required to fully parse this symbol file, in bytes.
+    ///
+    /// This allows to reject oversized files before building in-memory structures from them. The
+    /// estimate assumes that every line turns into a parsed record of the largest record type, in
+    /// addition to the raw data itself. It only counts lines and is therefore cheap to compute.
+    ///
+    /// This is a rough upper bound, which may be 2–4× the actual usage.
+    pub fn estimate_memory_usage(&self) -> usize {
+        let record_size = [
+            std::mem::size_of::<BreakpadFuncRecord<'_>>(),
+            std::mem::size_of::<BreakpadLineRecord>(),
+            std::mem::size_of::<BreakpadPublicRecord<'_>>(),
+            std::mem::size_of::<BreakpadStackRecord<'_>>(),
+        ]
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or_default();
+
+        let line_count = self.data.iter().filter(|&&b| b == b'\n').count() + 1;
+        line_count
+            .saturating_mul(record_size)
+            .saturating_add(self.data.len())
+    }
+
+    /// Returns an iterator over `STACK CFI` records, skipping `STACK WIN` records.
+    pub fn cfi_records(&self) -> BreakpadStackCfiRecords<'data> {
+        BreakpadStackCfiRecords {
+            lines: Lines::new(self.data),
+            finished: false,
+            offset: None,
+            record_lines: Lines::default(),
+            raw: None,
+        }
+    }
+
+    /// Returns all `STACK CFI INIT` records sorted by start address, including their deltas.
+    ///
+    /// Records at the same address keep their order in the file. This collects all records into
+    /// a vector and fails if a record cannot be parsed.
+    pub fn stack_cfi_records_sorted(
+        &self,
+    ) -> Result<Vec<BreakpadStackCfiRecord<'data>>, BreakpadError> {
+        let mut records = self.cfi_records().collect::<Result<Vec<_>, _>>()?;
+        records.sort_by_key(|record| record.start);
+        Ok(records)
+    }
+
+    /// Returns an iterator over `STACK WIN` records, skipping `STACK CFI` records.
+    pub fn win_records(&self) -> BreakpadStackWinRecords<'data> {
+        BreakpadStackWinRecords {
+            lines: Lines::new(self.data),
+            finished: false,
+            raw: None,
+        }
+    }
+
+    /// Returns an iterator over lines that do not match any known record type.
+    ///
+    /// The record iterators silently skip lines with an unknown prefix, such as records emitted by
+    /// a newer version of `dump_syms`. This yields the one-based line number and the trimmed
+    /// contents of every such line. Empty lines and line records within a `FUNC` block are not
+    /// reported. If a line is not valid UTF-8, only its valid prefix is returned.
+    pub fn unknown_records(&self) -> BreakpadUnknownRecords<'data> {
+        BreakpadUnknownRecords {
+            lines: Lines::new(self.data).enumerate(),
+            in_func: false,
+        }
+    }
+
+    /// Returns the raw data of the Breakpad file.
+    pub fn data(&self) -> &'data [u8] {
+        self.data
+    }
+
+    /// Writes the raw data of the Breakpad file to the given writer.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(self.data)
+    }
+
+    /// Returns a reader over the raw data of the Breakpad file.
+    pub fn as_reader(&self) -> BreakpadObjectReader<'data> {
+        BreakpadObjectReader { data: self.data }
+    }
+}
+
+/// A reader over the raw data of a Breakpad file.
+///
+/// Returned by [`BreakpadObject::as_reader`].
+#[derive(Clone, Debug)]
+pub struct BreakpadObjectReader<'data> {
+    /// The data that has not been read yet.
+    data: &'data [u8],
+}
+
+impl io::Read for BreakpadObjectReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.data.read(buf)
+    }
+}
+
+impl io::BufRead for BreakpadObjectReader<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.data)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.data.consume(amt)
+    }
+}
+
+impl fmt::Debug for BreakpadObject<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BreakpadObject")
+            .field("code_id", &self.code_id())
+            .field("debug_id", &self.debug_id())
+            .field("arch", &self.arch())
+            .field("name", &self.name())
+            .field("has_symbols", &self.has_symbols())
+            .field("has_debug_info", &self.has_debug_info())
+            .field("has_unwind_info", &self.has_unwind_info())
+            .field("is_malformed", &self.is_malformed())
+            .finish()
+    }
+}
+
+impl<'slf, 'data: 'slf> AsSelf<'slf> for BreakpadObject<'data> {
+    type Ref = BreakpadObject<'slf>;
+
+    fn as_self(&'slf self) -> &Self::Ref {
+        self
+    }
+}
+
+impl<'data> Parse<'data> for BreakpadObject<'data> {
+    type Error = BreakpadError;
+
+    fn test(data: &[u8]) -> bool {
+        Self::test(data)
+    }
+
+    fn parse(data: &'data [u8]) -> Result<Self, BreakpadError> {
+        Self::parse(data)
+    }
+}
+
+impl<'data: 'object, 'object> ObjectLike<'data, 'object> for BreakpadObject<'data> {
+    type Error = BreakpadError;
+    type Session = BreakpadDebugSession<'data>;
+    type SymbolIterator = BreakpadSymbolIterator<'data>;
+
+    fn file_format(&self) -> FileFormat {
+        self.file_format()
+    }
+
+    fn code_id(&self) -> Option<CodeId> {
+        self.code_id()
+    }
+
+    fn debug_id(&self) -> DebugId {
+        self.debug_id()
+    }
+
+    fn arch(&self) -> Arch {
+        self.arch()
+    }
+
+    fn kind(&self) -> ObjectKind {
+        self.kind()
+    }
+
+    fn load_address(&self) -> u64 {
+        self.load_address()
+    }
+
+    fn has_symbols(&self) -> bool {
+        self.has_symbols()
+    }
+
+    fn symbols(&self) -> Self::SymbolIterator {
+        self.symbols()
+    }
+
+    fn symbol_map(&self) -> SymbolMap<'data> {
+        self.symbol_map()
+    }
+
+    fn has_debug_info(&self) -> bool {
+        self.has_debug_info()
+    }
+
+    fn debug_session(&self) -> Result<Self::Session, Self::Error> {
+        self.debug_session()
+    }
+
+    fn has_unwind_info(&self) -> bool {
+        self.has_unwind_info()
+    }
+
+    fn has_sources(&self) -> bool {
+        self.has_sources()
+    }
+
+    fn is_malformed(&self) -> bool {
+        self.is_malformed()
+    }
+}
+
+/// An iterator over symbols in the Breakpad object.
+///
+/// Returned by [`BreakpadObject::symbols`](struct.BreakpadObject.html#method.symbols).
+pub struct BreakpadSymbolIterator<'data> {
+    records: BreakpadPublicRecords<'data>,
+}
+
+impl<'data> Iterator for BreakpadSymbolIterator<'data> {
+    type Item = Symbol<'data>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records.find_map(Result::ok).map(|record| Symbol {
+            name: Some(Cow::Borrowed(record.name)),
+            address: record.address,
+            size: 0,
+        })
+    }
+}
+
+/// An iterator over public symbols in a Breakpad object, including Breakpad metadata.
+///
+/// Returned by [`BreakpadObject::public_symbols`].
+#[derive(Clone, Debug)]
+pub struct BreakpadPublicSymbolIterator<'data> {
+    records: BreakpadPublicRecords<'data>,
+}
+
+impl<'data> Iterator for BreakpadPublicSymbolIterator<'data> {
+    type Item = BreakpadSymbol<'data>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records.find_map(Result::ok).map(BreakpadSymbol::from)
+    }
+}
+
+/// The kind of record a [`BreakpadSymbol`] originates from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BreakpadSymbolOrigin {
+    /// A `FUNC` record.
+    Func,
+    /// A `PUBLIC` record.
+    Public,
+}
+
+/// A named symbol from either a `FUNC` or a `PUBLIC` record.
+///
+/// Returned by [`BreakpadObject::all_symbols`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BreakpadSymbol<'d> {
+    /// The address of this symbol relative to the image base (load address).
+    pub address: u64,
+    /// The size of the code covered by this symbol, if known.
+    ///
+    /// This is only known for functions with a nonzero size.
+    pub size: Option<u64>,
+    /// The name of the symbol.
+    pub name: &'d str,
+    /// The kind of record this symbol originates from.
+    pub origin: BreakpadSymbolOrigin,
+    /// The size of the parameters on the runtime stack.
+    pub parameter_size: u64,
+    /// Whether this symbol was referenced multiple times.
+    pub multiple: bool,
+}
+
+impl<'d> BreakpadSymbol<'d> {
+    /// Returns the name of this symbol as [`Name`], detecting whether it is mangled.
+    ///
+    /// Breakpad names are usually demangled, but symbol files produced by some toolchains contain
+    /// raw Itanium, Rust, MSVC or Swift mangled names. Those are marked as
+    /// [`NameMangling::Mangled`] so that they can be demangled by `symbolic-demangle`.
+    pub fn to_name(&self) -> Name<'d> {
+        record_name(self.name)
+    }
+}
+
+impl<'d> From<BreakpadFuncRecord<'d>> for BreakpadSymbol<'d> {
+    fn from(record: BreakpadFuncRecord<'d>) -> Self {
+        Self {
+            address: record.address,
+            size: Some(record.size).filter(|&size| size > 0),
+            name: record.name,
+            origin: BreakpadSymbolOrigin::Func,
+            parameter_size: record.parameter_size,
+            multiple: record.multiple,
+        }
+    }
+}
+
+impl<'d> From<BreakpadPublicRecord<'d>> for BreakpadSymbol<'d> {
+    fn from(record: BreakpadPublicRecord<'d>) -> Self {
+        Self {
+            address: record.address,
+            size: None,
+            name: record.name,
+            origin: BreakpadSymbolOrigin::Public,
+            parameter_size: record.parameter_size,
+            multiple: record.multiple,
+        }
+    }
+}
+
+/// The number of functions whose line records are kept by [`BreakpadDebugSession::lookup`].
+const DEFAULT_LOOKUP_CACHE_CAPACITY: usize = 128;
+
+/// The source location of an address, returned by [`BreakpadDebugSession::lookup`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BreakpadSourceLocation<'d> {
+    /// The start address of the function containing the address.
+    pub function_address: u64,
+    /// The name of the function containing the address.
+    pub function_name: &'d str,
+    /// The path of the source file, if a line record covers the address.
+    ///
+    /// This is `None` if the line record references an unknown file.
+    pub file: Option<&'d str>,
+    /// The line number, if a line record covers the address.
+    pub line: Option<u64>,
+}
+
+/// A function returned by [`BreakpadDebugSession::dedupe_functions`].
+#[derive(Clone, Debug)]
+pub struct BreakpadDedupedFunction<'d> {
+    /// The function that was kept for its address range.
+    pub function: Function<'d>,
+    /// Names of other functions with the same address range that were dropped in favor of this
+    /// one, in the order they appear in the file.
+    pub aliases: Vec<Name<'d>>,
+}
+
+/// A function in the address index of a [`BreakpadDebugSession`].
+struct BreakpadIndexedFunction<'d> {
+    /// The start address of the function.
+    start: u64,
+    /// The end address of the function, using its inferred size for zero-sized functions.
+    end: u64,
+    /// The largest end address of this function and all functions preceding it in the index.
+    ///
+    /// This increases monotonically, so searches can stop at functions that cannot overlap.
+    max_end: u64,
+    record: BreakpadFuncRecord<'d>,
+}
+
+/// Functions and recently used line records for address lookups.
+struct BreakpadLookupCache<'d> {
+    /// All functions sorted by start address. Built on first use.
+    functions: Option<Vec<BreakpadIndexedFunction<'d>>>,
+    /// Sorted line records by function address, with the most recently used function last.
+    lines: Vec<(u64, Arc<[BreakpadLineRecord]>)>,
+    /// The maximum number of functions in `lines`.
+    capacity: usize,
+    /// The number of times line records of a function have been parsed.
+    misses: usize,
+}
+
+impl Default for BreakpadLookupCache<'_> {
+    fn default() -> Self {
+        Self {
+            functions: None,
+            lines: Vec::new(),
+            capacity: DEFAULT_LOOKUP_CACHE_CAPACITY,
+            misses: 0,
+        }
+    }
+}
+
+impl<'d> BreakpadLookupCache<'d> {
+    /// Returns the sorted line records of the given function, parsing them on a cache miss.
+    fn lines(&mut self, record: &BreakpadFuncRecord<'d>) -> Arc<[BreakpadLineRecord]> {
+        if let Some(index) = self.lines.iter().position(|(a, _)| *a == record.address) {
+            let entry = self.lines.remove(index);
+            let lines = entry.1.clone();
+            self.lines.push(entry);
+            return lines;
+        }
+
+        let mut lines: Vec<_> = record.lines().flatten().collect();
+        dmsort::sort_by_key(&mut lines, |line| line.address);
+        let lines: Arc<[_]> = lines.into();
+        self.misses += 1;
+
+        if self.capacity > 0 {
+            if self.lines.len() >= self.capacity {
+                self.lines.remove(0);
+            }
+            self.lines.push((record.address, lines.clone()));
+        }
+
+        lines
+    }
+}
+
+/// Debug session for Breakpad objects.
+pub struct BreakpadDebugSession<'data> {
+    file_map: BreakpadFileMap<'data>,
+    checksums: BTreeMap<u64, &'data str>,
+    func_records: BreakpadFuncRecords<'data>,
+    public_records: BreakpadPublicRecords<'data>,
+    inferred_sizes: BTreeMap<u64, u64>,
+    canonicalize: bool,
+    path_mappings: Vec<(String, String)>,
+    /// Paths of files that differ from their records after canonicalization and path mapping.
+    rewritten_paths: BTreeMap<u64, String>,
+    lookup_cache: Mutex<BreakpadLookupCache<'data>>,
+}
+
+impl<'data> BreakpadDebugSession<'data> {
+    /// Returns an iterator over all functions in this debug file.
+    pub fn functions(&self) -> BreakpadFunctionIterator<'_> {
+        BreakpadFunctionIterator {
+            file_map: &self.file_map,
+            inferred_sizes: &self.inferred_sizes,
+            rewritten_paths: &self.rewritten_paths,
+            func_records: self.func_records.clone(),
+            strict: false,
+            clamp_lines: false,
+        }
+    }
+
+    /// Returns an iterator over all functions in this debug file, rejecting unknown file ids.
+    ///
+    /// By default, line records referencing a file id without a corresponding `FILE` record are
+    /// resolved to an empty path. This iterator instead yields an error of kind
+    /// [`BreakpadErrorKind::UnknownFileId`] for the function containing such a line record.
+    pub fn functions_strict(&self) -> BreakpadFunctionIterator<'_> {
+        BreakpadFunctionIterator {
+            strict: true,
+            ..self.functions()
+        }
+    }
+
+    /// Returns all functions sorted by address, with one function per address range.
+    ///
+    /// Identical code folding and identical template instantiations result in multiple `FUNC`
+    /// records at the same address, usually marked with the `m` flag. Of all functions with the
+    /// same address and size, this keeps the first one that has line records, or the first one if
+    /// none of them has line records. The names of the other functions are collected in
+    /// [`BreakpadDedupedFunction::aliases`], so that they can still be displayed.
+    pub fn dedupe_functions(&self) -> Result<Vec<BreakpadDedupedFunction<'_>>, BreakpadError> {
+        let mut functions = self.functions().collect::<Result<Vec<_>, _>>()?;
+        // The sort is stable, which preserves the file order of functions in the same range.
+        functions.sort_by_key(|function| (function.address, function.size));
+
+        let mut deduped: Vec<BreakpadDedupedFunction<'_>> = Vec::with_capacity(functions.len());
+        for function in functions {
+            let existing = match deduped.last_mut() {
+                Some(existing)
+                    if existing.function.address == function.address
+                        && existing.function.size == function.size =>
+                {
+                    existing
+                }
+                _ => {
+                    deduped.push(BreakpadDedupedFunction {
+                        function,
+                        aliases: Vec::new(),
+                    });
+                    continue;
+                }
+            };
+
+            let alias = if existing.function.lines.is_empty() && !function.lines.is_empty() {
+                std::mem::replace(&mut existing.function, function).name
+            } else {
+                function.name
+            };
+
+            let is_known = alias.as_str() == existing.function.name.as_str()
+                || existing
+                    .aliases
+                    .iter()
+                    .any(|a| a.as_str() == alias.as_str());
+            if !is_known {
+                existing.aliases.push(alias);
+            }
+        }
+
+        Ok(deduped)
+    }
+
+    /// Infers sizes for `FUNC` records that declare a size of zero.
+    ///
+    /// Some symbol dumpers write `FUNC` records with a size of `0` if they cannot determine the
+    /// actual function size. Such functions never match range-based lookups. After calling this
+    /// method, [`functions`](Self::functions) reports the distance from each zero-sized function to
+    /// the next `FUNC` or `PUBLIC` address as its size, capped at `max_size`.
+    ///
+    /// A zero-sized function at the end of the module has no successor to measure against, so its
+    /// size cannot be inferred and remains zero. Parsed records are not affected by this and keep
+    /// reporting the size written in the file. Use [`inferred_size`](Self::inferred_size) to check
+    /// whether the size of a function was inferred.
+    pub fn infer_function_sizes(&mut self, max_size: u64) -> Result<(), BreakpadError> {
+        let mut addresses = Vec::new();
+        let mut zero_sized = Vec::new();
+
+        for record in self.func_records.clone() {
+            let record = record?;
+            if record.size == 0 {
+                zero_sized.push(record.address);
+            }
+            addresses.push(record.address);
+        }
+
+        for record in self.public_records.clone() {
+            addresses.push(record?.address);
+        }
+
+        dmsort::sort(&mut addresses);
+        addresses.dedup();
+
+        self.inferred_sizes.clear();
+        self.lookup_cache.get_mut().unwrap().functions = None;
+        for address in zero_sized {
+            let index = match addresses.binary_search(&address) {
+                Ok(index) => index + 1,
+                Err(index) => index,
+            };
+
+            if let Some(next) = addresses.get(index) {
+                let size = (next - address).min(max_size);
+                self.inferred_sizes.insert(address, size);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the inferred size of the zero-sized function at the given address.
+    ///
+    /// This returns `None` if sizes have not been inferred via
+    /// [`infer_function_sizes`](Self::infer_function_sizes), if there is no zero-sized function at
+    /// this address, or if its size could not be inferred.
+    pub fn inferred_size(&self, address: u64) -> Option<u64> {
+        self.inferred_sizes.get(&address).copied()
+    }
+
+    /// Looks up the function, file and line for the given address.
+    ///
+    /// The first lookup builds an index of all functions. The line records of a function are
+    /// parsed and sorted when it is first looked up, and then kept in a cache so that subsequent
+    /// lookups in the same function only require a binary search. The cache holds the line records
+    /// of up to 128 functions, evicting the least recently used one when full. Use
+    /// [`set_lookup_cache_capacity`](Self::set_lookup_cache_capacity) to change this.
+    ///
+    /// Records that fail to parse are ignored. Returns `None` if no function covers the address.
+    /// Functions with a size of zero cover no addresses unless their size has been inferred via
+    /// [`infer_function_sizes`](Self::infer_function_sizes). If functions overlap, the covering
+    /// function with the closest start address is used.
+    pub fn lookup(&self, address: u64) -> Option<BreakpadSourceLocation<'_>> {
+        let mut cache = self.lookup_cache.lock().unwrap();
+
+        let functions = self.function_index(&mut cache);
+        let index = functions.partition_point(|function| function.start <= address);
+        let function = functions[..index]
+            .iter()
+            .rev()
+            .take_while(|function| function.max_end > address)
+            .find(|function| function.end > address)?;
+
+        let (function_address, function_name) = (function.start, function.record.name);
+        let record = function.record.clone();
+        let lines = cache.lines(&record);
+        drop(cache);
+
+        let line = lines
+            .partition_point(|line| line.address <= address)
+            .checked_sub(1)
+            .map(|index| &lines[index])
+            .filter(|line| address < line.range().end);
+
+        Some(BreakpadSourceLocation {
+            function_address,
+            function_name,
+            file: line.and_then(|line| match self.rewritten_paths.get(&line.file_id) {
+                Some(path) => Some(path.as_str()),
+                None => line.filename(&self.file_map),
+            }),
+            line: line.map(|line| line.line),
+        })
+    }
+
+    /// Returns the index of all functions sorted by start address, building it on first use.
+    fn function_index<'c>(
+        &self,
+        cache: &'c mut BreakpadLookupCache<'data>,
+    ) -> &'c [BreakpadIndexedFunction<'data>] {
+        cache.functions.get_or_insert_with(|| {
+            let mut functions: Vec<_> = self
+                .func_records
+                .clone()
+                .flatten()
+                .map(|record| {
+                    let size = match record.size {
+                        0 => self.inferred_size(record.address).unwrap_or(0),
+                        size => size,
+                    };
+                    let end = record.address.saturating_add(size);
+                    BreakpadIndexedFunction {
+                        start: record.address,
+                        end,
+                        max_end: end,
+                        record,
+                    }
+                })
+                .collect();
+            dmsort::sort_by_key(&mut functions, |function| function.start);
+
+            let mut max_end = 0;
+            for function in &mut functions {
+                max_end = max_end.max(function.end);
+                function.max_end = max_end;
+            }
+            functions
+        })
+    }
+
+    /// Returns all functions intersecting the given address range, sorted by address.
+    ///
+    /// A function is included if its address range overlaps `range`. Functions with a size of zero
+    /// are included if their address lies within `range`, unless their size has been inferred via
+    /// [`infer_function_sizes`](Self::infer_function_sizes). This uses the same index of functions
+    /// as [`lookup`](Self::lookup), so line records are only parsed for the returned functions.
+    pub fn functions_in_range(
+        &self,
+        range: Range<u64>,
+    ) -> impl Iterator<Item = Result<Function<'_>, BreakpadError>> + '_ {
+        let mut cache = self.lookup_cache.lock().unwrap();
+        let functions = self.function_index(&mut cache);
+
+        // Functions before `first` end before the range. Zero-sized functions at its start are kept.
+        let first = functions.partition_point(|function| function.max_end < range.start);
+        let last = functions.partition_point(|function| function.start < range.end);
+        let records: Vec<_> = functions[first..last.max(first)]
+            .iter()
+            .filter(|f| f.end > range.start || (f.start == f.end && f.start >= range.start))
+            .map(|function| function.record.clone())
+            .collect();
+        drop(cache);
+
+        let converter = self.functions();
+        records
+            .into_iter()
+            .map(move |record| converter.convert(record))
+    }
+
+    /// Sets the number of functions whose line records are cached by [`lookup`](Self::lookup).
+    ///
+    /// A capacity of zero disables caching.
+    pub fn set_lookup_cache_capacity(&mut self, capacity: usize) {
+        let cache = self.lookup_cache.get_mut().unwrap();
+        cache.capacity = capacity;
+        let excess = cache.lines.len().saturating_sub(capacity);
+        cache.lines.drain(..excess);
+    }
+
+    /// Returns an iterator over all source files in this debug file.
+    pub fn files(&self) -> BreakpadFileIterator<'_> {
+        BreakpadFileIterator {
+            files: self.file_map.iter(),
+            rewritten_paths: &self.rewritten_paths,
+        }
+    }
+
+    /// Returns the checksum of the source file with the given file ID.
+    ///
+    /// Returns `None` if there is no `FILE` record with this ID or if the record has no checksum.
+    /// See [`BreakpadFileRecord`] for how checksums are recognized. Use
+    /// [`files_with_ids`](Self::files_with_ids) to look up the checksums of all files.
+    pub fn file_checksum(&self, id: u64) -> Option<&'data str> {
+        self.checksums.get(&id).copied()
+    }
+
+    /// Returns an iterator over all source files in this debug file along with their file IDs.
+    ///
+    /// This yields the same entries as [`files`](Self::files), paired with the ID of the `FILE`
+    /// record that line records refer to.
+    pub fn files_with_ids(&self) -> BreakpadFileWithIdIterator<'_> {
+        BreakpadFileWithIdIterator {
+            files: self.files(),
+        }
+    }
+
+    /// Returns the source file with the given file ID.
+    ///
+    /// Returns `None` if there is no `FILE` record with this ID.
+    pub fn file_by_id(&self, id: u64) -> Option<FileEntry<'_>> {
+        let path = match self.rewritten_paths.get(&id) {
+            Some(path) => path.as_str(),
+            None => self.file_map.get(&id)?,
+        };
+
+        Some(FileEntry {
+            compilation_dir: &[],
+            info: FileInfo::from_path(path.as_bytes()),
+        })
+    }
+
+    /// Returns an iterator over all unique source file paths in this debug file.
+    ///
+    /// Unlike [`files`](Self::files), which yields one entry per `FILE` record, this yields every
+    /// path only once, even if it appears under multiple file IDs.
+    pub fn files_dedup(&self) -> impl Iterator<Item = Result<FileEntry<'_>, BreakpadError>> {
+        let mut seen = HashSet::new();
+        let rewritten_paths = &self.rewritten_paths;
+        self.file_map
+            .iter()
+            .map(move |(id, path)| rewritten_paths.get(id).map_or(*path, String::as_str))
+            .filter(move |path| seen.insert(*path))
+            .map(|path| {
+                Ok(FileEntry {
+                    compilation_dir: &[],
+                    info: FileInfo::from_path(path.as_bytes()),
+                })
+            })
+    }
+
+    /// Canonicalizes Windows paths of all files in this session.
+    ///
+    /// Symbols converted from PDBs contain Windows paths such as `C:\build\src\foo.cpp`. After
+    /// calling this method, [`files`](Self::files) and [`functions`](Self::functions) report such
+    /// paths with forward slashes and a lowercase drive letter, such as `c:/build/src/foo.cpp`. Other
+    /// paths are not modified. This does not affect the names of parsed [`BreakpadFileRecord`]s.
+    pub fn canonicalize_paths(&mut self) {
+        self.canonicalize = true;
+        self.rewrite_paths();
+    }
+
+    /// Replaces prefixes of file paths in this session, such as build directories.
+    ///
+    /// Each mapping is a pair of a path prefix and its replacement. The prefix only matches entire
+    /// path components, and forward slashes and backslashes are treated as equal, so the prefix
+    /// `C:\build` matches `C:\build\src\foo.cpp` and `C:/build/src/foo.cpp`, but not
+    /// `C:\builds\foo.cpp`. Drive letters match regardless of case. If multiple prefixes match,
+    /// the longest one wins. An empty replacement turns paths into relative paths.
+    ///
+    /// After calling this method, [`files`](Self::files) and [`functions`](Self::functions) report
+    /// the mapped paths. Calling it again replaces all previous mappings. Path mapping is applied
+    /// before [`canonicalize_paths`](Self::canonicalize_paths). This does not affect the names of
+    /// parsed [`BreakpadFileRecord`]s.
+    pub fn set_path_mappings(&mut self, mappings: Vec<(String, String)>) {
+        self.path_mappings = mappings;
+        self.rewrite_paths();
+    }
+
+    /// Recomputes the paths of all files after the path options have changed.
+    fn rewrite_paths(&mut self) {
+        let mut rewritten_paths = BTreeMap::new();
+        for (&id, path) in &self.file_map {
+            let mapped = map_path(&self.path_mappings, path);
+            let canonical = if self.canonicalize {
+                canonicalize_path(mapped.as_deref().unwrap_or(path))
+            } else {
+                None
+            };
+
+            if let Some(rewritten) = canonical.or(mapped) {
+                rewritten_paths.insert(id, rewritten);
+            }
+        }
+        self.rewritten_paths = rewritten_paths;
+    }
+
+    /// Looks up a file's source contents by its full canonicalized path.
+    ///
+    /// The given path must be canonicalized.
+    pub fn source_by_path(&self, _path: &str) -> Result<Option<Cow<'_, str>>, BreakpadError> {
+        Ok(None)
+    }
+}
+
+impl<'data, 'session> DebugSession<'session> for BreakpadDebugSession<'data> {
+    type Error = BreakpadError;
+    type FunctionIterator = BreakpadFunctionIterator<'session>;
+    type FileIterator = BreakpadFileIterator<'session>;
+
+    fn functions(&'session self) -> Self::FunctionIterator {
+        self.functions()
+    }
+
+    fn files(&'session self) -> Self::FileIterator {
+        self.files()
+    }
+
+    fn source_by_path(&self, path: &str) -> Result<Option<Cow<'_, str>>, Self::Error> {
+        self.source_by_path(path)
+    }
+}
+
+/// Converts backslashes to forward slashes and lowercases the drive letter of a Windows path.
+///
+/// Returns `None` if the path does not need to be changed.
+fn canonicalize_path(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_uppercase() && bytes[1] == b':';
+    if !has_drive && !path.contains('\\') {
+        return None;
+    }
+
+    let mut canonical = path.replace('\\', "/");
+    if has_drive {
+        canonical[..1].make_ascii_lowercase();
+    }
+
+    Some(canonical)
+}
+
+/// Returns whether the given byte is a path separator on any platform.
+fn is_path_separator(byte: u8) -> bool {
+    byte == b'/' || byte == b'\\'
+}
+
+/// Strips a prefix of entire path components from a path.
+///
+/// Path separators match each other, and a drive letter matches regardless of case. Returns the
+/// remainder of the path without its leading separator.
+fn strip_path_prefix<'p>(path: &'p str, prefix: &str) -> Option<&'p str> {
+    let prefix = prefix.trim_end_matches(['/', '\\']);
+    let path_bytes = path.as_bytes();
+    if path_bytes.len() < prefix.len() {
+        return None;
+    }
+
+    let has_drive = prefix.as_bytes().get(1) == Some(&b':');
+    let matches = path_bytes
+        .iter()
+        .zip(prefix.bytes())
+        .enumerate()
+        .all(|(index, (&a, b))| {
+            a == b
+                || (is_path_separator(a) && is_path_separator(b))
+                || (index == 0 && has_drive && a.eq_ignore_ascii_case(&b))
+        });
+    if !matches {
+        return None;
+    }
+
+    match path_bytes.get(prefix.len()) {
+        None => Some(""),
+        Some(&byte) if is_path_separator(byte) => Some(&path[prefix.len() + 1..]),
+        Some(_) => None,
+    }
+}
+
+/// Applies the longest matching path mapping to a path.
+///
+/// Returns `None` if no mapping matches.
+fn map_path(mappings: &[(String, String)], path: &str) -> Option<String> {
+    let (_, replacement, remainder) = mappings
+        .iter()
+        .filter_map(|(prefix, replacement)| {
+            let remainder = strip_path_prefix(path, prefix)?;
+            Some((path.len() - remainder.len(), replacement, remainder))
+        })
+        .max_by_key(|&(matched, _, _)| matched)?;
+
+    if remainder.is_empty() {
+        return Some(replacement.clone());
+    }
+
+    let replacement = replacement.trim_end_matches(['/', '\\']);
+    if replacement.is_empty() {
+        return Some(remainder.to_owned());
+    }
+
+    // Join with the separator that followed the prefix in the original path.
+    let separator = path.as_bytes()[path.len() - remainder.len() - 1] as char;
+    Some(format!("{}{}{}", replacement, separator, remainder))
+}
+
+/// An iterator over source files in a Breakpad object.
+pub struct BreakpadFileIterator<'s> {
+    files: std::collections::btree_map::Iter<'s, u64, &'s str>,
+    rewritten_paths: &'s BTreeMap<u64, String>,
+}
+
+impl<'s> Iterator for BreakpadFileIterator<'s> {
+    type Item = Result<FileEntry<'s>, BreakpadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_, file) = self.next_with_id()?;
+        Some(Ok(file))
+    }
+}
+
+impl<'s> BreakpadFileIterator<'s> {
+    fn next_with_id(&mut self) -> Option<(u64, FileEntry<'s>)> {
+        let (id, path) = self.files.next()?;
+        let path = self.rewritten_paths.get(id).map_or(*path, String::as_str);
+        Some((
+            *id,
+            FileEntry {
+                compilation_dir: &[],
+                info: FileInfo::from_path(path.as_bytes()),
+            },
+        ))
+    }
+}
+
+/// An iterator over source files and their file IDs in a Breakpad object.
+///
+/// Returned by [`BreakpadDebugSession::files_with_ids`].
+pub struct BreakpadFileWithIdIterator<'s> {
+    files: BreakpadFileIterator<'s>,
+}
+
+impl<'s> Iterator for BreakpadFileWithIdIterator<'s> {
+    type Item = Result<(u64, FileEntry<'s>), BreakpadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.files.next_with_id().map(Ok)
+    }
+}
+
+/// An iterator over functions in a Breakpad object.
+pub struct BreakpadFunctionIterator<'s> {
+    file_map: &'s BreakpadFileMap<'s>,
+    inferred_sizes: &'s BTreeMap<u64, u64>,
+    rewritten_paths: &'s BTreeMap<u64, String>,
+    func_records: BreakpadFuncRecords<'s>,
+    strict: bool,
+    clamp_lines: bool,
+}
+
+impl<'s> BreakpadFunctionIterator<'s> {
+    /// Clamps line records to the address range of their function.
+    ///
+    /// Line records extending past the start or end of their function are shortened to the part
+    /// within the function. Line records entirely outside of the function are dropped. Functions
+    /// with a size of zero are not clamped, unless their size can be inferred from the following
+    /// function.
+    pub fn clamp_lines(self) -> Self {
+        Self {
+            clamp_lines: true,
+            ..self
+        }
+    }
+
+    fn convert(&self, record: BreakpadFuncRecord<'s>) -> Result<Function<'s>, BreakpadError> {
+        let size = match record.size {
+            0 => self
+                .inferred_sizes
+                .get(&record.address)
+                .copied()
+                .unwrap_or(0),
+            size => size,
+        };
+
+        let mut lines = Vec::new();
+        for line in record.lines() {
+            let mut line = line?;
+            if self.clamp_lines && size > 0 {
+                let start = line.address.max(record.address);
+                let end = line.range().end.min(record.address.saturating_add(size));
+                if start >= end {
+                    continue;
+                }
+                line.address = start;
+                line.size = end - start;
+            }
+
+            let filename = match line.filename(self.file_map) {
+                Some(filename) => filename,
+                None if self.strict => {
+                    return Err(BreakpadErrorKind::UnknownFileId {
+                        function_address: record.address,
+                        line_address: line.address,
+                        file_id: line.file_id,
+                    }
+                    .into())
+                }
+                None => "",
+            };
+            let filename = self
+                .rewritten_paths
+                .get(&line.file_id)
+                .map_or(filename, String::as_str);
+
+            lines.push(LineInfo {
+                address: line.address,
+                size: Some(line.size),
+                file: FileInfo::from_path(filename.as_bytes()),
+                line: line.line,
+            });
+        }
+
+        Ok(Function {
+            address: record.address,
+            size,
+            name: record_name(record.name),
+            compilation_dir: &[],
+            lines,
+            inlinees: Vec::new(),
+            inline: false,
+        })
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'s> BreakpadFunctionIterator<'s> {
+    /// Parses all remaining functions in parallel, keeping their order in the file.
+    ///
+    /// `FUNC` records are located with a quick scan over the data first. Their headers and line
+    /// records are then parsed on the rayon thread pool.
+    fn par_results(self) -> Vec<Result<Function<'s>, BreakpadError>> {
+        use rayon::prelude::*;
+
+        // Mirrors `BreakpadFuncRecords::next`, but only matches the record prefix.
+        let mut records = Vec::new();
+        let mut lines = self.func_records.lines.clone();
+        if !self.func_records.finished {
+            while let Some(line) = lines.next() {
+                if !self.func_records.full_scan && line.starts_with(b"STACK ") {
+                    break;
+                }
+
+                if line.starts_with(b"FUNC ") {
+                    records.push((line, lines.clone()));
+                }
+            }
+        }
+
+        records
+            .into_par_iter()
+            .map(|(line, lines)| self.convert(BreakpadFuncRecord::parse(line, lines)?))
+            .collect()
+    }
+
+    /// Collects all remaining functions, parsing them in parallel.
+    ///
+    /// This returns the same functions in the same order as collecting this iterator into a
+    /// `Result<Vec<_>, _>`. If any function fails to parse, the error of the first failing function
+    /// in file order is returned.
+    pub fn par_collect(self) -> Result<Vec<Function<'s>>, BreakpadError> {
+        self.par_results().into_iter().collect()
+    }
+
+    /// Collects all remaining functions, parsing them in parallel and skipping invalid ones.
+    ///
+    /// Returns all functions that could be parsed, along with the errors of all others. Both are
+    /// in file order.
+    pub fn par_collect_lenient(self) -> (Vec<Function<'s>>, Vec<BreakpadError>) {
+        let mut functions = Vec::new();
+        let mut errors = Vec::new();
+        for result in self.par_results() {
+            match result {
+                Ok(function) => functions.push(function),
+                Err(error) => errors.push(error),
+            }
+        }
+        (functions, errors)
+    }
+}
+
+impl<'s> Iterator for BreakpadFunctionIterator<'s> {
+    type Item = Result<Function<'s>, BreakpadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.func_records.next() {
+            Some(Ok(record)) => Some(self.convert(record)),
+            Some(Err(error)) => Some(Err(error)),
+            None => None,
+        }
+    }
+}
+
+impl std::iter::FusedIterator for BreakpadFunctionIterator<'_> {}
+
+mod parsing {
+    use nom::branch::alt;
+    use nom::bytes::complete::{take_while, take_while_m_n};
+    use nom::character::complete::{char, hex_digit1, multispace1, satisfy};
+    use nom::combinator::{cond, eof, rest};
+    use nom::multi::{fold_many0, separated_list1};
+    use nom::sequence::{pair, tuple};
+    use nom::{IResult, Parser};
+    use nom_supreme::error::ErrorTree;
+    use nom_supreme::final_parser::{Location, RecreateContext};
+    use nom_supreme::parser_ext::ParserExt;
+    use nom_supreme::tag::complete::tag;
+
+    use super::*;
+
+    type ParseResult<'a, T> = IResult<&'a str, T, ErrorTree<&'a str>>;
+    pub type ParseBreakpadError = ErrorTree<ErrorLine>;
+
+    /// A line with a 1-based column position, used for displaying errors.
+    ///
+    /// With the default formatter, this prints the line followed by the column number.
+    /// With the alternate formatter (using `:#`), it prints the line and a caret
+    /// pointing at the column position.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use symbolic_debuginfo::breakpad::parsing::ErrorLine;
+    ///
+    /// let error_line = ErrorLine {
+    ///     line: "This line cnotains a typo.".to_string(),
+    ///     column: 12,
+    /// };
+    ///
+    /// // "This line cnotains a typo.", column 12
+    /// println!("{}", error_line);
+    ///
+    /// // "This line cnotains a typo."
+    /// //             ^
+    /// println!("{:#}", error_line);
+    /// ```
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct ErrorLine {
+        /// A line of text containing an error.
+        pub line: String,
+
+        /// The position of the error, 1-based.
+        pub column: usize,
+    }
+
+    impl<'a> RecreateContext<&'a str> for ErrorLine {
+        fn recreate_context(original_input: &'a str, tail: &'a str) -> Self {
+            let Location { column, .. } = Location::recreate_context(original_input, tail);
+            Self {
+                line: original_input.to_string(),
+                column,
+            }
+        }
+    }
+
+    impl fmt::Display for ErrorLine {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            if f.alternate() {
+                writeln!(f)?;
+            }
+
+            write!(f, "\"{}\"", self.line)?;
+
+            if f.alternate() {
+                writeln!(f, "\n{:>width$}", "^", width = self.column + 1)?;
+            } else {
+                write!(f, ", column {}", self.column)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Parse a sequence of decimal digits as a number of the given type.
+    macro_rules! num_dec {
+        ($ty:ty) => {
+            nom::character::complete::digit1.map_res(|s: &str| s.parse::<$ty>())
+        };
+    }
+
+    /// Parse a sequence of hexadecimal digits as a number of the given type.
+    ///
+    /// The digits may be preceded by a `0x` or `0X` prefix, which some generators emit.
+    macro_rules! num_hex {
+        ($ty:ty) => {
+            nom::bytes::complete::tag_no_case("0x")
+                .opt()
+                .precedes(nom::character::complete::hex_digit1)
+                .map_res(|n| <$ty>::from_str_radix(n, 16))
+        };
+    }
+
+    /// Parse a sequence of non-whitespace characters.
+    fn non_whitespace(input: &str) -> ParseResult<&str> {
+        take_while(|c: char| !c.is_whitespace())(input)
+    }
+
+    /// Parse to the end of input and return the resulting string.
+    ///
+    /// If there is no input, return [`UNKNOWN_NAME`] instead.
+    fn name(input: &str) -> ParseResult<&str> {
+        rest.map(|name: &str| if name.is_empty() { UNKNOWN_NAME } else { name })
+            .parse(input)
+    }
+
+    /// Parse the flags preceding the address of a `FUNC` or `PUBLIC` record.
+    ///
+    /// Flags are single alphabetic characters followed by one or more spaces. Hex digits are never
+    /// flags, since they cannot be told apart from an address. Returns whether the `m` flag is set
+    /// and all other flags in order.
+    fn flags(input: &str) -> ParseResult<(bool, Vec<char>)> {
+        let flag =
+            satisfy(|c| c.is_ascii_alphabetic() && !c.is_ascii_hexdigit()).terminated(multispace1);
+
+        fold_many0(
+            flag,
+            || (false, Vec::new()),
+            |(multiple, mut unknown_flags), flag| {
+                if flag == 'm' {
+                    (true, unknown_flags)
+                } else {
+                    unknown_flags.push(flag);
+                    (multiple, unknown_flags)
+                }
+            },
+        )(input)
+    }
+
+    /// Parse a line number as a signed decimal number and return `max(0, n)`.
+    fn line_num(input: &str) -> ParseResult<u64> {
+        pair(char('-').opt(), num_dec!(u64))
+            .map(|(sign, num)| if sign.is_some() { 0 } else { num })
+            .parse(input)
+    }
+
+    /// Parse a [`BreakpadStackWinRecordType`].
+    fn stack_win_record_type(input: &str) -> ParseResult<BreakpadStackWinRecordType> {
+        alt((
+            char('0').value(BreakpadStackWinRecordType::Fpo),
+            char('1').value(BreakpadStackWinRecordType::Trap),
+            char('2').value(BreakpadStackWinRecordType::Tss),
+            char('3').value(BreakpadStackWinRecordType::Standard),
+            char('4').value(BreakpadStackWinRecordType::FrameData),
+            non_whitespace.value(BreakpadStackWinRecordType::Unknown),
+        ))(input)
+    }
+
+    /// Parse a [`BreakpadModuleRecord`].
+    ///
+    /// A module record has the form `MODULE <os> <arch> <id>( <name>)?`. The id may be hyphenated.
+    fn module_record(input: &str) -> ParseResult<BreakpadModuleRecord> {
+        let (input, _) = tag("MODULE")
+            .terminated(multispace1)
+            .context("module record prefix")
+            .parse(input)?;
+        let (input, (os, arch, id, name)) = tuple((
+            non_whitespace.terminated(multispace1).context("os"),
+            non_whitespace.terminated(multispace1).context("arch"),
+            hex_id.terminated(multispace1.or(eof)).context("module id"),
+            name.context("module name"),
+        ))
+        .cut()
+        .context("module record body")
+        .parse(input)?;
+
+        Ok((input, BreakpadModuleRecord { os, arch, id, name }))
+    }
+
+    /// Parse a sequence of hex digits, optionally separated by dashes.
+    ///
+    /// This is used for identifiers, which some dumpers emit in a hyphenated UUID-style format.
+    fn hex_id(input: &str) -> ParseResult<'_, &str> {
+        separated_list1(char('-'), hex_digit1)
+            .recognize()
+            .parse(input)
+    }
+
+    /// Parse a code identifier.
+    ///
+    /// Code identifiers consist of hex digits only. The exception are PE identifiers, which some
+    /// dumpers emit as `<timestamp>-<size>` with a timestamp of exactly eight hex digits.
+    fn code_id(input: &str) -> ParseResult<'_, &str> {
+        let timestamp = take_while_m_n(8, 8, |c: char| c.is_ascii_hexdigit());
+        pair(timestamp.terminated(char('-')), hex_digit1)
+            .recognize()
+            .or(hex_digit1)
+            .parse(input)
+    }
+
+    /// Parse a [`BreakpadModuleRecord`].
+    ///
+    /// A module record has the form `MODULE <os> <arch> <id>( <name>)?`.
+    /// This will fail if there is any input left over after the record.
+    pub fn module_record_final(input: &str) -> Result<BreakpadModuleRecord, ErrorTree<ErrorLine>> {
+        nom_supreme::final_parser::final_parser(module_record)(input)
+    }
+
+    /// Parse the `CodeId` variant of a [`BreakpadInfoRecord`].
+    ///
+    /// A `CodeId` record has the form `CODE_ID <code_id>( <code_file>)?`. The code identifier
+    /// consists of hex digits, except for PE identifiers in the form `<timestamp>-<size>`.
+    ///
+    /// Some dumpers omit the code identifier if it is unknown and emit `CODE_ID <code_file>`
+    /// instead. If the first token is not hexadecimal, the entire remainder is therefore parsed as
+    /// the code file with an empty code identifier.
+    fn info_code_id_record(input: &str) -> ParseResult<BreakpadInfoRecord> {
+        let (input, _) = tag("CODE_ID")
+            .terminated(multispace1)
+            .context("info code_id record prefix")
+            .parse(input)?;
+
+        let (input, (code_id, code_file)) = pair(
+            code_id.terminated(multispace1.or(eof)).context("code id"),
+            name.context("file name"),
+        )
+        .or(rest.map(|code_file| ("", code_file)))
+        .context("info code_id record body")
+        .parse(input)?;
+
+        Ok((input, BreakpadInfoRecord::CodeId { code_id, code_file }))
+    }
+
+    /// Parse the `Other` variant of a [`BreakpadInfoRecord`].
+    ///
+    /// An `Other` record has the form `<scope>( <info>)?`.
+    fn info_other_record(input: &str) -> ParseResult<BreakpadInfoRecord> {
+        let (input, (scope, info)) = pair(
+            non_whitespace
+                .terminated(multispace1.or(eof))
+                .context("info scope"),
+            rest,
+        )
+        .cut()
+        .context("info other record body")
+        .parse(input)?;
+
+        Ok((input, BreakpadInfoRecord::Other { scope, info }))
+    }
+
+    /// Parse a [`BreakpadInfoRecord`].
+    ///
+    /// An INFO record has the form `INFO (<code_id_record> | <other_record>)`.
+    fn info_record(input: &str) -> ParseResult<BreakpadInfoRecord> {
+        let (input, _) = tag("INFO")
+            .terminated(multispace1)
+            .context("info record prefix")
+            .parse(input)?;
+
+        info_code_id_record
+            .or(info_other_record)
+            .cut()
+            .context("info record body")
+            .parse(input)
+    }
+
+    /// Parse a [`BreakpadInfoRecord`].
+    ///
+    /// An INFO record has the form `INFO (<code_id_record> | <other_record>)`.
+    /// This will fail if there is any input left over after the record.
+    pub fn info_record_final(input: &str) -> Result<BreakpadInfoRecord, ErrorTree<ErrorLine>> {
+        nom_supreme::final_parser::final_parser(info_record)(input)
+    }
+
+    /// Parse a [`BreakpadFileRecord`].
+    ///
+    /// A FILE record has the form `FILE <id>( <name>)?`.
+    fn file_record(input: &str) -> ParseResult<BreakpadFileRecord> {
+        let (input, _) = tag("FILE")
+            .terminated(multispace1)
+            .context("file record prefix")
+            .parse(input)?;
+
+        let (input, (id, name)) = pair(
+            num_dec!(u64)
+                .terminated(multispace1.or(eof))
+                .context("file id"),
+            rest.context("file name"),
+        )
+        .cut()
+        .context("file record body")
+        .parse(input)?;
+
+        let (name, checksum) = split_file_checksum(name);
+        Ok((input, BreakpadFileRecord { id, name, checksum }))
+    }
+
+    /// Splits a trailing checksum off a file name.
+    ///
+    /// See [`BreakpadFileRecord`] for the heuristic.
+    fn split_file_checksum(name: &str) -> (&str, Option<&str>) {
+        // Fields are separated by ASCII whitespace. Other whitespace is part of the path.
+        let is_separator = |c: char| c.is_ascii_whitespace();
+        let (path, token) = match name.rsplit_once(is_separator) {
+            Some((path, token)) => (path.trim_end_matches(is_separator), token),
+            None => return (name, None),
+        };
+
+        let hex = match token.split_once(':') {
+            Some((algorithm, hex)) if !algorithm.is_empty() => {
+                if !algorithm.bytes().all(|b| b.is_ascii_alphanumeric()) {
+                    return (name, None);
+                }
+                hex
+            }
+            Some(_) => return (name, None),
+            None => token,
+        };
+
+        let is_hash = (32..=64).contains(&hex.len())
+            && hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+        if is_hash && !path.is_empty() {
+            (path, Some(token))
+        } else {
+            (name, None)
+        }
+    }
+
+    /// Parse a [`BreakpadFileRecord`].
+    ///
+    /// A FILE record has the form `FILE <id>( <name>)?`.
+    /// This will fail if there is any input left over after the record.
+    pub fn file_record_final(input: &str) -> Result<BreakpadFileRecord, ErrorTree<ErrorLine>> {
+        nom_supreme::final_parser::final_parser(file_record)(input)
+    }
+
+    /// Parse a [`BreakpadPublicRecord`].
+    ///
+    /// A PUBLIC record has the form `PUBLIC (<flag> )* <address> <parameter_size> ( <name>)?`.
+    fn public_record(input: &str) -> ParseResult<BreakpadPublicRecord> {
+        let (input, _) = tag("PUBLIC")
+            .terminated(multispace1)
+            .context("public record prefix")
+            .parse(input)?;
+
+        let (input, ((multiple, unknown_flags), address, parameter_size, name)) = tuple((
+            flags.context("flags"),
+            num_hex!(u64).terminated(multispace1).context("address"),
+            num_hex!(u64)
+                .terminated(multispace1.or(eof))
+                .context("param size"),
+            name.context("symbol name"),
+        ))
+        .cut()
+        .context("public record body")
+        .parse(input)?;
+
+        Ok((
+            input,
+            BreakpadPublicRecord {
+                multiple,
+                unknown_flags,
+                address,
+                parameter_size,
+                name,
+            },
+        ))
+    }
+
+    /// Parse a [`BreakpadPublicRecord`].
+    ///
+    /// A PUBLIC record has the form `PUBLIC (<flag> )* <address> <parameter_size> ( <name>)?`.
+    /// This will fail if there is any input left over after the record.
+    pub fn public_record_final(input: &str) -> Result<BreakpadPublicRecord, ErrorTree<ErrorLine>> {
+        nom_supreme::final_parser::final_parser(public_record)(input)
+    }
+
+    /// Parse a [`BreakpadFuncRecord`].
+    ///
+    /// A FUNC record has the form `FUNC (<flag> )* <address> <size> <parameter_size> ( <name>)?`.
+    fn func_record(input: &str) -> ParseResult<BreakpadFuncRecord> {
+        let (input, _) = tag("FUNC")
+            .terminated(multispace1)
+            .context("func record prefix")
+            .parse(input)?;
+
+        let (input, ((multiple, unknown_flags), address, size, parameter_size, name)) = tuple((
+            flags.context("flags"),
+            num_hex!(u64).terminated(multispace1).context("address"),
+            num_hex!(u64).terminated(multispace1).context("size"),
+            num_hex!(u64)
+                .terminated(multispace1.or(eof))
+                .context("param size"),
+            name.context("symbol name"),
+        ))
+        .cut()
+        .context("func record body")
+        .parse(input)?;
+
+        Ok((
+            input,
+            BreakpadFuncRecord {
+                multiple,
+                unknown_flags,
+                address,
+                size,
+                parameter_size,
+                name,
+                lines: Lines::default(),
+            },
+        ))
+    }
+
+    /// Parse a [`BreakpadFuncRecord`].
+    ///
+    /// A FUNC record has the form `FUNC (<flag> )* <address> <size> <parameter_size> ( <name>)?`.
+    /// This will fail if there is any input left over after the record.
+    pub fn func_record_final(input: &str) -> Result<BreakpadFuncRecord, ErrorTree<ErrorLine>> {
+        nom_supreme::final_parser::final_parser(func_record)(input)
+    }
+
+    /// Parse a [`BreakpadLineRecord`].
+    ///
+    /// A LINE record has the form `<address> <size> <line> <file_id>`.
+    fn line_record(input: &str) -> ParseResult<BreakpadLineRecord> {
+        let (input, (address, size, line, file_id, column)) = tuple((
+            num_hex!(u64).terminated(multispace1).context("address"),
+            num_hex!(u64).terminated(multispace1).context("size"),
+            line_num.terminated(multispace1).context("line number"),
+            num_dec!(u64).context("file id"),
+            multispace1.precedes(num_dec!(u64)).opt().context("column"),
+        ))
+        .context("line record")
+        .parse(input)?;
+
+        Ok((
+            input,
+            BreakpadLineRecord {
+                address,
+                size,
+                line,
+                file_id,
+                column,
+            },
+        ))
+    }
+
+    /// Parse a [`BreakpadLineRecord`].
+    ///
+    /// A LINE record has the form `<address> <size> <line> <file_id> [<column>]`.
+    /// This will fail if there is any input left over after the record.
+    pub fn line_record_final(input: &str) -> Result<BreakpadLineRecord, ErrorTree<ErrorLine>> {
+        nom_supreme::final_parser::final_parser(line_record)(input)
+    }
+
+    /// Parse a [`BreakpadStackCfiDeltaRecord`].
+    ///
+    /// A STACK CFI Delta record has the form `STACK CFI <address> <rules>`.
     fn stack_cfi_delta_record(input: &str) -> ParseResult<BreakpadStackCfiDeltaRecord> {
         let (input, _) = tag("STACK CFI")
             .terminated(multispace1)
             .context("stack cfi prefix")
             .parse(input)?;
 
-        let (input, (address, rules)) = pair(
-            num_hex!(u64).terminated(multispace1).context("address"),
-            rest.context("rules"),
-        )
-        .cut()
-        .context("stack cfi delta record body")
-        .parse(input)?;
+        let (input, (address, rules)) = pair(
+            num_hex!(u64).terminated(multispace1).context("address"),
+            rest.context("rules"),
+        )
+        .cut()
+        .context("stack cfi delta record body")
+        .parse(input)?;
+
+        Ok((input, BreakpadStackCfiDeltaRecord { address, rules }))
+    }
+
+    /// Parse a [`BreakpadStackCfiDeltaRecord`].
+    ///
+    /// A STACK CFI Delta record has the form `STACK CFI <address> <rules>`.
+    /// This will fail if there is any input left over after the record.
+    pub fn stack_cfi_delta_record_final(
+        input: &str,
+    ) -> Result<BreakpadStackCfiDeltaRecord, ErrorTree<ErrorLine>> {
+        nom_supreme::final_parser::final_parser(stack_cfi_delta_record)(input)
+    }
+
+    /// Parse a [`BreakpadStackCfiRecord`].
+    ///
+    /// A STACK CFI INIT record has the form `STACK CFI INIT <address> <size> <init_rules>`.
+    fn stack_cfi_record(input: &str) -> ParseResult<BreakpadStackCfiRecord> {
+        let (input, _) = tag("STACK CFI INIT")
+            .terminated(multispace1)
+            .context("stack cfi init  prefix")
+            .parse(input)?;
+
+        let (input, (start, size, init_rules)) = tuple((
+            num_hex!(u64).terminated(multispace1).context("start"),
+            num_hex!(u64).terminated(multispace1).context("size"),
+            rest.context("rules"),
+        ))
+        .cut()
+        .context("stack cfi record body")
+        .parse(input)?;
+
+        Ok((
+            input,
+            BreakpadStackCfiRecord {
+                start,
+                size,
+                init_rules,
+                deltas: Lines::default(),
+            },
+        ))
+    }
+
+    /// Parse a [`BreakpadStackCfiRecord`].
+    ///
+    /// A STACK CFI INIT record has the form `STACK CFI INIT <address> <size> <init_rules>`.
+    /// This will fail if there is any input left over after the record.
+    pub fn stack_cfi_record_final(
+        input: &str,
+    ) -> Result<BreakpadStackCfiRecord, ErrorTree<ErrorLine>> {
+        nom_supreme::final_parser::final_parser(stack_cfi_record)(input)
+    }
+
+    /// Parse a [`BreakpadStackWinRecord`].
+    ///
+    /// A STACK WIN record has the form
+    /// `STACK WIN <ty> <code_start> <code_size> <prolog_size> <epilog_size> <params_size> <saved_regs_size> <locals_size> <max_stack_size> <has_program_string> (<program_string> | <uses_base_pointer>)`.
+    fn stack_win_record(input: &str) -> ParseResult<BreakpadStackWinRecord> {
+        let (input, _) = tag("STACK WIN")
+            .terminated(multispace1)
+            .context("stack win prefix")
+            .parse(input)?;
+
+        let (
+            input,
+            (
+                ty,
+                code_start,
+                code_size,
+                prolog_size,
+                epilog_size,
+                params_size,
+                saved_regs_size,
+                locals_size,
+                max_stack_size,
+                has_program_string,
+            ),
+        ) = tuple((
+            stack_win_record_type
+                .terminated(multispace1)
+                .context("record type"),
+            num_hex!(u32).terminated(multispace1).context("code start"),
+            num_hex!(u32).terminated(multispace1).context("code size"),
+            num_hex!(u16).terminated(multispace1).context("prolog size"),
+            num_hex!(u16).terminated(multispace1).context("epilog size"),
+            num_hex!(u32).terminated(multispace1).context("params size"),
+            num_hex!(u16)
+                .terminated(multispace1)
+                .context("saved regs size"),
+            num_hex!(u32).terminated(multispace1).context("locals size"),
+            num_hex!(u32)
+                .terminated(multispace1)
+                .context("max stack size"),
+            non_whitespace
+                .map(|s| s != "0")
+                .terminated(multispace1)
+                .context("has_program_string"),
+        ))
+        .cut()
+        .context("stack win record body")
+        .parse(input)?;
+
+        let (input, program_string) =
+            cond(has_program_string, rest.context("program string"))(input)?;
+        let (input, uses_base_pointer) =
+            cond(!has_program_string, non_whitespace.map(|s| s != "0"))
+                .map(|o| o.unwrap_or(false))
+                .parse(input)?;
+
+        Ok((
+            input,
+            BreakpadStackWinRecord {
+                ty,
+                code_start,
+                code_size,
+                prolog_size,
+                epilog_size,
+                params_size,
+                saved_regs_size,
+                locals_size,
+                max_stack_size,
+                uses_base_pointer,
+                program_string,
+            },
+        ))
+    }
+
+    /// Parse a [`BreakpadStackWinRecord`].
+    ///
+    /// A STACK WIN record has the form
+    /// `STACK WIN <ty> <code_start> <code_size> <prolog_size> <epilog_size> <params_size> <saved_regs_size> <locals_size> <max_stack_size> <has_program_string> (<program_string> | <uses_base_pointer>)`.
+    /// This will fail if there is any input left over after the record.
+    pub fn stack_win_record_final(
+        input: &str,
+    ) -> Result<BreakpadStackWinRecord, ErrorTree<ErrorLine>> {
+        nom_supreme::final_parser::final_parser(stack_win_record)(input)
+    }
+
+    /// Parse a [`BreakpadStackRecord`], containing either a [`BreakpadStackCfiRecord`] or a
+    /// [`BreakpadStackWinRecord`].
+    ///
+    /// This will fail if there is any input left over after the record.
+    pub fn stack_record_final(input: &str) -> Result<BreakpadStackRecord, ParseBreakpadError> {
+        nom_supreme::final_parser::final_parser(alt((
+            stack_cfi_record.map(BreakpadStackRecord::Cfi),
+            stack_win_record.map(BreakpadStackRecord::Win),
+        )))(input)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_position() {
+        let data = b"\xef\xbb\xbfMODULE\r\nFILE\n\nFUNC";
+        let mut lines = Lines::new(data);
+
+        assert_eq!(lines.position(), 3);
+        assert_eq!(lines.next(), Some(&b"MODULE"[..]));
+        assert_eq!(lines.position(), 11);
+        assert_eq!(lines.next(), Some(&b"FILE"[..]));
+        assert_eq!(lines.position(), 16);
+        assert_eq!(lines.next(), Some(&b""[..]));
+        assert_eq!(lines.position(), 17);
+        assert_eq!(lines.next(), Some(&b"FUNC"[..]));
+        assert_eq!(lines.position(), data.len());
+        assert_eq!(lines.next(), None);
+        assert_eq!(lines.position(), data.len());
+    }
+
+    #[test]
+    fn test_parse_module_record() -> Result<(), BreakpadError> {
+        let string = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash";
+        let record = BreakpadModuleRecord::parse(&*string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮BreakpadModuleRecord {
+       ⋮    os: "Linux",
+       ⋮    arch: "x86_64",
+       ⋮    id: "492E2DD23CC306CA9C494EEF1533A3810",
+       ⋮    name: "crash",
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_module_record_short_id() -> Result<(), BreakpadError> {
+        // NB: This id is one character short, missing the age. DebugId can handle this, however.
+        let string = b"MODULE Linux x86_64 6216C672A8D33EC9CF4A1BAB8B29D00E libdispatch.so";
+        let record = BreakpadModuleRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮BreakpadModuleRecord {
+       ⋮    os: "Linux",
+       ⋮    arch: "x86_64",
+       ⋮    id: "6216C672A8D33EC9CF4A1BAB8B29D00E",
+       ⋮    name: "libdispatch.so",
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_info_record_other() -> Result<(), BreakpadError> {
+        let string = b"INFO VENDOR-annotation some value";
+        let record = BreakpadInfoRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮Other {
+       ⋮    scope: "VENDOR-annotation",
+       ⋮    info: "some value",
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_info_record_missing_code_id() -> Result<(), BreakpadError> {
+        let string = b"INFO CODE_ID libfoo.so";
+        let record = BreakpadInfoRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮CodeId {
+       ⋮    code_id: "",
+       ⋮    code_file: "libfoo.so",
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_code_id() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID lib foo.so
+FILE 0 main.c
+";
+        let object = BreakpadObject::parse(data)?;
+        assert!(object.info_records().all(|record| record.is_ok()));
+        assert_eq!(object.code_id(), None);
+        assert_eq!(object.code_file(), Some("lib foo.so"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_prefixed_hex() -> Result<(), BreakpadError> {
+        let func = BreakpadFuncRecord::parse(b"FUNC m 0x1730 0X1a 0 main", Lines::default())?;
+        assert_eq!((func.address, func.size), (0x1730, 0x1a));
+
+        let public = BreakpadPublicRecord::parse(b"PUBLIC 0x5180 0 main")?;
+        assert_eq!(public.address, 0x5180);
+
+        let line = BreakpadLineRecord::parse(b"0x1730 0x6 93 20")?;
+        assert_eq!((line.address, line.size, line.line), (0x1730, 6, 93));
+
+        let cfi = BreakpadStackCfiRecord::parse(b"STACK CFI INIT 0x1880 0x2d .cfa: $rsp 8 +")?;
+        assert_eq!((cfi.start, cfi.size), (0x1880, 0x2d));
+
+        assert!(BreakpadPublicRecord::parse(b"PUBLIC 0x 0 main").is_err());
+        assert!(BreakpadPublicRecord::parse(b"PUBLIC 0xg1 0 main").is_err());
+        assert!(BreakpadPublicRecord::parse(b"PUBLIC x51 0 main").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_file_record() -> Result<(), BreakpadError> {
+        let string = b"FILE 37 /usr/include/libkern/i386/_OSByteOrder.h";
+        let record = BreakpadFileRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮BreakpadFileRecord {
+       ⋮    id: 37,
+       ⋮    name: "/usr/include/libkern/i386/_OSByteOrder.h",
+       ⋮    checksum: None,
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_file_record_checksum() -> Result<(), BreakpadError> {
+        let string = b"FILE 39 /usr/local/src/filename with spaces.c sha256:9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        let record = BreakpadFileRecord::parse(string)?;
+        assert_eq!(record.name, "/usr/local/src/filename with spaces.c");
+        assert_eq!(
+            record.checksum,
+            Some("sha256:9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08")
+        );
+        assert_eq!(record.to_string(), str::from_utf8(string).unwrap());
+
+        let record = BreakpadFileRecord::parse(b"FILE 40 main.c d41d8cd98f00b204e9800998ecf8427e")?;
+        assert_eq!(record.name, "main.c");
+        assert_eq!(record.checksum, Some("d41d8cd98f00b204e9800998ecf8427e"));
+
+        // Without whitespace, a hash-like path is not split.
+        let record =
+            BreakpadFileRecord::parse(b"FILE 41 objects/d41d8cd98f00b204e9800998ecf8427e")?;
+        assert_eq!(record.name, "objects/d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(record.checksum, None);
+
+        // Tokens that are too short or not lowercase hex remain part of the path.
+        let record = BreakpadFileRecord::parse(b"FILE 42 src/my file deadbeef")?;
+        assert_eq!(record.name, "src/my file deadbeef");
+        assert_eq!(record.checksum, None);
+
+        // Non-ASCII whitespace does not separate a checksum.
+        let record = BreakpadFileRecord::parse(
+            "FILE 43 src/my\u{a0}file.c\u{3000}d41d8cd98f00b204e9800998ecf8427e".as_bytes(),
+        )?;
+        assert_eq!(
+            record.name,
+            "src/my\u{a0}file.c\u{3000}d41d8cd98f00b204e9800998ecf8427e"
+        );
+        assert_eq!(record.checksum, None);
+
+        let record = BreakpadFileRecord::parse(
+            "FILE 44 src/my\u{3000}file.c d41d8cd98f00b204e9800998ecf8427e".as_bytes(),
+        )?;
+        assert_eq!(record.name, "src/my\u{3000}file.c");
+        assert_eq!(record.checksum, Some("d41d8cd98f00b204e9800998ecf8427e"));
+
+        // Without a name, the checksum would be read back as the name.
+        let record = BreakpadFileRecord {
+            id: 45,
+            name: "",
+            checksum: Some("d41d8cd98f00b204e9800998ecf8427e"),
+        };
+        assert_eq!(record.to_string(), "FILE 45");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_checksums() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 src/main.c d41d8cd98f00b204e9800998ecf8427e
+FILE 1 src/util.c
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.debug_session()?;
+
+        assert_eq!(
+            session.file_checksum(0),
+            Some("d41d8cd98f00b204e9800998ecf8427e")
+        );
+        assert_eq!(session.file_checksum(1), None);
+        assert_eq!(session.file_checksum(2), None);
+
+        let file = session.file_by_id(0).unwrap();
+        assert_eq!(file.path_str(), "src/main.c");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_file_record_space() -> Result<(), BreakpadError> {
+        let string = b"FILE 38 /usr/local/src/filename with spaces.c";
+        let record = BreakpadFileRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮BreakpadFileRecord {
+       ⋮    id: 38,
+       ⋮    name: "/usr/local/src/filename with spaces.c",
+       ⋮    checksum: None,
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_func_record() -> Result<(), BreakpadError> {
+        // Lines will be tested separately
+        let string = b"FUNC 1730 1a 0 <name omitted>";
+        let record = BreakpadFuncRecord::parse(string, Lines::default())?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮BreakpadFuncRecord {
+       ⋮    multiple: false,
+       ⋮    unknown_flags: [],
+       ⋮    address: 5936,
+       ⋮    size: 26,
+       ⋮    parameter_size: 0,
+       ⋮    name: "<name omitted>",
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_func_record_multiple() -> Result<(), BreakpadError> {
+        let string = b"FUNC m 1730 1a 0 <name omitted>";
+        let record = BreakpadFuncRecord::parse(string, Lines::default())?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮BreakpadFuncRecord {
+       ⋮    multiple: true,
+       ⋮    unknown_flags: [],
+       ⋮    address: 5936,
+       ⋮    size: 26,
+       ⋮    parameter_size: 0,
+       ⋮    name: "<name omitted>",
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_func_record_no_name() -> Result<(), BreakpadError> {
+        let string = b"FUNC 0 f 0";
+        let record = BreakpadFuncRecord::parse(string, Lines::default())?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮BreakpadFuncRecord {
+       ⋮    multiple: false,
+       ⋮    unknown_flags: [],
+       ⋮    address: 0,
+       ⋮    size: 15,
+       ⋮    parameter_size: 0,
+       ⋮    name: "<unknown>",
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_line_record() -> Result<(), BreakpadError> {
+        let string = b"1730 6 93 20";
+        let record = BreakpadLineRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮BreakpadLineRecord {
+       ⋮    address: 5936,
+       ⋮    size: 6,
+       ⋮    line: 93,
+       ⋮    file_id: 20,
+       ⋮    column: None,
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_line_record_negative_line() -> Result<(), BreakpadError> {
+        let string = b"e0fd10 5 -376 2225";
+        let record = BreakpadLineRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+        BreakpadLineRecord {
+            address: 14744848,
+            size: 5,
+            line: 0,
+            file_id: 2225,
+            column: None,
+        }
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_line_record_whitespace() -> Result<(), BreakpadError> {
+        let string = b"    1000 1c 2972 2
+";
+        let record = BreakpadLineRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(
+            record, @r###"
+        BreakpadLineRecord {
+            address: 4096,
+            size: 28,
+            line: 2972,
+            file_id: 2,
+            column: None,
+        }
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_record_display() -> Result<(), BreakpadError> {
+        let record = BreakpadLineRecord::parse(b"1730 6 93 20")?;
+        assert_eq!(record.to_string(), "1730 6 93 20");
+        assert_eq!(
+            BreakpadLineRecord::parse(record.to_string().as_bytes())?,
+            record
+        );
+
+        let record = BreakpadLineRecord::parse(b"e0fd10 5 -376 2225")?;
+        assert_eq!(record.to_string(), "e0fd10 5 0 2225");
+
+        let record = BreakpadLineRecord::parse(b"    1000 1c 2972 2\n")?;
+        assert_eq!(record.to_string(), "1000 1c 2972 2");
+
+        let record = BreakpadLineRecord::parse(b"1730 6 93 20 17")?;
+        assert_eq!(record.column, Some(17));
+        assert_eq!(record.to_string(), "1730 6 93 20 17");
+        assert_eq!(
+            BreakpadLineRecord::parse(record.to_string().as_bytes())?,
+            record
+        );
+
+        assert!(BreakpadLineRecord::parse(b"1730 6 93 20 17 1").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_record_new() -> Result<(), BreakpadError> {
+        let record = BreakpadLineRecord::new(0x1730, 6, 93, 20, None)?;
+        assert_eq!(record, BreakpadLineRecord::parse(b"1730 6 93 20")?);
+
+        let mut file_map = BreakpadFileMap::new();
+        file_map.insert(20, "main.c");
+        assert!(BreakpadLineRecord::new(0x1730, 6, 93, 20, Some(&file_map)).is_ok());
+
+        let error = BreakpadLineRecord::new(0x1730, 6, 93, 21, Some(&file_map)).unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::InvalidLineRecord);
+
+        let error = BreakpadLineRecord::new(u64::MAX, 6, 93, 20, None).unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::InvalidLineRecord);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_public_record() -> Result<(), BreakpadError> {
+        let string = b"PUBLIC 5180 0 __clang_call_terminate";
+        let record = BreakpadPublicRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮BreakpadPublicRecord {
+       ⋮    multiple: false,
+       ⋮    unknown_flags: [],
+       ⋮    address: 20864,
+       ⋮    parameter_size: 0,
+       ⋮    name: "__clang_call_terminate",
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_public_record_multiple() -> Result<(), BreakpadError> {
+        let string = b"PUBLIC m 5180 0 __clang_call_terminate";
+        let record = BreakpadPublicRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮BreakpadPublicRecord {
+       ⋮    multiple: true,
+       ⋮    unknown_flags: [],
+       ⋮    address: 20864,
+       ⋮    parameter_size: 0,
+       ⋮    name: "__clang_call_terminate",
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_public_record_no_name() -> Result<(), BreakpadError> {
+        let string = b"PUBLIC 5180 0";
+        let record = BreakpadPublicRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+       ⋮BreakpadPublicRecord {
+       ⋮    multiple: false,
+       ⋮    unknown_flags: [],
+       ⋮    address: 20864,
+       ⋮    parameter_size: 0,
+       ⋮    name: "<unknown>",
+       ⋮}
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_stack_cfi_init_record() -> Result<(), BreakpadError> {
+        let string = b"STACK CFI INIT 1880 2d .cfa: $rsp 8 + .ra: .cfa -8 + ^";
+        let record = BreakpadStackRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+        Cfi(
+            BreakpadStackCfiRecord {
+                start: 6272,
+                size: 45,
+                init_rules: ".cfa: $rsp 8 + .ra: .cfa -8 + ^",
+                deltas: Lines(
+                    LineOffsets {
+                        data: [],
+                        finished: true,
+                        index: 0,
+                    },
+                ),
+            },
+        )
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_stack_win_record() -> Result<(), BreakpadError> {
+        let string =
+            b"STACK WIN 4 371a c 0 0 0 0 0 0 1 $T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + =";
+        let record = BreakpadStackRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+        Win(
+            BreakpadStackWinRecord {
+                ty: FrameData,
+                code_start: 14106,
+                code_size: 12,
+                prolog_size: 0,
+                epilog_size: 0,
+                params_size: 0,
+                saved_regs_size: 0,
+                locals_size: 0,
+                max_stack_size: 0,
+                uses_base_pointer: false,
+                program_string: Some(
+                    "$T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + =",
+                ),
+            },
+        )
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_stack_win_record_type_3() -> Result<(), BreakpadError> {
+        let string = b"STACK WIN 3 8a10b ec b 0 c c 4 0 0 1";
+        let record = BreakpadStackWinRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+        BreakpadStackWinRecord {
+            ty: Standard,
+            code_start: 565515,
+            code_size: 236,
+            prolog_size: 11,
+            epilog_size: 0,
+            params_size: 12,
+            saved_regs_size: 12,
+            locals_size: 4,
+            max_stack_size: 0,
+            uses_base_pointer: true,
+            program_string: None,
+        }
+        "###);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_stack_win_whitespace() -> Result<(), BreakpadError> {
+        let string =
+            b"     STACK WIN 4 371a c 0 0 0 0 0 0 1 $T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + =
+                ";
+        let record = BreakpadStackRecord::parse(string)?;
+
+        insta::assert_debug_snapshot!(record, @r###"
+        Win(
+            BreakpadStackWinRecord {
+                ty: FrameData,
+                code_start: 14106,
+                code_size: 12,
+                prolog_size: 0,
+                epilog_size: 0,
+                params_size: 0,
+                saved_regs_size: 0,
+                locals_size: 0,
+                max_stack_size: 0,
+                uses_base_pointer: false,
+                program_string: Some(
+                    "$T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + =",
+                ),
+            },
+        )
+        "###);
+        Ok(())
+    }
+
+    use similar_asserts::assert_eq;
+
+    #[test]
+    fn test_lineoffsets_fused() {
+        let data = b"";
+        let mut offsets = LineOffsets::new(data);
+
+        offsets.next();
+        assert_eq!(None, offsets.next());
+        assert_eq!(None, offsets.next());
+        assert_eq!(None, offsets.next());
+    }
+
+    macro_rules! test_lineoffsets {
+        ($name:ident, $data:literal, $( ($index:literal, $line:literal) ),*) => {
+            #[test]
+            fn $name() {
+                let mut offsets = LineOffsets::new($data);
+
+                $(
+                    assert_eq!(Some(($index, &$line[..])), offsets.next());
+                )*
+                assert_eq!(None, offsets.next());
+            }
+        };
+    }
+
+    test_lineoffsets!(test_lineoffsets_empty, b"", (0, b""));
+    test_lineoffsets!(test_lineoffsets_oneline, b"hello", (0, b"hello"));
+    test_lineoffsets!(
+        test_lineoffsets_trailing_n,
+        b"hello\n",
+        (0, b"hello"),
+        (6, b"")
+    );
+    test_lineoffsets!(
+        test_lineoffsets_trailing_rn,
+        b"hello\r\n",
+        (0, b"hello"),
+        (7, b"")
+    );
+    test_lineoffsets!(
+        test_lineoffsets_n,
+        b"hello\nworld\nyo",
+        (0, b"hello"),
+        (6, b"world"),
+        (12, b"yo")
+    );
+    test_lineoffsets!(
+        test_lineoffsets_rn,
+        b"hello\r\nworld\r\nyo",
+        (0, b"hello"),
+        (7, b"world"),
+        (14, b"yo")
+    );
+    test_lineoffsets!(
+        test_lineoffsets_mixed,
+        b"hello\r\nworld\nyo",
+        (0, b"hello"),
+        (7, b"world"),
+        (13, b"yo")
+    );
+
+    #[test]
+    fn test_parse_bom() -> Result<(), BreakpadError> {
+        let data = b"\xef\xbb\xbfMODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 5F2D4E3A crash.so
+FILE 0 main.c
+";
+        assert!(BreakpadObject::test(data));
+
+        let object = BreakpadObject::parse(data)?;
+        assert_eq!(object.name(), "crash");
+        assert_eq!(object.code_id(), Some(CodeId::new("5F2D4E3A".into())));
+        assert_eq!(object.file_records().count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scoped_infos() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 5F2D4E3A crash.so
+INFO RELEASE 1.2.3
+INFO VENDOR_BUILD  nightly build 42
+FILE 0 main.c
+INFO LATE ignored
+";
+        let object = BreakpadObject::parse(data)?;
+
+        assert_eq!(
+            object.infos().collect::<Vec<_>>(),
+            [
+                ("CODE_ID", "5F2D4E3A crash.so"),
+                ("RELEASE", "1.2.3"),
+                ("VENDOR_BUILD", "nightly build 42"),
+            ]
+        );
+
+        assert_eq!(object.info("RELEASE"), Some("1.2.3"));
+        assert_eq!(object.info("VENDOR_BUILD"), Some("nightly build 42"));
+        assert_eq!(object.info("CODE_ID"), Some("5F2D4E3A crash.so"));
+        assert_eq!(object.info("LATE"), None);
+        assert_eq!(object.code_id(), Some(CodeId::new("5F2D4E3A".into())));
+
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO TAG first
+INFO RELEASE 1.2.3
+INFO TAG second
+";
+        let object = BreakpadObject::parse(data)?;
+        let tags: Vec<_> = object.info_records_for_scope("TAG").collect();
+        assert_eq!(tags, ["first", "second"]);
+        assert_eq!(object.info_records_for_scope("LATE").count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_cfi_coverage() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 40 0 partial
+FUNC 1100 20 0 covered
+FUNC 1200 10 0 uncovered
+FUNC 1300 0 0 unsized
+STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+STACK CFI INIT 1018 8 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+STACK CFI INIT 1100 18 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+STACK WIN 4 1110 10 0 0 0 0 0 0 1 $eip 4 + ^ = $esp $esp 8 + =
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let gap = |func_addr, func_size, gap_start, gap_size| BreakpadCfiGap {
+            func_addr,
+            func_size,
+            gap_start,
+            gap_size,
+        };
+
+        assert_eq!(
+            object.verify_cfi_coverage(),
+            [
+                gap(0x1000, 0x40, 0x1010, 0x8),
+                gap(0x1000, 0x40, 0x1020, 0x20),
+                gap(0x1200, 0x10, 0x1200, 0x10),
+            ]
+        );
+
+        Ok(())
+    }
+
+    const DANGLING_FILE_ID: &[u8] = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1000 20 0 main
+1000 10 1 0
+1010 10 2 7
+FUNC 1020 10 0 other
+1020 10 5 0
+";
+
+    #[test]
+    fn test_functions_unknown_file_id() -> Result<(), BreakpadError> {
+        let object = BreakpadObject::parse(DANGLING_FILE_ID)?;
+        let session = object.debug_session()?;
+
+        let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(functions.len(), 2);
+        assert_eq!(functions[0].lines[1].file.path_str(), "");
+
+        let results: Vec<_> = session
+            .functions_strict()
+            .map(|result| result.map_err(|e| e.kind()))
+            .collect();
+
+        assert!(matches!(
+            results[0],
+            Err(BreakpadErrorKind::UnknownFileId {
+                function_address: 0x1000,
+                line_address: 0x1010,
+                file_id: 7,
+            })
+        ));
+        assert!(results[1].is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_unknown_file_id() -> Result<(), BreakpadError> {
+        let object = BreakpadObject::parse(DANGLING_FILE_ID)?;
+
+        assert_eq!(
+            object.validate(),
+            [BreakpadValidationIssue::UnknownFileId {
+                function_address: 0x1000,
+                line_address: 0x1010,
+                file_id: 7,
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_file_ids() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 4 a.c
+FILE 9 b.c 0123456789abcdef0123456789abcdef
+FILE 4 a.c
+FUNC 1000 20 0 main
+1000 10 1 9
+1010 10 2 4 7
+PUBLIC 2000 0 other
+STACK CFI INIT 1000 20 .cfa: $rsp 8 +
+";
+        let rewritten = rewrite_file_ids(data)?;
+
+        assert_eq!(
+            str::from_utf8(&rewritten)?,
+            "MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 a.c
+FILE 1 b.c 0123456789abcdef0123456789abcdef
+FUNC 1000 20 0 main
+1000 10 1 1
+1010 10 2 0 7
+PUBLIC 2000 0 other
+STACK CFI INIT 1000 20 .cfa: $rsp 8 +
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_file_ids_conflicting() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 a.c
+FUNC 1000 20 0 main
+1000 20 1 0
+FILE 0 b.c
+FUNC 2000 20 0 other
+2000 20 1 0
+";
+        let rewritten = rewrite_file_ids(data)?;
+
+        assert_eq!(
+            str::from_utf8(&rewritten)?,
+            "MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 a.c
+FUNC 1000 20 0 main
+1000 20 1 0
+FILE 1 b.c
+FUNC 2000 20 0 other
+2000 20 1 1
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_file_ids_unknown() {
+        let result = rewrite_file_ids(DANGLING_FILE_ID).map_err(|e| e.kind());
+        assert!(matches!(
+            result,
+            Err(BreakpadErrorKind::UnknownFileId {
+                function_address: 0x1000,
+                line_address: 0x1010,
+                file_id: 7,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_public_symbols_in_range() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+PUBLIC 1000 0 first
+PUBLIC 2000 0 second
+PUBLIC 2800 0 third
+PUBLIC 3000 0 fourth
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let names = |start, end| {
+            object
+                .public_symbols_in_range(start, end)
+                .map(|result| result.map(|record| record.name))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        assert_eq!(names(0x2000, 0x3000)?, ["second", "third"]);
+        assert_eq!(names(0x1001, 0x2001)?, ["second"]);
+        assert_eq!(names(0x0, 0x1000)?, Vec::<&str>::new());
+        assert_eq!(names(0x3000, u64::MAX)?, ["fourth"]);
+
+        // Records out of order are found, and errors do not end the range.
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+PUBLIC 2000 0 second
+PUBLIC 10000000000000000 0 invalid
+PUBLIC 1000 0 first
+PUBLIC 3000 0 third
+";
+        let object = BreakpadObject::parse(data)?;
+        let results = object
+            .public_symbols_in_range(0x1000, 0x2001)
+            .map(|result| result.map(|record| record.name).map_err(|e| e.kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            [Ok("second"), Err(BreakpadErrorKind::Parse("")), Ok("first")]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_public_to_func() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+PUBLIC 1000 0 first
+PUBLIC m 1040 0 second
+PUBLIC 1040 0 second_alias
+PUBLIC 1100 0 last
+STACK CFI INIT 1000 40 .cfa: $rsp 8 +
+";
+        let object = BreakpadObject::parse(data)?;
+        assert!(!object.has_debug_info());
+
+        let converted = object.convert_public_to_func()?;
+        assert_eq!(
+            str::from_utf8(&converted)?,
+            "MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 40 0 first
+FUNC m 1040 c0 0 second
+FUNC 1100 0 0 last
+PUBLIC 1000 0 first
+PUBLIC m 1040 0 second
+PUBLIC 1040 0 second_alias
+PUBLIC 1100 0 last
+STACK CFI INIT 1000 40 .cfa: $rsp 8 +
+"
+        );
+
+        let converted = BreakpadObject::parse(&converted)?;
+        assert!(converted.has_debug_info());
+
+        let session = converted.debug_session()?;
+        let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(functions.len(), 3);
+        assert!(functions.iter().all(|function| function.lines.is_empty()));
+
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1040 20 0 second
+1040 20 2 0
+FILE 1 other.c
+FUNC 1000 10 0 first
+1000 10 1 1
+PUBLIC 1000 0 first
+PUBLIC 1020 0 gap
+PUBLIC 1100 0 last";
+        let object = BreakpadObject::parse(data)?;
+        let converted = object.convert_public_to_func()?;
+        assert_eq!(
+            str::from_utf8(&converted)?,
+            "MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FILE 1 other.c
+FUNC 1000 10 0 first
+1000 10 1 1
+FUNC 1020 20 0 gap
+FUNC 1040 20 0 second
+1040 20 2 0
+FUNC 1100 0 0 last
+PUBLIC 1000 0 first
+PUBLIC 1020 0 gap
+PUBLIC 1100 0 last"
+        );
+
+        Ok(())
+    }
+
+    const REORDERED: &[u8] = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 5F2D4E3A crash.so
+FILE 0 main.c
+FUNC 1000 10 0 main
+1000 10 1 0
+INFO RELEASE 1.2.3
+FILE 1 late.c
+PUBLIC 2000 0 early_public
+STACK CFI INIT 1000 10 .cfa: $rsp 8 +
+FUNC 1100 10 0 late_func
+1100 10 1 1
+PUBLIC 3000 0 late_public
+";
+
+    #[test]
+    fn test_full_scan() -> Result<(), BreakpadError> {
+        let object = BreakpadObject::parse(REORDERED)?;
+
+        let infos = |records: BreakpadInfoRecords| records.count();
+        assert_eq!(infos(object.info_records()), 1);
+        assert_eq!(infos(object.info_records_full()), 2);
+
+        let files = |records: BreakpadFileRecords<'static>| {
+            records
+                .map(|r| r.map(|r| r.name))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        assert_eq!(files(object.file_records())?, ["main.c"]);
+        assert_eq!(files(object.file_records_full())?, ["main.c", "late.c"]);
+
+        let publics = |records: BreakpadPublicRecords<'static>| {
+            records
+                .map(|r| r.map(|r| r.name))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        assert_eq!(publics(object.public_records())?, ["early_public"]);
+        assert_eq!(
+            publics(object.public_records_full())?,
+            ["early_public", "late_public"]
+        );
+
+        let funcs = |records: BreakpadFuncRecords<'static>| {
+            records
+                .map(|r| r.map(|r| r.name))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        assert_eq!(funcs(object.func_records())?, ["main"]);
+        assert_eq!(funcs(object.func_records_full())?, ["main", "late_func"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_out_of_order() -> Result<(), BreakpadError> {
+        let object = BreakpadObject::parse(REORDERED)?;
+
+        let out_of_order = |line_number, record| BreakpadValidationIssue::OutOfOrderRecord {
+            line_number,
+            record,
+        };
+
+        assert_eq!(
+            object.validate(),
+            [
+                out_of_order(6, "INFO"),
+                out_of_order(7, "FILE"),
+                out_of_order(10, "FUNC"),
+                out_of_order(12, "PUBLIC"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_invalid_records() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1000 20 0 main
+1000 10 1 0
+1010 zz 2 0
+FUNC xyz 10 0 broken
+2000 10 3 7
+PUBLIC m
+STACK CFI INIT 1000
+STACK CFI INIT 1000 20 .cfa: $rsp 8 +
+STACK CFI 1004 .cfa $rsp 16 +
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let invalid = |line_number, record| BreakpadValidationIssue::InvalidRecord {
+            line_number,
+            record,
+        };
+
+        assert_eq!(
+            object.validate(),
+            [
+                invalid(5, "line"),
+                invalid(6, "FUNC"),
+                invalid(8, "PUBLIC"),
+                invalid(9, "STACK CFI INIT"),
+                BreakpadValidationIssue::InvalidCfiRules { line_number: 11 },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FILE 1 util.c
+FUNC 1000 100 0 main
+1000 80 10 0
+1080 80 11 1
+FUNC 1100 100 0 helper
+1100 100 20 1
+FUNC 1200 100 0 no_lines
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.debug_session()?;
+
+        for i in 0..1000 {
+            let address = 0x1000 + (i * 7) % 0x300;
+            let location = session.lookup(address).unwrap();
+            match address {
+                0x1000..=0x107f => {
+                    assert_eq!(location.function_name, "main");
+                    assert_eq!(location.file, Some("main.c"));
+                    assert_eq!(location.line, Some(10));
+                }
+                0x1080..=0x10ff => {
+                    assert_eq!(location.function_name, "main");
+                    assert_eq!(location.file, Some("util.c"));
+                    assert_eq!(location.line, Some(11));
+                }
+                0x1100..=0x11ff => {
+                    assert_eq!(location.function_name, "helper");
+                    assert_eq!(location.line, Some(20));
+                }
+                _ => {
+                    assert_eq!(location.function_name, "no_lines");
+                    assert_eq!(location.file, None);
+                    assert_eq!(location.line, None);
+                }
+            }
+        }
+
+        assert_eq!(session.lookup(0xfff), None);
+        assert_eq!(session.lookup(0x1300), None);
+
+        // The line records of each function were only parsed once.
+        assert_eq!(session.lookup_cache.lock().unwrap().misses, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup_overlapping() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1000 400 0 outer
+1000 400 1 0
+FUNC 1100 100 0 nested
+1100 100 2 0
+FUNC 1180 10 0 innermost
+1180 10 3 0
+FUNC 2000 10 0 other
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.debug_session()?;
+        let name = |address| session.lookup(address).map(|l| l.function_name);
+
+        assert_eq!(name(0x1000), Some("outer"));
+        assert_eq!(name(0x1100), Some("nested"));
+        assert_eq!(name(0x1185), Some("innermost"));
+        assert_eq!(name(0x1190), Some("nested"));
+        assert_eq!(name(0x1200), Some("outer"));
+        assert_eq!(name(0x13ff), Some("outer"));
+        assert_eq!(session.lookup(0x1200).unwrap().line, Some(1));
+        assert_eq!(name(0x1400), None);
+        assert_eq!(name(0x2010), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup_cache_eviction() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1000 10 0 a
+1000 10 1 0
+FUNC 1010 10 0 b
+1010 10 2 0
+";
+        let object = BreakpadObject::parse(data)?;
+        let mut session = object.debug_session()?;
+        session.set_lookup_cache_capacity(1);
+
+        for address in [0x1000, 0x1000, 0x1010, 0x1000] {
+            session.lookup(address).unwrap();
+        }
+
+        let cache = session.lookup_cache.lock().unwrap();
+        assert_eq!(cache.misses, 3);
+        assert_eq!(cache.lines.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_by_name() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 20 0 main
+FUNC 1020 10 0 helper(int)
+PUBLIC 2000 0 _start
+PUBLIC m 2010 0 helper(int)
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let func = object.func_by_name("helper(int)")?.unwrap();
+        assert_eq!((func.address, func.size), (0x1020, 0x10));
+        assert!(object.func_by_name("helper")?.is_none());
+
+        let public = object.public_by_name("helper(int)")?.unwrap();
+        assert_eq!(public.address, 0x2010);
+        assert!(public.multiple);
+        assert!(object.public_by_name("main")?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_windows_paths() -> Result<(), BreakpadError> {
+        let data = b"MODULE windows x86 3249D99D0C4049318610F4E4FB0B69361 crash.pdb
+FILE 0 C:\\build\\src\\foo.cpp
+FILE 1 C:\\build/src\\mixed.cpp
+FILE 2 /usr/src/posix.c
+FUNC 1000 30 0 main
+1000 10 1 0
+1010 10 2 1
+1020 10 3 2
+";
+        let object = BreakpadObject::parse(data)?;
+        let mut session = object.debug_session()?;
+
+        let files = |session: &BreakpadDebugSession| {
+            session
+                .files()
+                .map(|file| {
+                    file.map(|file| (file.dir_str().into_owned(), file.name_str().into_owned()))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        assert_eq!(
+            files(&session)?,
+            [
+                ("C:\\build\\src".to_owned(), "foo.cpp".to_owned()),
+                ("C:\\build/src".to_owned(), "mixed.cpp".to_owned()),
+                ("/usr/src".to_owned(), "posix.c".to_owned()),
+            ]
+        );
+
+        session.canonicalize_paths();
+        assert_eq!(
+            files(&session)?,
+            [
+                ("c:/build/src".to_owned(), "foo.cpp".to_owned()),
+                ("c:/build/src".to_owned(), "mixed.cpp".to_owned()),
+                ("/usr/src".to_owned(), "posix.c".to_owned()),
+            ]
+        );
+
+        let function = session.functions().next().unwrap()?;
+        let dirs: Vec<_> = function
+            .lines
+            .iter()
+            .map(|line| line.file.dir_str())
+            .collect();
+        assert_eq!(dirs, ["c:/build/src", "c:/build/src", "/usr/src"]);
+
+        // The raw records remain untouched.
+        let record = object.file_records().next().unwrap()?;
+        assert_eq!(record.name, "C:\\build\\src\\foo.cpp");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_path_mappings() -> Result<(), BreakpadError> {
+        let data = b"MODULE windows x86 3249D99D0C4049318610F4E4FB0B69361 crash.pdb
+FILE 0 /builds/worker/checkouts/gecko/dom/base/Element.cpp
+FILE 1 C:\\build\\src\\foo.cpp
+FILE 2 /builds/worker/checkouts/gecko-dev/other.cpp
+FILE 3 /usr/include/stdio.h
+FUNC 1000 20 0 main
+1000 10 1 0
+1010 10 2 1
+";
+        let object = BreakpadObject::parse(data)?;
+        let mut session = object.debug_session()?;
+        session.set_path_mappings(vec![
+            ("/builds/worker".to_owned(), "/worker".to_owned()),
+            ("/builds/worker/checkouts/gecko/".to_owned(), "".to_owned()),
+            ("c:/build".to_owned(), "D:\\src".to_owned()),
+        ]);
+
+        let paths = |session: &BreakpadDebugSession| {
+            session
+                .files()
+                .map(|file| file.map(|file| file.abs_path_str()))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        assert_eq!(
+            paths(&session)?,
+            [
+                "dom/base/Element.cpp",
+                "D:\\src\\src\\foo.cpp",
+                "/worker/checkouts/gecko-dev/other.cpp",
+                "/usr/include/stdio.h",
+            ]
+        );
+
+        let function = session.functions().next().unwrap()?;
+        let lines: Vec<_> = function
+            .lines
+            .iter()
+            .map(|line| line.file.path_str())
+            .collect();
+        assert_eq!(lines, ["dom/base/Element.cpp", "D:\\src\\src\\foo.cpp"]);
+
+        // Canonicalization applies to the mapped path.
+        session.canonicalize_paths();
+        let file = session.files().nth(1).unwrap()?;
+        assert_eq!(file.dir_str(), "d:/src/src");
+
+        session.set_path_mappings(Vec::new());
+        assert_eq!(
+            paths(&session)?[0],
+            "/builds/worker/checkouts/gecko/dom/base/Element.cpp"
+        );
+        let file = session.files().nth(1).unwrap()?;
+        assert_eq!(file.dir_str(), "c:/build/src");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate_memory_usage() -> Result<(), BreakpadError> {
+        let small = BreakpadObject::parse(DANGLING_FILE_ID)?;
+        let large = BreakpadObject::parse(REORDERED)?;
+
+        assert!(small.estimate_memory_usage() > DANGLING_FILE_ID.len());
+        assert!(small.estimate_memory_usage() < large.estimate_memory_usage());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stack_record_flavors() -> Result<(), BreakpadError> {
+        let data = b"MODULE windows x86 3249D99D0C4049318610F4E4FB0B69361 crash.pdb
+FUNC 1000 20 0 main
+STACK WIN 4 1000 10 0 0 0 0 0 0 1 $eip 4 + ^ = $esp $esp 8 + =
+STACK CFI INIT 1000 10 .cfa: $esp 4 + .ra: .cfa 4 - ^
+STACK CFI 1004 .cfa: $esp 8 +
+STACK WIN 4 1010 10 0 0 0 0 0 0 1 $eip 4 + ^ = $esp $esp 8 + =
+STACK CFI INIT 1010 10 .cfa: $esp 4 + .ra: .cfa 4 - ^
+";
+        let object = BreakpadObject::parse(data)?;
+        assert!(object.has_cfi_info());
+        assert!(object.has_win_info());
+
+        let cfi = object.cfi_records().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            cfi.iter().map(|r| r.start).collect::<Vec<_>>(),
+            [0x1000, 0x1010]
+        );
+        assert_eq!(cfi[0].deltas().count(), 1);
+
+        let win = object.win_records().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            win.iter().map(|r| r.code_start).collect::<Vec<_>>(),
+            [0x1000, 0x1010]
+        );
+
+        let object = BreakpadObject::parse(DANGLING_FILE_ID)?;
+        assert!(!object.has_cfi_info());
+        assert!(!object.has_win_info());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_dedup() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 src/main.c
+FILE 1 src/util.c
+FILE 2 src/main.c
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.debug_session()?;
+        assert_eq!(session.files().count(), 3);
+
+        let paths = session
+            .files_dedup()
+            .map(|file| file.map(|file| file.path_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(paths, ["src/main.c", "src/util.c"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_cfi_rules() -> Result<(), BreakpadError> {
+        let record = BreakpadStackCfiRecord::parse(
+            b"STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^",
+        )?;
+        let rule = |register, expression| BreakpadCfiRule {
+            register,
+            expression,
+        };
+        assert_eq!(
+            record.parsed_init_rules()?,
+            [rule(".cfa", "$rsp 8 +"), rule(".ra", ".cfa -8 + ^")]
+        );
+
+        let delta = BreakpadStackCfiDeltaRecord::parse(b"STACK CFI 1004 .cfa: $rsp +")?;
+        let error = delta.parsed_rules().unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::InvalidCfiRules);
+
+        for rules in ["$rsp 8 +", ".cfa:", ": $rsp", ".cfa: $rsp 8", ".cfa: ^"] {
+            assert!(parse_cfi_rules(rules).is_err(), "{}", rules);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_cfi_rules() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+STACK CFI 1004 .cfa: $rsp 16 +
+STACK CFI 1008 .cfa: $rsp 16 + +
+";
+        let object = BreakpadObject::parse(data)?;
+        assert_eq!(
+            object.validate(),
+            [BreakpadValidationIssue::InvalidCfiRules { line_number: 4 }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_by_id() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 src/main.c
+FILE 3 src/util.c
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.debug_session()?;
+
+        let file = session.file_by_id(3).unwrap();
+        assert_eq!(file.path_str(), "src/util.c");
+        assert!(session.file_by_id(1).is_none());
+
+        let files = session
+            .files_with_ids()
+            .map(|file| file.map(|(id, file)| (id, file.path_str())))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(files, [(0, "src/main.c".into()), (3, "src/util.c".into())]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_minimal() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 5F2D4E3A crash.so
+FILE 0 main.c
+FUNC 1000 20 0 main
+1000 20 1 0
+PUBLIC 2000 0 _start
+STACK CFI INIT 1000 20 .cfa: $rsp 8 +
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let mut minimal = Vec::new();
+        object.write_minimal(&mut minimal).unwrap();
+        assert_eq!(
+            str::from_utf8(&minimal)?,
+            "MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 5F2D4E3A crash.so
+PUBLIC 2000 0 _start
+"
+        );
+
+        let minimal = BreakpadObject::parse(&minimal)?;
+        assert_eq!(minimal.debug_id(), object.debug_id());
+        assert_eq!(minimal.code_id(), object.code_id());
+        assert!(minimal.has_symbols());
+        assert!(!minimal.has_debug_info());
+        assert!(!minimal.has_unwind_info());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedupe_functions() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC m 1000 10 0 folded_a
+FUNC m 1000 10 0 folded_b
+1000 10 1 0
+FUNC m 1000 10 0 folded_c
+FUNC 1010 10 0 other
+1010 10 2 0
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.debug_session()?;
+        let functions = session.dedupe_functions()?;
+
+        assert_eq!(functions.len(), 2);
+
+        let folded = &functions[0];
+        assert_eq!(folded.function.name.as_str(), "folded_b");
+        assert_eq!(folded.function.lines.len(), 1);
+        let aliases: Vec<_> = folded.aliases.iter().map(|name| name.as_str()).collect();
+        assert_eq!(aliases, ["folded_a", "folded_c"]);
+
+        let other = &functions[1];
+        assert_eq!(other.function.name.as_str(), "other");
+        assert!(other.aliases.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "demangle")]
+    fn test_demangled_name() -> Result<(), BreakpadError> {
+        let record = BreakpadPublicRecord::parse(b"PUBLIC 1000 0 _ZN3foo3barEv")?;
+        assert_eq!(record.demangled_name().as_deref(), Some("foo::bar()"));
+
+        let record = BreakpadPublicRecord::parse(b"PUBLIC 1000 0 ?bar@foo@@QAEXXZ")?;
+        assert_eq!(
+            record.demangled_name().as_deref(),
+            Some("public: void __thiscall foo::bar(void)")
+        );
+
+        let record = BreakpadFuncRecord::parse(b"FUNC 1000 10 0 foo::bar()", Lines::default())?;
+        assert_eq!(record.demangled_name(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "unwind")]
+    fn test_unwind_session() -> Result<(), BreakpadError> {
+        let data = b"MODULE windows x86 5A9832E5287241C1838ED98914E9B7FF1 crash.pdb
+STACK WIN 0 2000 20 1 0 0 0 0 0 0 1
+STACK WIN 4 2000 10 1 0 0 0 0 0 1 $eip 4 + ^ =
+STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+STACK CFI 1004 .cfa: $rsp 16 +
+STACK CFI 1008 .cfa: $rsp 24 +
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.unwind_session()?;
+
+        let rules = |address| session.cfi_rules_for_address(address);
+        assert_eq!(rules(0xfff), None);
+        assert_eq!(
+            rules(0x1000).as_deref(),
+            Some(".cfa: $rsp 8 + .ra: .cfa -8 + ^")
+        );
+        assert_eq!(
+            rules(0x1005).as_deref(),
+            Some(".cfa: $rsp 8 + .ra: .cfa -8 + ^ .cfa: $rsp 16 +")
+        );
+        assert_eq!(
+            rules(0x100f).as_deref(),
+            Some(".cfa: $rsp 8 + .ra: .cfa -8 + ^ .cfa: $rsp 16 + .cfa: $rsp 24 +")
+        );
+        assert_eq!(rules(0x1010), None);
+
+        let win = |address| session.win_record_for_address(address).map(|r| r.ty);
+        assert_eq!(win(0x1fff), None);
+        assert_eq!(win(0x2000), Some(BreakpadStackWinRecordType::FrameData));
+        assert_eq!(win(0x2010), Some(BreakpadStackWinRecordType::Fpo));
+        assert_eq!(win(0x2020), None);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "unwind")]
+    fn test_unwind_session_nested() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+STACK CFI INIT 1000 100 .cfa: $rsp 8 +
+STACK CFI 1010 .cfa: $rsp 16 +
+STACK CFI INIT 1020 10 .cfa: $rsp 32 +
+STACK CFI INIT 1040 20 .cfa: $rsp 48 +
+STACK CFI INIT 1048 8 .cfa: $rsp 64 +
+STACK CFI INIT 2000 10 .cfa: $rsp 80 +
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.unwind_session()?;
+
+        let rules = |address| session.cfi_rules_for_address(address);
+        assert_eq!(rules(0x1024).as_deref(), Some(".cfa: $rsp 32 +"));
+        assert_eq!(
+            rules(0x1030).as_deref(),
+            Some(".cfa: $rsp 8 + .cfa: $rsp 16 +")
+        );
+        assert_eq!(rules(0x104c).as_deref(), Some(".cfa: $rsp 64 +"));
+        assert_eq!(rules(0x1058).as_deref(), Some(".cfa: $rsp 48 +"));
+        assert_eq!(
+            rules(0x1080).as_deref(),
+            Some(".cfa: $rsp 8 + .cfa: $rsp 16 +")
+        );
+        assert_eq!(rules(0x1100), None);
+        assert_eq!(rules(0x2008).as_deref(), Some(".cfa: $rsp 80 +"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_offsets() -> Result<(), BreakpadError> {
+        let data = b"\xef\xbb\xbfMODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash\r
+FILE 0 main.c\r
+FUNC 1000 10 0 main\r
+1000 8 1 0\r
+1008 8 2 0\r
+FUNC 1010 10 0 other\r
+PUBLIC 2000 0 _start\r
+STACK CFI INIT 1000 10 .cfa: $rsp 8 +\r
+STACK CFI 1004 .cfa: $rsp 16 +\r
+STACK WIN 4 2170 14 1 0 0 0 0 0 1 $eip 4 + ^ =";
+        let object = BreakpadObject::parse(data)?;
+
+        let mut funcs = object.func_records();
+        assert_eq!(funcs.offset(), None);
+        let main = funcs.next().unwrap()?;
+        let offset = funcs.offset().unwrap();
+        let mut lines = Lines::new(&object.data()[offset..]);
+        let reparsed = BreakpadFuncRecord::parse(lines.next().unwrap(), lines)?;
+        assert_eq!(reparsed, main);
+        assert_eq!(
+            reparsed.line_block_range().len(),
+            main.line_block_range().len()
+        );
+
+        let range = main.line_block_range();
+        assert_eq!(&data[range.clone()], b"1000 8 1 0\r\n1008 8 2 0\r\n");
+        let lines = Lines::new(&data[range])
+            .filter(|line| !line.is_empty())
+            .map(BreakpadLineRecord::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(lines, main.lines().collect::<Result<Vec<_>, _>>()?);
+
+        let other = funcs.next().unwrap()?;
+        assert!(other.line_block_range().is_empty());
+
+        let mut publics = object.public_records();
+        let public = publics.next().unwrap()?;
+        let offset = publics.offset().unwrap();
+        let line = object.data()[offset..]
+            .split(|&b| b == b'\n')
+            .next()
+            .unwrap();
+        assert_eq!(BreakpadPublicRecord::parse(line)?, public);
+
+        let mut stacks = object.stack_records();
+        assert_eq!(stacks.offset(), None);
+        for _ in 0..2 {
+            let record = stacks.next().unwrap()?;
+            let offset = stacks.offset().unwrap();
+            let mut reopened = BreakpadStackRecords::new(&object.data()[offset..]);
+            assert_eq!(reopened.next().unwrap()?, record);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn test_func_records_stream() -> Result<(), BreakpadError> {
+        use futures::task::{noop_waker_ref, Context, Poll};
+        use futures::Stream;
+
+        let mut data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash\n".to_vec();
+        for i in 0..250 {
+            writeln!(data, "FUNC {:x} 10 0 func_{}", i * 0x10, i).unwrap();
+        }
+
+        let object = BreakpadObject::parse(&data)?;
+        let mut stream = object.func_records_stream();
+        let mut context = Context::from_waker(noop_waker_ref());
+
+        let mut records = Vec::new();
+        let mut pending = 0;
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut context) {
+                Poll::Ready(Some(record)) => records.push(record?),
+                Poll::Ready(None) => break,
+                Poll::Pending => pending += 1,
+            }
+        }
+
+        assert_eq!(
+            records,
+            object.func_records().collect::<Result<Vec<_>, _>>()?
+        );
+        assert_eq!(pending, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_records_for_file() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FILE 1 util.h
+FUNC 1000 20 0 main
+1000 8 1 0
+1008 8 10 1
+1010 10 2 0
+FUNC 1020 10 0 helper
+1020 10 11 1
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let all = object.all_line_records().collect::<Result<Vec<_>, _>>()?;
+        let addresses: Vec<_> = all
+            .iter()
+            .map(|(line, func)| (line.address, *func))
+            .collect();
+        assert_eq!(
+            addresses,
+            [
+                (0x1000, 0x1000),
+                (0x1008, 0x1000),
+                (0x1010, 0x1000),
+                (0x1020, 0x1020)
+            ]
+        );
+
+        let lines = object
+            .line_records_for_file(1)
+            .collect::<Result<Vec<_>, _>>()?;
+        let line_numbers: Vec<_> = lines.iter().map(|line| line.line).collect();
+        assert_eq!(line_numbers, [10, 11]);
+
+        assert_eq!(object.line_records_for_file(2).count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_symbol_by_address() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 10 0 main
+1000 10 1 0
+FUNC 1020 0 0 unsized
+PUBLIC 1000 0 main_public
+PUBLIC 1040 0 _start
+PUBLIC 1060 0 _end
+";
+        let object = BreakpadObject::parse(data)?;
+        let lookup = |address| {
+            object
+                .symbol_by_address(address)
+                .map(|s| (s.name.unwrap().into_owned(), s.address, s.size))
+        };
+
+        // Before the first symbol.
+        assert_eq!(lookup(0xfff), None);
+
+        // The function is preferred over the public symbol at the same address.
+        assert_eq!(lookup(0x1000), Some(("main".into(), 0x1000, 0x10)));
+        assert_eq!(lookup(0x100f), Some(("main".into(), 0x1000, 0x10)));
+
+        // In the gap after a function, the preceding function is returned.
+        assert_eq!(lookup(0x1018), Some(("main".into(), 0x1000, 0x10)));
+
+        // Sizes of unsized functions and public symbols extend to the next symbol.
+        assert_eq!(lookup(0x1030), Some(("unsized".into(), 0x1020, 0x20)));
+        assert_eq!(lookup(0x1050), Some(("_start".into(), 0x1040, 0x20)));
+
+        // The last symbol has no known end.
+        assert_eq!(lookup(0x2000), Some(("_end".into(), 0x1060, 0)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameter_size_for_address() -> Result<(), BreakpadError> {
+        let data = b"MODULE windows x86 3249D99D0C4049318610F4E4FB0B69361 crash.pdb
+FUNC 1000 10 8 _main
+PUBLIC 1010 c _callback@12
+PUBLIC 1030 0 _exit
+";
+        let object = BreakpadObject::parse(data)?;
+        assert_eq!(object.parameter_size_for_address(0xfff), None);
+        assert_eq!(object.parameter_size_for_address(0x1008), Some(8));
+        assert_eq!(object.parameter_size_for_address(0x1020), Some(0xc));
+        assert_eq!(object.parameter_size_for_address(0x2000), Some(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_module_record() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 wrong name\r
+FILE 0 main.c\r
+FUNC 1000 10 0 main\r
+";
+        let object = BreakpadObject::parse(data)?;
+        let module = BreakpadModuleRecord {
+            name: "crash",
+            ..object.module_record().clone()
+        };
+
+        let rewritten = object.with_module_record(&module)?;
+        assert_eq!(
+            rewritten,
+            b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash\r
+FILE 0 main.c\r
+FUNC 1000 10 0 main\r
+"
+        );
+
+        let rewritten = BreakpadObject::parse(&rewritten)?;
+        assert_eq!(rewritten.module_record(), &module);
+        assert_eq!(rewritten.name(), "crash");
+        assert_eq!(rewritten.debug_id(), object.debug_id());
+        assert_eq!(rewritten.arch(), object.arch());
+
+        let invalid = |module| object.with_module_record(&module).unwrap_err().kind();
+        let base = object.module_record().clone();
+        assert_eq!(
+            invalid(BreakpadModuleRecord {
+                id: "xyz",
+                ..base.clone()
+            }),
+            BreakpadErrorKind::InvalidModuleRecord
+        );
+        assert_eq!(
+            invalid(BreakpadModuleRecord {
+                arch: "pdp11",
+                ..base.clone()
+            }),
+            BreakpadErrorKind::InvalidArchitecture
+        );
+        assert_eq!(
+            invalid(BreakpadModuleRecord {
+                name: "a\nFUNC",
+                ..base
+            }),
+            BreakpadErrorKind::InvalidModuleRecord
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_code_coverage_percent() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1000 20 0 main
+1000 10 1 0
+1010 8 2 0
+FUNC 1020 20 0 stripped
+";
+        let object = BreakpadObject::parse(data)?;
+        assert_eq!(object.code_coverage_percent()?, Some(0.375));
+
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+PUBLIC 1000 0 main
+";
+        let object = BreakpadObject::parse(data)?;
+        assert_eq!(object.code_coverage_percent()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_functions_by_size() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 10 0 small
+FUNC 1010 40 0 large
+FUNC 1050 20 0 medium
+FUNC 1070 20 0 medium2
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let large = object
+            .large_functions(0x20)
+            .map(|func| func.map(|func| func.name))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(large, ["large", "medium", "medium2"]);
+
+        let names: Vec<_> = object
+            .functions_by_size()?
+            .into_iter()
+            .map(|func| func.name)
+            .collect();
+        assert_eq!(names, ["large", "medium", "medium2", "small"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_lenient() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux madeup64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 10 0 main
+";
+        let error = BreakpadObject::parse(data).unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::InvalidArchitecture);
+
+        let object = BreakpadObject::parse_lenient(data)?;
+        assert_eq!(object.arch(), Arch::Unknown);
+        assert_eq!(object.raw_arch(), "madeup64");
+        assert_eq!(object.raw_os(), "Linux");
+        assert_eq!(object.func_records().next().unwrap()?.name, "main");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_long_module_name() -> Result<(), BreakpadError> {
+        let name = format!("C:\\build\\{}crash.pdb", "nested\\".repeat(70));
+        assert!(name.len() > 500);
+
+        let data = format!(
+            "MODULE windows x86_64 3249D99D0C4049318610F4E4FB0B69361 {}\nFILE 0 \u{e9}.c\n",
+            name
+        );
+        let object = BreakpadObject::parse(data.as_bytes())?;
+        assert_eq!(object.name(), name);
+
+        // A module name with a multi-byte character split by the cap.
+        let mut data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 ".to_vec();
+        data.resize(BREAKPAD_HEADER_CAP - 1, b'a');
+        data.extend_from_slice("\u{e9}".as_bytes());
+        let object = BreakpadObject::parse(&data)?;
+        assert!(object.name().ends_with("a\u{e9}"));
+
+        data.extend_from_slice(b"bc\nFILE 0 main.c\n");
+        let object = BreakpadObject::parse(&data)?;
+        assert!(object.name().ends_with("a\u{e9}"));
+
+        // A character that is incomplete at the end of the data is dropped.
+        data.truncate(BREAKPAD_HEADER_CAP);
+        let object = BreakpadObject::parse(&data)?;
+        assert!(object.name().ends_with('a'));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_threads() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FILE 1 util.c
+FUNC 1000 20 0 main
+1000 10 1 0
+1010 10 2 1
+FUNC 1040 10 0 helper
+1040 10 7 1
+PUBLIC 1000 0 main
+PUBLIC 1060 0 _start
+STACK CFI INIT 1000 20 .cfa: $rsp 8 +
+STACK CFI 1008 .cfa: $rsp 16 +
+STACK WIN 4 1040 10 0 0 0 0 0 0 1 $T0 .raSearch =
+";
+        let object = BreakpadObject::parse(data)?;
+        let index = object.index();
+        assert_eq!(index.file_map().len(), 2);
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let main = index.lookup(0x1014).unwrap();
+                        assert_eq!(main.function_name, "main");
+                        assert_eq!((main.file, main.line), (Some("util.c"), Some(2)));
+
+                        let helper = index.lookup(0x1048).unwrap();
+                        assert_eq!(helper.function_name, "helper");
+                        assert_eq!(helper.line, Some(7));
+
+                        assert!(index.lookup(0x1030).is_none());
+                        assert_eq!(index.public_at(0x1070).unwrap().name, "_start");
+
+                        match index.stack_record_at(0x100c) {
+                            Some(BreakpadStackRecord::Cfi(cfi)) => {
+                                assert_eq!(cfi.deltas().count(), 1)
+                            }
+                            other => panic!("unexpected stack record {:?}", other),
+                        }
+                        assert!(matches!(
+                            index.stack_record_at(0x1040),
+                            Some(BreakpadStackRecord::Win(_))
+                        ));
+                        assert!(index.stack_record_at(0x1020).is_none());
+                    })
+                })
+                .collect();
+
+            for handle in handles {
+                handle.join().unwrap();
+            }
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_records_by_address() -> Result<(), BreakpadError> {
+        let mut funcs = [
+            BreakpadFuncRecord::parse(b"FUNC 2000 10 0 b", Lines::default())?,
+            BreakpadFuncRecord::parse(b"FUNC 1000 10 0 a", Lines::default())?,
+        ];
+        funcs.sort();
+        assert_eq!(funcs[0].name, "a");
+
+        let a = BreakpadPublicRecord::parse(b"PUBLIC 1000 0 a")?;
+        let b = BreakpadPublicRecord::parse(b"PUBLIC 1000 0 b")?;
+        assert_ne!(a, b);
+        assert_eq!(a.cmp(&b), Ordering::Less);
+        assert_eq!(a.cmp(&a.clone()), Ordering::Equal);
+
+        let early = BreakpadStackCfiRecord::parse(b"STACK CFI INIT 10 4 .cfa: $esp 4 +")?;
+        let late = BreakpadStackCfiRecord::parse(b"STACK CFI INIT 20 4 .cfa: $esp 4 +")?;
+        assert!(early < late);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_functions_par_collect() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1000 20 0 main
+1000 10 1 0
+1010 10 2 7
+FUNC 1020 10 0 helper
+1020 10 5 0
+FUNC 1030 0 0 empty
+PUBLIC 1040 0 public
+STACK CFI INIT 1000 20 .cfa: $rsp 8 +
+FUNC 2000 10 0 after_stack
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.debug_session()?;
+
+        let sequential = session.functions().collect::<Result<Vec<_>, _>>()?;
+        let parallel = session.functions().par_collect()?;
+        let summarize =
+            |function: &Function<'_>| (function.address, function.size, function.lines.len());
+        assert_eq!(
+            parallel.iter().map(summarize).collect::<Vec<_>>(),
+            sequential.iter().map(summarize).collect::<Vec<_>>(),
+        );
+        assert_eq!(parallel.len(), 3);
+
+        // The line record in `main` references an unknown file id.
+        let error = session.functions_strict().par_collect().unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            BreakpadErrorKind::UnknownFileId { file_id: 7, .. }
+        ));
+
+        let (functions, errors) = session.functions_strict().par_collect_lenient();
+        assert_eq!(functions.len(), 2);
+        assert_eq!(functions[0].name.as_str(), "helper");
+        assert_eq!(errors.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_file_id_consistency() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1000 20 0 main
+1000 10 4 0
+1010 10 5 3
+FUNC 1020 10 0 helper
+1020 10 9 1
+";
+        let object = BreakpadObject::parse(data)?;
+        let errors = object.check_file_id_consistency()?;
+        assert_eq!(
+            errors,
+            [
+                BreakpadFileIdError {
+                    file_id: 3,
+                    func_address: 0x1000,
+                    line_number: 5,
+                },
+                BreakpadFileIdError {
+                    file_id: 1,
+                    func_address: 0x1020,
+                    line_number: 9,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_byte_ranges_reassemble() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash\r
+INFO CODE_ID 492E2DD23CC306CA9C494EEF1533A381\r
+FILE 0 main.c\r
+FILE 1 util.c\r
+FUNC 1000 20 0 main\r
+1000 10 1 0\r
+1010 10 2 1\r
+FUNC 1040 10 0 helper\r
+1040 10 7 1\r
+PUBLIC 1000 0 main\r
+PUBLIC 1060 0 _start\r
+STACK CFI INIT 1000 20 .cfa: $rsp 8 +\r
+STACK CFI 1008 .cfa: $rsp 16 +\r
+STACK WIN 4 1040 10 0 0 0 0 0 0 1 $T0 .raSearch =\r
+STACK CFI INIT 1040 10 .cfa: $rsp 8 +";
+        let object = BreakpadObject::parse(data)?;
+
+        let header = object.header_range();
+        assert!(data[header.clone()].ends_with(b"A381\r\n"));
+
+        let mut output = data[header].to_vec();
+        output.extend_from_slice(&data[object.file_block_range().unwrap()]);
+
+        let mut funcs = object.func_records();
+        while let Some(func) = funcs.next() {
+            func?;
+            output.extend_from_slice(&data[funcs.byte_range().unwrap()]);
+        }
+        output.extend_from_slice(&data[object.public_block_range().unwrap()]);
+
+        let mut cfi = object.cfi_records();
+        while let Some(record) = cfi.next() {
+            record?;
+            output.extend_from_slice(&data[cfi.byte_range().unwrap()]);
+        }
+
+        let expected: Vec<u8> = data
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.starts_with(b"STACK WIN"))
+            .collect::<Vec<_>>()
+            .join(&b'\n');
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            String::from_utf8_lossy(&expected)
+        );
+
+        let reassembled = BreakpadObject::parse(&output)?;
+        assert_eq!(reassembled.func_records().count(), 2);
+        assert_eq!(reassembled.cfi_records().count(), 2);
+        assert!(reassembled.win_records().next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_arc() -> Result<(), BreakpadError> {
+        let data: Arc<[u8]> = Arc::from(
+            &b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 20 0 main
+"[..],
+        );
+
+        let first = BreakpadObject::from_arc(data.clone())?;
+        let second = BreakpadObject::from_arc(data.clone())?;
+        assert_eq!(Arc::strong_count(&data), 3);
+        assert_eq!(first.get().name(), "crash");
+        assert_eq!(second.get().func_records().next().unwrap()?.name, "main");
+        assert!(std::ptr::eq(first.get().data(), &data[..]));
+
+        drop(data);
+        assert_eq!(first.get().debug_id(), second.get().debug_id());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lines_out_of_range() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1000 20 0 overshoot
+1000 10 1 0
+1010 20 2 0
+FUNC 1040 20 0 undershoot
+1030 20 3 0
+1050 10 4 0
+1070 10 5 0
+FUNC 1080 0 0 unsized
+1000 10 6 0
+";
+        let object = BreakpadObject::parse(data)?;
+        let issues = object.validate();
+        let out_of_range: Vec<_> = issues
+            .iter()
+            .filter_map(|issue| match *issue {
+                BreakpadValidationIssue::LineOutOfRange {
+                    function_address,
+                    line_address,
+                    ..
+                } => Some((function_address, line_address)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            out_of_range,
+            [(0x1000, 0x1010), (0x1040, 0x1030), (0x1040, 0x1070)]
+        );
+
+        let session = object.debug_session()?;
+        let lines = |function: &Function<'_>| {
+            function
+                .lines
+                .iter()
+                .map(|line| (line.address, line.size, line.line))
+                .collect::<Vec<_>>()
+        };
+
+        let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(lines(&functions[0])[1], (0x1010, Some(0x20), 2));
+
+        let functions = session
+            .functions()
+            .clamp_lines()
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            lines(&functions[0]),
+            [(0x1000, Some(0x10), 1), (0x1010, Some(0x10), 2)]
+        );
+        assert_eq!(
+            lines(&functions[1]),
+            [(0x1040, Some(0x10), 3), (0x1050, Some(0x10), 4)]
+        );
+        assert_eq!(lines(&functions[2]), [(0x1000, Some(0x10), 6)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_records_sorted() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 2000 10 0 b
+FUNC 1000 10 0 a
+PUBLIC 3000 0 d
+PUBLIC 2000 0 c
+STACK CFI INIT 2000 10 .cfa: $rsp 8 +
+STACK CFI INIT 1000 10 .cfa: $rsp 8 +
+STACK CFI 1004 .cfa: $rsp 16 +
+";
+        let object = BreakpadObject::parse(data)?;
+        assert!(!object.validate_public_sorted());
+
+        let funcs = object.func_records_sorted()?;
+        assert_eq!(funcs[0].name, "a");
+        let publics = object.public_records_sorted()?;
+        assert_eq!(publics[0].name, "c");
+        let cfi = object.stack_cfi_records_sorted()?;
+        assert_eq!(cfi[0].start, 0x1000);
+        assert_eq!(cfi[0].deltas().count(), 1);
+
+        let sorted = BreakpadObject::parse(
+            b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+PUBLIC 1000 0 a
+PUBLIC 1000 0 b
+PUBLIC 2000 0 c
+",
+        )?;
+        assert!(sorted.validate_public_sorted());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_records_sorted_same_address() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 2000 10 0 f
+FUNC 1000 10 0 a
+FUNC 1000 20 0 b
+FUNC 1000 30 0 c
+PUBLIC 2000 0 f
+PUBLIC 1000 0 a
+PUBLIC 1000 0 b
+PUBLIC 1000 0 c
+STACK CFI INIT 2000 10 .cfa: $rsp 8 +
+STACK CFI INIT 1000 10 .cfa: $rsp 8 +
+STACK CFI INIT 1000 20 .cfa: $rsp 16 +
+STACK CFI INIT 1000 30 .cfa: $rsp 24 +
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let funcs = object.func_records_sorted()?;
+        let names: Vec<_> = funcs.iter().map(|func| func.name).collect();
+        assert_eq!(names, ["a", "b", "c", "f"]);
+
+        let publics = object.public_records_sorted()?;
+        let names: Vec<_> = publics.iter().map(|public| public.name).collect();
+        assert_eq!(names, ["a", "b", "c", "f"]);
+
+        let cfi = object.stack_cfi_records_sorted()?;
+        let sizes: Vec<_> = cfi.iter().map(|record| record.size).collect();
+        assert_eq!(sizes, [0x10, 0x20, 0x30, 0x10]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_end_address_overflow() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC ffffffffffffff00 ff 0 top
+ffffffffffffff00 f0 1 0
+fffffffffffffff0 20 2 0
+STACK CFI INIT ffffffffffffff00 ff .cfa: $rsp 8 +
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let func = object.func_records().next().unwrap()?;
+        assert_eq!(func.end_address(), Some(u64::MAX));
+        let line = func.lines().nth(1).unwrap()?;
+        assert_eq!(line.end_address(), None);
+        assert_eq!(line.range(), 0xffff_ffff_ffff_fff0..u64::MAX);
+        let cfi = object.cfi_records().next().unwrap()?;
+        assert_eq!(cfi.end_address(), Some(u64::MAX));
+
+        let session = object.debug_session()?;
+        let location = session.lookup(0xffff_ffff_ffff_fff8).unwrap();
+        assert_eq!((location.function_name, location.line), ("top", Some(2)));
+        let location = object.index().lookup(0xffff_ffff_ffff_fff8).unwrap();
+        assert_eq!(location.line, Some(2));
+        assert!(session.lookup(u64::MAX).is_none());
+
+        assert!(object
+            .validate()
+            .contains(&BreakpadValidationIssue::LineOutOfRange {
+                function_address: 0xffff_ffff_ffff_ff00,
+                line_address: 0xffff_ffff_ffff_fff0,
+                line_size: 0x20,
+            }));
+
+        let functions = session
+            .functions()
+            .clamp_lines()
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(functions[0].lines[1].size, Some(0xf));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate_size_breakdown() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1000 20 0 main
+1000 20 1 0
+
+INLINE_ORIGIN 0 inlined
+STACK CFI INIT 1000 20 .cfa: $rsp 8 +
+STACK CFI 1008 .cfa: $rsp 16 +";
+        let object = BreakpadObject::parse(data)?;
+        let breakdown = object.estimate_size_breakdown();
+
+        assert_eq!(breakdown["MODULE"], 60);
+        assert_eq!(breakdown["FILE"], 14);
+        assert_eq!(breakdown["<LINE>"], 12);
+        assert_eq!(breakdown["<OTHER>"], 25);
+        assert_eq!(breakdown["STACK CFI INIT"], 38);
+        assert_eq!(breakdown["STACK CFI"], 30);
+        assert!(!breakdown.contains_key("PUBLIC"));
+        assert_eq!(breakdown.values().sum::<usize>(), data.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_public_symbols() -> Result<(), BreakpadError> {
+        let data = b"MODULE windows x86 3249D99D0C4049318610F4E4FB0B69361 crash.pdb
+PUBLIC m 1000 8 _memcpy
+PUBLIC 2000 c __stdcall@12
+PUBLIC invalid
+";
+        let object = BreakpadObject::parse(data)?;
+        let symbols: Vec<_> = object.public_symbols().collect();
+
+        insta::assert_debug_snapshot!(symbols, @r###"
+       ⋮[
+       ⋮    BreakpadSymbol {
+       ⋮        address: 4096,
+       ⋮        size: None,
+       ⋮        name: "_memcpy",
+       ⋮        origin: Public,
+       ⋮        parameter_size: 8,
+       ⋮        multiple: true,
+       ⋮    },
+       ⋮    BreakpadSymbol {
+       ⋮        address: 8192,
+       ⋮        size: None,
+       ⋮        name: "__stdcall@12",
+       ⋮        origin: Public,
+       ⋮        parameter_size: 12,
+       ⋮        multiple: false,
+       ⋮    },
+       ⋮]
+        "###);
+
+        // The generic symbols drop the Breakpad metadata.
+        assert_eq!(object.symbols().count(), symbols.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stack_cfi_into_owned() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+STACK CFI 1008 .cfa: $rsp 24 +
+STACK CFI 1004 .cfa: $rsp 16 +
+STACK CFI 1008 .cfa: $rsp 32 +
+STACK CFI INIT 2000 10 .cfa: $rsp 8 +
+";
+        let object = BreakpadObject::parse(data)?;
+        let record = object.cfi_records().next().unwrap()?.into_owned()?;
+        drop(object);
+
+        assert_eq!(record.init_rules, ".cfa: $rsp 8 + .ra: .cfa -8 + ^");
+        assert_eq!(
+            record.deltas,
+            [
+                (0x1004, ".cfa: $rsp 16 +".to_owned()),
+                (0x1008, ".cfa: $rsp 24 +".to_owned()),
+                (0x1008, ".cfa: $rsp 32 +".to_owned()),
+            ]
+        );
+
+        assert_eq!(record.rules_at(0xfff), None);
+        assert_eq!(
+            record.rules_at(0x1000).as_deref(),
+            Some(".cfa: $rsp 8 + .ra: .cfa -8 + ^")
+        );
+        assert_eq!(
+            record.rules_at(0x1006).as_deref(),
+            Some(".cfa: $rsp 8 + .ra: .cfa -8 + ^ .cfa: $rsp 16 +")
+        );
+        assert_eq!(
+            record.rules_at(0x100f).as_deref(),
+            Some(".cfa: $rsp 8 + .ra: .cfa -8 + ^ .cfa: $rsp 16 + .cfa: $rsp 24 + .cfa: $rsp 32 +")
+        );
+        assert_eq!(record.rules_at(0x1010), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stack_cfi_from_rules() -> Result<(), BreakpadError> {
+        let record = BreakpadStackCfiRecord::from_rules(0x1000, 0x10, ".cfa: $rsp 8 +")?;
+        assert_eq!(record.deltas().count(), 0);
+
+        let line = record.to_string();
+        assert_eq!(BreakpadStackCfiRecord::parse(line.as_bytes())?, record);
+
+        assert!(BreakpadStackCfiRecord::from_rules(0x1000, 0x10, ".cfa: $rsp +").is_err());
+        assert!(BreakpadStackCfiRecord::from_rules(u64::MAX, 0x10, ".cfa: $rsp 8 +").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stack_cfi_builder() -> Result<(), BreakpadError> {
+        let mut builder = BreakpadStackCfiBuilder::new(0x1000, 0x10);
+        builder
+            .add_row(
+                0x1000,
+                [
+                    ("$rbp", "$rbp"),
+                    (".ra", ".cfa -8 + ^"),
+                    (".cfa", "$rsp 8 +"),
+                ],
+            )
+            .add_row(
+                0x1001,
+                [
+                    ("$rbp", "$rbp"),
+                    (".ra", ".cfa -8 + ^"),
+                    (".cfa", "$rsp 16 +"),
+                ],
+            )
+            .add_row(
+                0x1004,
+                [
+                    ("$rbp", "$rbp"),
+                    (".ra", ".cfa -8 + ^"),
+                    (".cfa", "$rsp 16 +"),
+                ],
+            )
+            .add_row(0x1008, [(".cfa", "$rsp 24 +"), ("$rbp", ".cfa -16 + ^")]);
+        let output = builder.build()?;
+
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^ $rbp: $rbp
+STACK CFI 1001 .cfa: $rsp 16 +
+STACK CFI 1008 .cfa: $rsp 24 + $rbp: .cfa -16 + ^
+"
+        );
+
+        #[cfg(feature = "unwind")]
+        {
+            let mut data =
+                b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash\n".to_vec();
+            data.extend_from_slice(&output);
+            let object = BreakpadObject::parse(&data)?;
+            let session = object.unwind_session()?;
+            assert_eq!(
+                session.cfi_rules_for_address(0x1005).as_deref(),
+                Some(".cfa: $rsp 8 + .ra: .cfa -8 + ^ $rbp: $rbp .cfa: $rsp 16 +")
+            );
+        }
+
+        let mut builder = BreakpadStackCfiBuilder::new(0x1000, 0x10);
+        builder
+            .add_row(0x1000, [(".cfa", "$rsp 8 +")])
+            .add_row(0x1008, [(".cfa", "$rsp 8 +")]);
+        assert_eq!(builder.build()?, b"STACK CFI INIT 1000 10 .cfa: $rsp 8 +\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stack_cfi_builder_errors() {
+        let builder = BreakpadStackCfiBuilder::new(0x1000, 0x10);
+        assert!(builder.build().is_err());
+
+        let mut builder = BreakpadStackCfiBuilder::new(0x1000, 0x10);
+        builder.add_row(0x1004, [(".cfa", "$rsp 8 +")]);
+        assert!(builder.build().is_err());
+
+        let mut builder = BreakpadStackCfiBuilder::new(0x1000, 0x10);
+        builder
+            .add_row(0x1000, [(".cfa", "$rsp 8 +")])
+            .add_row(0x1010, [(".cfa", "$rsp 16 +")]);
+        assert!(builder.build().is_err());
+
+        let mut builder = BreakpadStackCfiBuilder::new(0x1000, 0x10);
+        builder.add_row(0x1000, [(".cfa", "$rsp +")]);
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn test_parse_record_flags() -> Result<(), BreakpadError> {
+        let record = BreakpadFuncRecord::parse(b"FUNC m 1000 10 0 foo", Lines::default())?;
+        assert!(record.multiple);
+        assert!(record.unknown_flags.is_empty());
+        assert_eq!(record.address, 0x1000);
+
+        let record = BreakpadFuncRecord::parse(b"FUNC m z 1000 10 0 foo", Lines::default())?;
+        assert!(record.multiple);
+        assert_eq!(record.unknown_flags, ['z']);
+        assert_eq!(record.address, 0x1000);
+        assert_eq!(record.to_string(), "FUNC m z 1000 10 0 foo");
+
+        // Hex digits are never flags, so the address is parsed even if it starts with a letter.
+        let record = BreakpadFuncRecord::parse(b"FUNC m a000 10 0 foo", Lines::default())?;
+        assert!(record.multiple);
+        assert!(record.unknown_flags.is_empty());
+        assert_eq!(record.address, 0xa000);
+
+        let record = BreakpadFuncRecord::parse(b"FUNC a 10 0 foo", Lines::default())?;
+        assert_eq!(record.address, 0xa);
+        assert_eq!(record.name, "foo");
+
+        let record = BreakpadPublicRecord::parse(b"PUBLIC q m 2000 4 bar")?;
+        assert!(record.multiple);
+        assert_eq!(record.unknown_flags, ['q']);
+        assert_eq!(record.to_string(), "PUBLIC m q 2000 4 bar");
+
+        assert!(BreakpadFuncRecord::parse(b"FUNC mm 1000 10 0 foo", Lines::default()).is_err());
+
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC m z 1000 10 0 foo
+PUBLIC q 2000 4 bar
+";
+        let object = BreakpadObject::parse(data)?;
+        assert_eq!(
+            object.validate(),
+            [
+                BreakpadValidationIssue::UnknownFlags {
+                    line_number: 2,
+                    flags: vec!['z'],
+                },
+                BreakpadValidationIssue::UnknownFlags {
+                    line_number: 3,
+                    flags: vec!['q'],
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stack_records_by_type() -> Result<(), BreakpadError> {
+        let data = b"MODULE windows x86 5A9832E5287241C1838ED98914E9B7FF1 crash.pdb
+STACK CFI INIT 1000 10 .cfa: $esp 4 + .ra: .cfa -4 + ^
+STACK CFI 1004 .cfa: $esp 8 +
+STACK WIN 4 2000 10 1 0 0 0 0 0 1 $eip 4 + ^ =
+STACK CFI INIT 3000 10 .cfa: $esp 4 + .ra: .cfa -4 + ^
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let cfi = object
+            .cfi_records()
+            .map(|record| record.map(|record| record.start))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(cfi, [0x1000, 0x3000]);
+
+        let win = object
+            .win_records()
+            .map(|record| record.map(|record| record.code_start))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(win, [0x2000]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_multiple_lines() {
+        fn is_multiple_lines<T: fmt::Debug>(result: Result<T, BreakpadError>) -> bool {
+            matches!(result, Err(error) if error.kind() == BreakpadErrorKind::MultipleLines)
+        }
+
+        let module = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash\nFILE 0 a.c";
+        assert!(is_multiple_lines(BreakpadModuleRecord::parse(module)));
+        let info = b"INFO CODE_ID 5AB380779000 crash\r\nFILE 0 a.c";
+        assert!(is_multiple_lines(BreakpadInfoRecord::parse(info)));
+        let file = b"FILE 0 a.c\nFILE 1 b.c";
+        assert!(is_multiple_lines(BreakpadFileRecord::parse(file)));
+        let public = b"PUBLIC 1000 0 foo\nPUBLIC 2000 0 bar";
+        assert!(is_multiple_lines(BreakpadPublicRecord::parse(public)));
+        let func = b"FUNC 1000 10 0 foo\n1000 10 1 0";
+        assert!(is_multiple_lines(BreakpadFuncRecord::parse(
+            func,
+            Lines::default()
+        )));
+        let line = b"1000 8 1 0\n1008 8 2 0";
+        assert!(is_multiple_lines(BreakpadLineRecord::parse(line)));
+        let cfi = b"STACK CFI INIT 1000 10 .cfa: $rsp 8 +\nSTACK CFI 1004 .cfa: $rsp 16 +";
+        assert!(is_multiple_lines(BreakpadStackCfiRecord::parse(cfi)));
+        assert!(is_multiple_lines(BreakpadStackRecord::parse(cfi)));
+        let delta = b"STACK CFI 1004 .cfa: $rsp 16 +\rSTACK CFI 1008 .cfa: $rsp 24 +";
+        assert!(is_multiple_lines(BreakpadStackCfiDeltaRecord::parse(delta)));
+        let win =
+            b"STACK WIN 4 2000 10 1 0 0 0 0 0 1 $eip 4 + ^ =\nSTACK WIN 4 3000 10 1 0 0 0 0 0 1";
+        assert!(is_multiple_lines(BreakpadStackWinRecord::parse(win)));
+
+        // Surrounding line breaks are still trimmed.
+        assert!(BreakpadFileRecord::parse(b"FILE 0 a.c\r\n").is_ok());
+    }
+
+    #[test]
+    fn test_apple_arch_strings() -> Result<(), BreakpadError> {
+        let cases = [
+            ("arm64e", Arch::Arm64e),
+            ("arm64_32", Arch::Arm64_32),
+            ("armv7", Arch::ArmV7),
+            ("armv7s", Arch::ArmV7s),
+            ("armv7k", Arch::ArmV7k),
+            ("x86_64h", Arch::Amd64h),
+        ];
+
+        for (string, arch) in cases {
+            let data = format!(
+                "MODULE ios {} 492E2DD23CC306CA9C494EEF1533A3810 crash\n",
+                string
+            );
+            let object = BreakpadObject::parse(data.as_bytes())?;
+            assert_eq!(object.arch(), arch, "{}", string);
+            assert_eq!(object.raw_arch(), string);
+            assert_eq!(arch.name(), string);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_binary_roundtrip() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 5AB380779000 crash.exe
+INFO GENERATOR dump_syms
+FILE 0 a.c
+FILE 1 b.c MD5 0123456789abcdef
+FUNC m 1000 20 4 foo
+1000 10 1 0
+1010 10 2 1 7
+PUBLIC 2000 0 bar
+STACK CFI INIT 1000 20 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+STACK CFI 1004 .cfa: $rsp 16 +
+STACK WIN 4 2000 10 1 0 0 0 0 0 1 $eip 4 + ^ =
+STACK WIN 0 3000 10 1 0 0 0 0 0 0 2
+";
+        let object = BreakpadObject::parse(data)?;
+        let binary = object.to_compact_binary()?;
+        let compact = BreakpadObject::from_compact_binary(&binary)?;
+
+        assert_eq!(compact.module_record(), &object.module);
+        assert_eq!(compact.arch(), object.arch());
+
+        let infos = object.info_records().collect::<Result<Vec<_>, _>>()?;
+        let compact_infos = compact.info_records().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(compact_infos, infos);
+
+        let files = object.file_records().collect::<Result<Vec<_>, _>>()?;
+        let compact_files = compact.file_records().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(compact_files, files);
+
+        let publics = object.public_records().collect::<Result<Vec<_>, _>>()?;
+        let compact_publics = compact.public_records().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(compact_publics, publics);
+
+        let wins = object.win_records().collect::<Result<Vec<_>, _>>()?;
+        let compact_wins = compact.win_records().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(compact_wins, wins);
+
+        let funcs = object.func_records().collect::<Result<Vec<_>, _>>()?;
+        let compact_funcs = compact.func_records().collect::<Result<Vec<_>, _>>()?;
+        let compact_records = compact_funcs.iter().map(|f| f.record().clone());
+        assert_eq!(compact_records.collect::<Vec<_>>(), funcs);
+        assert!(compact_funcs[0].multiple);
+
+        let lines = funcs[0].lines().collect::<Result<Vec<_>, _>>()?;
+        let compact_lines = compact_funcs[0].lines().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(compact_lines, lines);
+        assert_eq!(compact_lines[1].column, Some(7));
+        assert_eq!(
+            compact_funcs[0].clone().into_owned(),
+            funcs[0].clone().into_owned()?
+        );
+
+        let cfis = object.cfi_records().collect::<Result<Vec<_>, _>>()?;
+        let compact_cfis = compact.cfi_records().collect::<Result<Vec<_>, _>>()?;
+        let compact_records = compact_cfis.iter().map(|c| c.record().clone());
+        assert_eq!(compact_records.collect::<Vec<_>>(), cfis);
+
+        let deltas = cfis[0].deltas().collect::<Result<Vec<_>, _>>()?;
+        let compact_deltas = compact_cfis[0].deltas().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(compact_deltas, deltas);
+        assert_eq!(compact_deltas.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_binary_invalid() -> Result<(), BreakpadError> {
+        let is_invalid = |result: Result<_, BreakpadError>| matches!(result, Err(error) if error.kind() == BreakpadErrorKind::InvalidCompactBinary);
+
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 a.c
+";
+        let binary = BreakpadObject::parse(data)?.to_compact_binary()?;
+        assert!(CompactBreakpadObject::test(&binary));
+        assert!(!CompactBreakpadObject::test(data));
+
+        assert!(is_invalid(
+            BreakpadObject::from_compact_binary(data).map(|_| ())
+        ));
+        let truncated = &binary[..binary.len() - 1];
+        assert!(is_invalid(
+            BreakpadObject::from_compact_binary(truncated).map(|_| ())
+        ));
+
+        let mut extended = binary.clone();
+        extended.push(0);
+        assert!(is_invalid(
+            BreakpadObject::from_compact_binary(&extended).map(|_| ())
+        ));
+
+        // Data after the last record of a section is reported after that record.
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 a.c
+FILE 1 b.c
+";
+        let object = BreakpadObject::parse(data)?;
+        let mut binary = object.to_compact_binary()?;
+        let module = object.module;
+        let strings = [module.os, module.arch, module.id, module.name];
+        let header = b"BPCB".len() + strings.iter().map(|s| 1 + s.len()).sum::<usize>();
+        // Skip the count and length of the INFO section to reach the FILE count.
+        assert_eq!(binary[header + 2], 2);
+        binary[header + 2] = 1;
+
+        let compact = BreakpadObject::from_compact_binary(&binary)?;
+        let mut files = compact.file_records();
+        assert_eq!(files.next().unwrap()?.name, "a.c");
+        assert!(is_invalid(files.next().unwrap().map(|_| ())));
+        assert!(files.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_code_id_normalization() -> Result<(), BreakpadError> {
+        let expected = CodeId::new("5f0a3ae91c2000".into());
+
+        for code_id in [
+            "5F0A3AE91C2000",
+            "5f0a3ae91c2000",
+            "5F0A3AE9-1C2000",
+            "5f0a3ae9-1C2000",
+        ] {
+            let data = format!(
+                "MODULE windows x86 5A9832E5287241C1838ED98914E9B7FF1 crash.pdb\n\
+                 INFO CODE_ID {} crash.exe\n",
+                code_id
+            );
+            let object = BreakpadObject::parse(data.as_bytes())?;
+            assert_eq!(object.code_id(), Some(expected.clone()), "{}", code_id);
+            assert_eq!(object.code_id().unwrap().as_str(), "5f0a3ae91c2000");
+            assert_eq!(object.raw_code_id(), Some(code_id));
+        }
+
+        // Tokens with separators elsewhere are code files, even if all their digits are hex.
+        for code_file in ["abc-def", "5f0a-3ae9-1c2000"] {
+            let data = format!(
+                "MODULE windows x86 5A9832E5287241C1838ED98914E9B7FF1 crash.pdb\n\
+                 INFO CODE_ID {}\n",
+                code_file
+            );
+            let object = BreakpadObject::parse(data.as_bytes())?;
+            assert_eq!(object.code_id(), None, "{}", code_file);
+            assert_eq!(object.code_file(), Some(code_file));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_module_platform() -> Result<(), BreakpadError> {
+        let platform =
+            |line: &str| BreakpadModuleRecord::parse(line.as_bytes()).map(|m| m.platform());
+        let id = "492E2DD23CC306CA9C494EEF1533A3810";
+
+        assert_eq!(
+            platform(&format!("MODULE Linux x86 {} a", id))?,
+            Platform::LinuxX86
+        );
+        assert_eq!(
+            platform(&format!("MODULE Linux x86_64 {} a", id))?,
+            Platform::LinuxX86_64
+        );
+        assert_eq!(
+            platform(&format!("MODULE Linux arm {} a", id))?,
+            Platform::LinuxArm
+        );
+        assert_eq!(
+            platform(&format!("MODULE Linux arm64 {} a", id))?,
+            Platform::LinuxArm64
+        );
+        assert_eq!(
+            platform(&format!("MODULE mac x86_64 {} a", id))?,
+            Platform::MacOsX86_64
+        );
+        assert_eq!(
+            platform(&format!("MODULE mac arm64e {} a", id))?,
+            Platform::MacOsArm64
+        );
+        assert_eq!(
+            platform(&format!("MODULE windows x86 {} a", id))?,
+            Platform::WindowsX86
+        );
+        assert_eq!(
+            platform(&format!("MODULE windows x86_64 {} a", id))?,
+            Platform::WindowsX86_64
+        );
+        assert_eq!(
+            platform(&format!("MODULE Android arm64 {} a", id))?,
+            Platform::Android
+        );
+
+        let unknown = platform(&format!("MODULE windows arm64 {} a", id))?;
+        assert_eq!(unknown, Platform::Unknown("windows".into(), "arm64".into()));
+        assert!(unknown.is_64bit());
+        assert!(unknown.is_windows());
+
+        let unknown = platform(&format!("MODULE fuchsia foo {} a", id))?;
+        assert_eq!(unknown, Platform::Unknown("fuchsia".into(), "foo".into()));
+        assert!(!unknown.is_64bit());
+        assert!(!unknown.is_windows());
+
+        assert!(Platform::MacOsArm64.is_64bit());
+        assert!(!Platform::LinuxArm.is_64bit());
+        assert!(Platform::WindowsX86.is_windows());
+        assert!(!Platform::LinuxX86_64.is_windows());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_id_age() -> Result<(), BreakpadError> {
+        let parse = |id: &str| {
+            let data = format!("MODULE Linux x86_64 {} crash\n", id);
+            BreakpadObject::parse(data.as_bytes()).map(|object| {
+                (
+                    object.debug_id(),
+                    object.debug_id_age(),
+                    object.breakpad_id(),
+                )
+            })
+        };
+
+        let (id, age, breakpad_id) = parse("6216C672A8D33EC9CF4A1BAB8B29D00E")?;
+        assert_eq!(id.appendix(), 0);
+        assert_eq!(age, None);
+        assert_eq!(breakpad_id, "6216C672A8D33EC9CF4A1BAB8B29D00E0");
+
+        let (id, age, breakpad_id) = parse("6216C672A8D33EC9CF4A1BAB8B29D00E2A")?;
+        assert_eq!(id.appendix(), 0x2a);
+        assert_eq!(age, Some(0x2a));
+        assert_eq!(breakpad_id, "6216C672A8D33EC9CF4A1BAB8B29D00E2A");
+
+        let (lower, age, breakpad_id) = parse("6216c672a8d33ec9cf4a1bab8b29d00e2a")?;
+        assert_eq!(lower, id);
+        assert_eq!(age, Some(0x2a));
+        assert_eq!(breakpad_id, "6216C672A8D33EC9CF4A1BAB8B29D00E2A");
+
+        let (hyphenated, age, breakpad_id) = parse("6216c672-a8d3-3ec9-cf4a-1bab8b29d00e-2a")?;
+        assert_eq!(hyphenated, id);
+        assert_eq!(age, Some(0x2a));
+        assert_eq!(breakpad_id, "6216C672A8D33EC9CF4A1BAB8B29D00E2A");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_has_complete_debug_info() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+PUBLIC 1000 0 foo
+";
+        let object = BreakpadObject::parse(data)?;
+        assert!(!object.has_debug_info());
+        assert!(!object.has_complete_debug_info());
+
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 a.c
+FUNC 1000 10 0 foo
+FUNC 2000 10 0 bar
+";
+        let object = BreakpadObject::parse(data)?;
+        assert!(object.has_debug_info());
+        assert!(!object.has_complete_debug_info());
+
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 a.c
+FUNC 1000 10 0 foo
+FUNC 2000 10 0 bar
+2000 10 1 0
+";
+        let object = BreakpadObject::parse(data)?;
+        assert!(!object.has_complete_debug_info());
+
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 a.c
+FUNC 1000 10 0 foo
+1000 10 1 0
+FUNC 2000 10 0 bar
+";
+        let object = BreakpadObject::parse(data)?;
+        assert!(object.has_complete_debug_info());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_with_ids_line_lookup() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 2 src/main.c
+FILE 7 src/util.c
+FUNC 1000 20 0 main
+1000 10 1 7
+1010 10 2 2
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.debug_session()?;
+
+        let files = session
+            .files_with_ids()
+            .map(|file| file.map(|(id, file)| (id, file.path_str())))
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+        assert_eq!(files.keys().copied().collect::<Vec<_>>(), [2, 7]);
+
+        let func = object.func_records().next().unwrap()?;
+        let function = session.functions().next().unwrap()?;
+        for (record, line) in func.lines().zip(&function.lines) {
+            assert_eq!(files[&record?.file_id], line.file.path_str());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_functions_in_range() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 a.c
+FUNC 3000 10 0 third
+3000 10 3 0
+FUNC 1000 10 0 first
+1000 10 1 0
+FUNC 1800 0 0 empty
+FUNC 2000 10 0 second
+2000 10 2 0
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.debug_session()?;
+
+        let names = |range| {
+            session
+                .functions_in_range(range)
+                .map(|function| function.map(|function| function.name.to_string()))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        assert_eq!(names(0x1008..0x2004)?, ["first", "empty", "second"]);
+        assert_eq!(names(0x1010..0x1800)?, Vec::<String>::new());
+        assert_eq!(names(0x2010..0x3000)?, Vec::<String>::new());
+        assert_eq!(names(0x0..u64::MAX)?, ["first", "empty", "second", "third"]);
+
+        let function = session.functions_in_range(0x3000..0x3001).next().unwrap()?;
+        assert_eq!(function.lines.len(), 1);
+        assert_eq!(function.lines[0].line, 3);
+
+        // Functions starting before the range are found even if later ones end before it.
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 1000 0 outer
+FUNC 1100 10 0 nested
+FUNC 1800 10 0 later
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.debug_session()?;
+        let names = session
+            .functions_in_range(0x1400..0x1500)
+            .map(|function| function.map(|function| function.name.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(names, ["outer"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_records_into_owned() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 a.c 0123456789abcdef0123456789abcdef
+FUNC m 1000 20 4 foo
+1000 10 1 0
+1010 10 2 0
+PUBLIC 2000 8 bar
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let file = object.file_records().next().unwrap()?.into_owned();
+        assert_eq!(file.id, 0);
+        assert_eq!(file.name, "a.c");
+        assert_eq!(
+            file.checksum.as_deref(),
+            Some("0123456789abcdef0123456789abcdef")
+        );
+
+        let func = BreakpadFuncRecordOwned::try_from(object.func_records().next().unwrap()?)?;
+        assert!(func.multiple);
+        assert_eq!(
+            (func.address, func.size, func.parameter_size),
+            (0x1000, 0x20, 4)
+        );
+        assert_eq!(func.name, "foo");
+        assert_eq!(
+            func.lines.iter().map(|line| line.line).collect::<Vec<_>>(),
+            [1, 2]
+        );
+
+        let public = BreakpadPublicRecordOwned::from(object.public_records().next().unwrap()?);
+        assert_eq!((public.address, public.parameter_size), (0x2000, 8));
+        assert_eq!(public.name, "bar");
+
+        // The owned records are independent of the source data.
+        drop(object);
+        assert_eq!(func.lines[1].address, 0x1010);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stack_records_validate_rules() -> Result<(), BreakpadError> {
+        let data = b"MODULE windows x86 5A9832E5287241C1838ED98914E9B7FF1 crash.pdb
+STACK CFI INIT 1000 10 .cfa: $esp 4 + .ra: .cfa -4 + ^
+STACK CFI 1004 .cfa: $esp 8 +
+STACK CFI INIT 2000 10 .cfa: $esp 4 + .ra: .cfa -4 + ^
+STACK CFI 2004 .cfa: $esp +
+STACK WIN 4 3000 10 1 0 0 0 0 0 1 $eip 4 + ^ =
+STACK CFI INIT 4000 10 .cfa $esp 4 +
+";
+        let object = BreakpadObject::parse(data)?;
+
+        // The default iterator does not check rules.
+        assert_eq!(object.stack_records().filter(Result::is_err).count(), 0);
+
+        let results: Vec<_> = object.stack_records().validate_rules().collect();
+        assert_eq!(results.len(), 4);
+        assert!(results[0].is_ok());
+        assert!(results[2].is_ok());
+
+        let error = results[1].as_ref().unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::InvalidCfiRules);
+        let message = error.source().unwrap().to_string();
+        assert!(message.contains("0x2004"), "{}", message);
+        assert!(message.contains(".cfa: $esp +"), "{}", message);
+
+        let error = results[3].as_ref().unwrap_err();
+        assert!(error.source().unwrap().to_string().contains("0x4000"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_to_and_reader() -> Result<(), BreakpadError> {
+        use std::io::Read;
+
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 10 0 foo
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let mut written = Vec::new();
+        object.write_to(&mut written).unwrap();
+        assert_eq!(written, data);
+
+        let mut read = Vec::new();
+        let mut reader = object.as_reader();
+        let mut chunk = [0; 7];
+        let len = reader.read(&mut chunk).unwrap();
+        read.extend_from_slice(&chunk[..len]);
+        reader.read_to_end(&mut read).unwrap();
+        assert_eq!(read, data);
+        assert_eq!(reader.read(&mut chunk).unwrap(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_name_mangling() {
+        let cases = [
+            ("_ZN3foo3barEv", NameMangling::Mangled, Language::Cpp),
+            ("_Z3fooi", NameMangling::Mangled, Language::Cpp),
+            ("_ZTV3Foo", NameMangling::Mangled, Language::Cpp),
+            ("__ZN3foo3barEv", NameMangling::Mangled, Language::Cpp),
+            (
+                "_ZN4core3ptr13drop_in_place17h0123456789abcdefE",
+                NameMangling::Mangled,
+                Language::Rust,
+            ),
+            (
+                "_RNvCs1234_7mycrate3foo",
+                NameMangling::Mangled,
+                Language::Rust,
+            ),
+            (
+                "_RINvCs1234_7mycrate3fooE",
+                NameMangling::Mangled,
+                Language::Rust,
+            ),
+            ("?foo@@YAHXZ", NameMangling::Mangled, Language::Cpp),
+            ("??0Foo@@QAE@XZ", NameMangling::Mangled, Language::Cpp),
+            ("_T0s5print", NameMangling::Mangled, Language::Swift),
+            ("$s4main3fooyyF", NameMangling::Mangled, Language::Swift),
+            ("_$s4main3fooyyF", NameMangling::Mangled, Language::Swift),
+            ("main", NameMangling::Unmangled, Language::Unknown),
+            ("_start", NameMangling::Unmangled, Language::Unknown),
+            (
+                "__libc_start_main",
+                NameMangling::Unmangled,
+                Language::Unknown,
+            ),
+            ("_Zero", NameMangling::Unmangled, Language::Unknown),
+            ("_Run", NameMangling::Unmangled, Language::Unknown),
+            ("_Tool", NameMangling::Unmangled, Language::Unknown),
+            (
+                "_ZN3foo3barEv ()",
+                NameMangling::Unmangled,
+                Language::Unknown,
+            ),
+            ("foo::bar(int)", NameMangling::Unmangled, Language::Unknown),
+            ("<unknown>", NameMangling::Unmangled, Language::Unknown),
+        ];
+
+        for (name, mangling, language) in cases {
+            assert_eq!(
+                detect_name_mangling(name),
+                (mangling, language),
+                "name: {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_mangled_function_names() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 10 0 _ZN3foo3barEv
+FUNC 2000 10 0 foo::bar()
+PUBLIC 3000 0 _RNvCs1234_7mycrate3foo
+";
+        let object = BreakpadObject::parse(data)?;
+        let session = object.debug_session()?;
+        let names: Vec<_> = session
+            .functions()
+            .map(|function| function.map(|f| (f.name.mangling(), f.name.language())))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(
+            names,
+            [
+                (NameMangling::Mangled, Language::Cpp),
+                (NameMangling::Unmangled, Language::Unknown),
+            ]
+        );
+
+        let symbol = object.public_symbols().next().unwrap();
+        let name = symbol.to_name();
+        assert_eq!(name.mangling(), NameMangling::Mangled);
+        assert_eq!(name.language(), Language::Rust);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_func_records_from_offset() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 foo.c
+FUNC 1000 10 0 first
+1000 10 1 0
+FUNC 2000 10 0 second
+2000 10 2 0
+FUNC 3000 10 0 third
+PUBLIC 4000 0 public
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let mut index = Vec::new();
+        let mut records = object.func_records();
+        while let Some(record) = records.next() {
+            index.push((record?.address, records.offset().unwrap()));
+        }
+
+        let slot = index.partition_point(|&(address, _)| address <= 0x2008) - 1;
+        let mut records = object.func_records_from_offset(index[slot].1);
+        let record = records.next().unwrap()?;
+        assert_eq!(record.name, "second");
+        assert_eq!(record.lines().count(), 1);
+        assert_eq!(records.offset(), Some(index[1].1));
+
+        let names: Vec<_> = records
+            .map(|r| r.map(|r| r.name))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(names, ["third"]);
+
+        assert_eq!(object.func_records_from_offset(0).count(), 3);
+        assert_eq!(object.func_records_from_offset(data.len()).count(), 0);
+        assert_eq!(object.func_records_from_offset(usize::MAX).count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_records() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 492E2DD23CC306CA9C494EEF1533A381
+FILE 0 foo.c
+INLINE_ORIGIN 0 inlined
+FUNC 1000 10 0 main
+1000 8 1 0
+1008 8 2 0
+
+PUBLIC 2000 0 public
+FOO bar baz
+1000 8 3 0
+STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+STACK CFI 1004 .cfa: $rsp 16 +
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let unknown: Vec<_> = object.unknown_records().collect();
+        assert_eq!(unknown, [(10, "FOO bar baz"), (11, "1000 8 3 0")]);
+
+        let issues = object.validate();
+        assert_eq!(
+            issues,
+            [
+                BreakpadValidationIssue::UnknownRecord { line_number: 10 },
+                BreakpadValidationIssue::UnknownRecord { line_number: 11 },
+            ]
+        );
+
+        // Unknown records do not show up in any of the record iterators.
+        assert_eq!(object.func_records().count(), 1);
+        assert_eq!(object.func_records().next().unwrap()?.lines().count(), 2);
+        assert_eq!(object.public_records().count(), 1);
+        assert_eq!(object.file_records().count(), 1);
+        assert_eq!(object.stack_records().count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_public_records_from_offset() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 10 0 func
+PUBLIC 2000 0 first
+PUBLIC 3000 0 second
+PUBLIC 4000 0 third
+STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let mut index = Vec::new();
+        let mut records = object.public_records();
+        while let Some(record) = records.next() {
+            index.push((record?.address, records.offset().unwrap()));
+        }
+
+        let slot = index.partition_point(|&(address, _)| address <= 0x3008) - 1;
+        let mut records = object.public_records_from_offset(index[slot].1);
+        assert_eq!(records.next().unwrap()?.name, "second");
+        assert_eq!(records.offset(), Some(index[1].1));
+
+        let names: Vec<_> = records
+            .map(|r| r.map(|r| r.name))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(names, ["third"]);
+
+        assert_eq!(object.public_records_from_offset(0).count(), 3);
+        assert_eq!(object.public_records_from_offset(data.len()).count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_with_strategy() -> Result<(), BreakpadError> {
+        let first = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 492E2DD23CC306CA9C494EEF1533A381
+FILE 0 foo.c
+FUNC 1000 10 0 shared
+1000 8 1 0
+FUNC 2000 10 0 first_only
+2000 10 2 0
+STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+";
+        let second = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 492E2DD23CC306CA9C494EEF1533A381
+FILE 0 bar.c
+FILE 1 foo.c
+FUNC 1000 10 0 shared
+1008 8 3 0
+FUNC 3000 10 0 second_only
+3000 10 4 1
+PUBLIC 4000 0 public
+STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+";
+        let first = BreakpadObject::parse(first)?;
+        let second = BreakpadObject::parse(second)?;
+
+        let merge = |strategy| -> Result<String, BreakpadError> {
+            let merged = first.merge_with_strategy(&second, strategy)?;
+            Ok(String::from_utf8(merged).unwrap())
+        };
+
+        let header = "MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 492E2DD23CC306CA9C494EEF1533A381
+FILE 0 foo.c
+FILE 1 bar.c
+";
+        let tail = "FUNC 2000 10 0 first_only
+2000 10 2 0
+FUNC 3000 10 0 second_only
+3000 10 4 0
+PUBLIC 4000 0 public
+STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+";
+
+        let keep_first = "FUNC 1000 10 0 shared\n1000 8 1 0\n";
+        assert_eq!(
+            merge(BreakpadMergeStrategy::KeepFirst)?,
+            format!("{}{}{}", header, keep_first, tail)
+        );
+
+        let keep_second = "FUNC 1000 10 0 shared\n1008 8 3 1\n";
+        assert_eq!(
+            merge(BreakpadMergeStrategy::KeepSecond)?,
+            format!("{}{}{}", header, keep_second, tail)
+        );
+
+        let merge_lines = "FUNC 1000 10 0 shared\n1000 8 1 0\n1008 8 3 1\n";
+        assert_eq!(
+            merge(BreakpadMergeStrategy::MergeLines)?,
+            format!("{}{}{}", header, merge_lines, tail)
+        );
+
+        let error = merge(BreakpadMergeStrategy::Error).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            BreakpadErrorKind::MergeConflict { address: 0x1000 }
+        );
+
+        // Merging identical objects never conflicts.
+        let merged = first.merge_with_strategy(&first, BreakpadMergeStrategy::Error)?;
+        assert_eq!(merged, first.normalize()?);
+
+        let other =
+            BreakpadObject::parse(b"MODULE Linux x86_64 0000000000000000000000000000000000 crash")?;
+        let error = first
+            .merge_with_strategy(&other, BreakpadMergeStrategy::KeepFirst)
+            .unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::ModuleMismatch);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_with_strategy_inline() -> Result<(), BreakpadError> {
+        let lines = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1000 20 0 main
+1000 10 1 0
+1010 10 2 0
+STACK CFI INIT 1000 20 .cfa: $rsp 8 +
+PUBLIC 3000 0 late_public
+FUNC 2000 10 0 late_func
+2000 10 3 0
+";
+        let inlines = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 inlined.h
+FILE 1 main.c
+INLINE_ORIGIN 0 other
+INLINE_ORIGIN 1 inlined
+FUNC 1000 20 0 main
+INLINE 0 2 1 1 1004 8
+1004 8 5 0
+";
+        let lines = BreakpadObject::parse(lines)?;
+        let inlines = BreakpadObject::parse(inlines)?;
+
+        let merged = lines.merge_with_strategy(&lines, BreakpadMergeStrategy::MergeLines)?;
+        assert_eq!(
+            str::from_utf8(&merged)?,
+            "MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1000 20 0 main
+1000 10 1 0
+1010 10 2 0
+FUNC 2000 10 0 late_func
+2000 10 3 0
+PUBLIC 3000 0 late_public
+STACK CFI INIT 1000 20 .cfa: $rsp 8 +
+"
+        );
+
+        let merged = lines.merge_with_strategy(&inlines, BreakpadMergeStrategy::MergeLines)?;
+        assert_eq!(
+            str::from_utf8(&merged)?,
+            "MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FILE 1 inlined.h
+INLINE_ORIGIN 0 other
+INLINE_ORIGIN 1 inlined
+FUNC 1000 20 0 main
+1000 10 1 0
+1004 8 5 1
+INLINE 0 2 0 1 1004 8
+1010 10 2 0
+FUNC 2000 10 0 late_func
+2000 10 3 0
+PUBLIC 3000 0 late_public
+STACK CFI INIT 1000 20 .cfa: $rsp 8 +
+"
+        );
+
+        let merged = inlines.merge_with_strategy(&inlines, BreakpadMergeStrategy::MergeLines)?;
+        let merged = BreakpadObject::parse(&merged)?;
+        let func = merged.func_records().next().unwrap()?;
+        assert_eq!(func.lines().count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_writer_order() -> Result<(), BreakpadError> {
+        let module = BreakpadModuleRecord::parse(
+            b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash",
+        )?;
+        let file = BreakpadFileRecord {
+            id: 0,
+            name: "foo.c",
+            checksum: None,
+        };
+        let func = BreakpadFuncRecord::parse(b"FUNC 1000 10 0 main", Lines::default())?;
+        let public = BreakpadPublicRecord::parse(b"PUBLIC 2000 0 public")?;
+        let line = BreakpadLineRecord::new(0x1000, 0x10, 1, 1, None)?;
+        let out_of_order =
+            |record, previous| BreakpadErrorKind::RecordOutOfOrder { record, previous };
+
+        let mut writer = BreakpadStreamWriter::new(Vec::new()).strict_file_ids();
+        let error = writer.write_file(&file).unwrap_err();
+        assert_eq!(error.kind(), out_of_order("FILE", None));
+
+        writer.write_module(&module)?;
+        let error = writer.write_module(&module).unwrap_err();
+        assert_eq!(error.kind(), out_of_order("MODULE", Some("MODULE")));
+        let error = writer.write_line(&line).unwrap_err();
+        assert_eq!(error.kind(), out_of_order("line", Some("MODULE")));
+
+        writer.write_file(&file)?;
+        writer.write_func(&func)?;
+        let error = writer.write_line(&line).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            BreakpadErrorKind::UnknownFileId {
+                function_address: 0x1000,
+                line_address: 0x1000,
+                file_id: 1,
+            }
+        );
+
+        writer.write_public(&public)?;
+        let error = writer.write_func(&func).unwrap_err();
+        assert_eq!(error.kind(), out_of_order("FUNC", Some("PUBLIC")));
+
+        let data = writer.into_inner();
+        assert_eq!(
+            str::from_utf8(&data)?,
+            "MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 foo.c
+FUNC 1000 10 0 main
+PUBLIC 2000 0 public
+"
+        );
 
-        Ok((input, BreakpadStackCfiDeltaRecord { address, rules }))
+        Ok(())
     }
 
-    /// Parse a [`BreakpadStackCfiDeltaRecord`].
-    ///
-    /// A STACK CFI Delta record has the form `STACK CFI <address> <rules>`.
-    /// This will fail if there is any input left over after the record.
-    pub fn stack_cfi_delta_record_final(
-        input: &str,
-    ) -> Result<BreakpadStackCfiDeltaRecord, ErrorTree<ErrorLine>> {
-        nom_supreme::final_parser::final_parser(stack_cfi_delta_record)(input)
+    #[test]
+    fn test_lenient_records() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 foo.c
+FILE x bar.c
+FUNC 1000 10 0 first
+1000 10 1 0
+FUNC zzzz 10 0 broken
+FUNC 2000 10 0 second
+2000 8 2 0
+2008 bad
+PUBLIC 3000 0 public
+PUBLIC 0 invalid
+STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+STACK CFI broken
+STACK CFI 1004 .cfa: $rsp 16 +
+STACK WIN 4 2170
+";
+        let object = BreakpadObject::parse(data)?;
+
+        let names: Vec<_> = object.func_records().lenient().map(|r| r.name).collect();
+        assert_eq!(names, ["first", "second"]);
+        assert_eq!(object.func_records().filter(Result::is_err).count(), 1);
+
+        let second = object.func_records().lenient().nth(1).unwrap();
+        assert_eq!(second.lines().lenient().count(), 1);
+        assert_eq!(object.file_records().lenient().count(), 1);
+        assert_eq!(object.public_records().lenient().count(), 1);
+        assert_eq!(object.stack_records().lenient().count(), 1);
+        assert_eq!(object.win_records().lenient().count(), 0);
+
+        let cfi = object.cfi_records().lenient().next().unwrap();
+        let deltas: Vec<_> = cfi.deltas().lenient().map(|d| d.address).collect();
+        assert_eq!(deltas, [0x1004]);
+
+        Ok(())
     }
 
-    /// Parse a [`BreakpadStackCfiRecord`].
-    ///
-    /// A STACK CFI INIT record has the form `STACK CFI INIT <address> <size> <init_rules>`.
-    fn stack_cfi_record(input: &str) -> ParseResult<BreakpadStackCfiRecord> {
-        let (input, _) = tag("STACK CFI INIT")
-            .terminated(multispace1)
-            .context("stack cfi init  prefix")
-            .parse(input)?;
+    #[test]
+    fn test_has_inline_info() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+INLINE_ORIGIN 0 inlined
+FUNC 1000 10 0 main
+";
+        assert!(BreakpadObject::parse(data)?.has_inline_info());
 
-        let (input, (start, size, init_rules)) = tuple((
-            num_hex!(u64).terminated(multispace1).context("start"),
-            num_hex!(u64).terminated(multispace1).context("size"),
-            rest.context("rules"),
-        ))
-        .cut()
-        .context("stack cfi record body")
-        .parse(input)?;
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 10 0 INLINE_ORIGIN
+";
+        assert!(!BreakpadObject::parse(data)?.has_inline_info());
 
-        Ok((
-            input,
-            BreakpadStackCfiRecord {
-                start,
-                size,
-                init_rules,
-                deltas: Lines::default(),
-            },
-        ))
+        Ok(())
     }
 
-    /// Parse a [`BreakpadStackCfiRecord`].
-    ///
-    /// A STACK CFI INIT record has the form `STACK CFI INIT <address> <size> <init_rules>`.
-    /// This will fail if there is any input left over after the record.
-    pub fn stack_cfi_record_final(
-        input: &str,
-    ) -> Result<BreakpadStackCfiRecord, ErrorTree<ErrorLine>> {
-        nom_supreme::final_parser::final_parser(stack_cfi_record)(input)
+    #[test]
+    fn test_all_symbols() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 20 0 main
+1000 20 1 0
+FUNC m 1040 0 4 unsized
+FUNC 1080 10 0 helper
+PUBLIC 1000 0 main_public
+PUBLIC 1010 0 inside_main
+PUBLIC 1020 0 after_main
+PUBLIC 1040 0 unsized_public
+PUBLIC m 1060 8 between
+PUBLIC 1090 0 after_helper
+";
+        let object = BreakpadObject::parse(data)?;
+        let symbols = object.all_symbols()?;
+
+        let summary: Vec<_> = symbols
+            .iter()
+            .map(|s| (s.address, s.name, s.origin))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (0x1000, "main", BreakpadSymbolOrigin::Func),
+                (0x1020, "after_main", BreakpadSymbolOrigin::Public),
+                (0x1040, "unsized", BreakpadSymbolOrigin::Func),
+                (0x1060, "between", BreakpadSymbolOrigin::Public),
+                (0x1080, "helper", BreakpadSymbolOrigin::Func),
+                (0x1090, "after_helper", BreakpadSymbolOrigin::Public),
+            ]
+        );
+
+        assert_eq!(
+            symbols[2],
+            BreakpadSymbol {
+                address: 0x1040,
+                size: None,
+                name: "unsized",
+                origin: BreakpadSymbolOrigin::Func,
+                parameter_size: 4,
+                multiple: true,
+            }
+        );
+        assert_eq!(symbols[0].size, Some(0x20));
+        assert!(symbols[3].multiple);
+        assert_eq!(symbols[3].parameter_size, 8);
+
+        // The public symbol iterator is not affected.
+        assert_eq!(object.symbols().count(), 6);
+
+        Ok(())
     }
 
-    /// Parse a [`BreakpadStackWinRecord`].
-    ///
-    /// A STACK WIN record has the form
-    /// `STACK WIN <ty> <code_start> <code_size> <prolog_size> <epilog_size> <params_size> <saved_regs_size> <locals_size> <max_stack_size> <has_program_string> (<program_string> | <uses_base_pointer>)`.
-    fn stack_win_record(input: &str) -> ParseResult<BreakpadStackWinRecord> {
-        let (input, _) = tag("STACK WIN")
-            .terminated(multispace1)
-            .context("stack win prefix")
-            .parse(input)?;
+    #[test]
+    fn test_record_range_overflow() {
+        let error = BreakpadFuncRecord::parse(b"FUNC ffffffffffffffff 1 0 f", Lines::default())
+            .unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::FuncRecordOverflow);
 
-        let (
-            input,
-            (
-                ty,
-                code_start,
-                code_size,
-                prolog_size,
-                epilog_size,
-                params_size,
-                saved_regs_size,
-                locals_size,
-                max_stack_size,
-                has_program_string,
-            ),
-        ) = tuple((
-            stack_win_record_type
-                .terminated(multispace1)
-                .context("record type"),
-            num_hex!(u32).terminated(multispace1).context("code start"),
-            num_hex!(u32).terminated(multispace1).context("code size"),
-            num_hex!(u16).terminated(multispace1).context("prolog size"),
-            num_hex!(u16).terminated(multispace1).context("epilog size"),
-            num_hex!(u32).terminated(multispace1).context("params size"),
-            num_hex!(u16)
-                .terminated(multispace1)
-                .context("saved regs size"),
-            num_hex!(u32).terminated(multispace1).context("locals size"),
-            num_hex!(u32)
-                .terminated(multispace1)
-                .context("max stack size"),
-            non_whitespace
-                .map(|s| s != "0")
-                .terminated(multispace1)
-                .context("has_program_string"),
-        ))
-        .cut()
-        .context("stack win record body")
-        .parse(input)?;
+        let error = BreakpadStackCfiRecord::parse(b"STACK CFI INIT ffffffffffffffff 2 .cfa: sp")
+            .unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::FuncRecordOverflow);
 
-        let (input, program_string) =
-            cond(has_program_string, rest.context("program string"))(input)?;
-        let (input, uses_base_pointer) =
-            cond(!has_program_string, non_whitespace.map(|s| s != "0"))
-                .map(|o| o.unwrap_or(false))
-                .parse(input)?;
+        let error =
+            BreakpadStackRecord::parse(b"STACK CFI INIT fffffffffffffff0 10 .cfa: sp").unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::FuncRecordOverflow);
 
-        Ok((
-            input,
-            BreakpadStackWinRecord {
-                ty,
-                code_start,
-                code_size,
-                prolog_size,
-                epilog_size,
-                params_size,
-                saved_regs_size,
-                locals_size,
-                max_stack_size,
-                uses_base_pointer,
-                program_string,
-            },
-        ))
+        assert!(
+            BreakpadFuncRecord::parse(b"FUNC fffffffffffffffe 1 0 f", Lines::default()).is_ok()
+        );
     }
 
-    /// Parse a [`BreakpadStackWinRecord`].
-    ///
-    /// A STACK WIN record has the form
-    /// `STACK WIN <ty> <code_start> <code_size> <prolog_size> <epilog_size> <params_size> <saved_regs_size> <locals_size> <max_stack_size> <has_program_string> (<program_string> | <uses_base_pointer>)`.
-    /// This will fail if there is any input left over after the record.
-    pub fn stack_win_record_final(
-        input: &str,
-    ) -> Result<BreakpadStackWinRecord, ErrorTree<ErrorLine>> {
-        nom_supreme::final_parser::final_parser(stack_win_record)(input)
-    }
+    #[test]
+    fn test_func_summary() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 30 0 main
+1000 8 1 2
+1008 0 2 5
+1008 8 3 0
+1010 20 4 2
+FUNC 1030 10 0 empty
+";
+        let object = BreakpadObject::parse(data)?;
+        let mut funcs = object.func_records();
+
+        let summary = funcs.next().unwrap()?.summary()?;
+        assert_eq!(
+            summary,
+            BreakpadFuncSummary {
+                address: 0x1000,
+                size: 0x30,
+                name: "main".to_owned(),
+                line_count: 3,
+                total_line_bytes: 0x30,
+                file_ids: vec![0, 2],
+            }
+        );
 
-    /// Parse a [`BreakpadStackRecord`], containing either a [`BreakpadStackCfiRecord`] or a
-    /// [`BreakpadStackWinRecord`].
-    ///
-    /// This will fail if there is any input left over after the record.
-    pub fn stack_record_final(input: &str) -> Result<BreakpadStackRecord, ParseBreakpadError> {
-        nom_supreme::final_parser::final_parser(alt((
-            stack_cfi_record.map(BreakpadStackRecord::Cfi),
-            stack_win_record.map(BreakpadStackRecord::Win),
-        )))(input)
+        let summary = funcs.next().unwrap()?.summary()?;
+        assert_eq!(summary.line_count, 0);
+        assert!(summary.file_ids.is_empty());
+
+        Ok(())
     }
-}
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
-    fn test_parse_module_record() -> Result<(), BreakpadError> {
-        let string = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash";
-        let record = BreakpadModuleRecord::parse(&*string)?;
-
-        insta::assert_debug_snapshot!(record, @r###"
-       ⋮BreakpadModuleRecord {
-       ⋮    os: "Linux",
-       ⋮    arch: "x86_64",
-       ⋮    id: "492E2DD23CC306CA9C494EEF1533A3810",
-       ⋮    name: "crash",
-       ⋮}
-        "###);
+    fn test_normalize() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 5F2D4E3A crash.so
+FILE 1 late.c
+FILE  0   main.c
+STACK WIN 4 2170 14 1 0 0 0 0 0 1 $eip 4 + ^ =
+STACK CFI INIT 1000 10 .cfa: $rsp 8 +
+STACK CFI 1004 .cfa: $rsp 16 +
+FUNC 1100 10 0 late_func
+1100 10 1 1
+FUNC m 1000 10 0 main
+1008 8 2 0
+1000 8 1 0
+1000 8 1 0
+1010 0 3 0
+PUBLIC 3000 0 late_public
+INFO CODE_ID 5F2D4E3A crash.so
+PUBLIC 2000 0x0 early_public
+";
+        let object = BreakpadObject::parse(data)?;
+        let normalized = object.normalize()?;
+
+        assert_eq!(
+            str::from_utf8(&normalized)?,
+            "MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 5F2D4E3A crash.so
+FILE 0 main.c
+FILE 1 late.c
+FUNC m 1000 10 0 main
+1000 8 1 0
+1008 8 2 0
+FUNC 1100 10 0 late_func
+1100 10 1 1
+PUBLIC 2000 0 early_public
+PUBLIC 3000 0 late_public
+STACK CFI INIT 1000 10 .cfa: $rsp 8 +
+STACK CFI 1004 .cfa: $rsp 16 +
+STACK WIN 4 2170 14 1 0 0 0 0 0 1 $eip 4 + ^ =
+"
+        );
+
+        let renormalized = BreakpadObject::parse(&normalized)?.normalize()?;
+        assert_eq!(renormalized, normalized);
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_module_record_short_id() -> Result<(), BreakpadError> {
-        // NB: This id is one character short, missing the age. DebugId can handle this, however.
-        let string = b"MODULE Linux x86_64 6216C672A8D33EC9CF4A1BAB8B29D00E libdispatch.so";
-        let record = BreakpadModuleRecord::parse(string)?;
+    fn test_normalize_roundtrip() -> Result<(), BreakpadError> {
+        let object = BreakpadObject::parse(REORDERED)?;
+        let normalized = object.normalize()?;
+        let normalized = BreakpadObject::parse(&normalized)?;
 
-        insta::assert_debug_snapshot!(record, @r###"
-       ⋮BreakpadModuleRecord {
-       ⋮    os: "Linux",
-       ⋮    arch: "x86_64",
-       ⋮    id: "6216C672A8D33EC9CF4A1BAB8B29D00E",
-       ⋮    name: "libdispatch.so",
-       ⋮}
-        "###);
+        fn collect<T>(records: impl Iterator<Item = Result<T, BreakpadError>>) -> Vec<T> {
+            records.collect::<Result<_, _>>().unwrap()
+        }
+
+        // All records in `REORDERED` are already sorted within their kind.
+        assert_eq!(
+            collect(normalized.info_records_full()),
+            collect(object.info_records_full())
+        );
+        assert_eq!(
+            collect(normalized.file_records_full()),
+            collect(object.file_records_full())
+        );
+        assert_eq!(
+            collect(normalized.public_records_full()),
+            collect(object.public_records_full())
+        );
+        assert_eq!(
+            collect(normalized.stack_records()),
+            collect(object.stack_records())
+        );
+
+        let funcs = collect(object.func_records_full());
+        let normalized_funcs = collect(normalized.func_records_full());
+        assert_eq!(normalized_funcs, funcs);
+        for (normalized_func, func) in normalized_funcs.iter().zip(&funcs) {
+            assert_eq!(collect(normalized_func.lines()), collect(func.lines()));
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_file_record() -> Result<(), BreakpadError> {
-        let string = b"FILE 37 /usr/include/libkern/i386/_OSByteOrder.h";
-        let record = BreakpadFileRecord::parse(string)?;
-
-        insta::assert_debug_snapshot!(record, @r###"
-       ⋮BreakpadFileRecord {
-       ⋮    id: 37,
-       ⋮    name: "/usr/include/libkern/i386/_OSByteOrder.h",
-       ⋮}
-        "###);
+    fn test_normalize_lossy() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE x main.c
+FUNC 1000 10 0 main
+1000 10 1 0
+FUNC zzz 10 0 broken
+1010 10 1 0
+1020 10 1 0
+PUBLIC 2000 0 _start
+";
+        let object = BreakpadObject::parse(data)?;
+        assert!(object.normalize().is_err());
+
+        let (normalized, dropped) = object.normalize_lossy();
+        assert_eq!(dropped, 4);
+        assert_eq!(
+            str::from_utf8(&normalized)?,
+            "MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FUNC 1000 10 0 main
+1000 10 1 0
+PUBLIC 2000 0 _start
+"
+        );
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_file_record_space() -> Result<(), BreakpadError> {
-        let string = b"FILE 38 /usr/local/src/filename with spaces.c";
-        let record = BreakpadFileRecord::parse(string)?;
+    fn test_infer_function_sizes() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+FILE 0 main.c
+FUNC 1000 0 0 zero_sized
+1000 8 1 0
+FUNC 1020 10 0 sized
+1020 10 2 0
+FUNC 1100 0 0 capped
+PUBLIC 1200 0 public
+FUNC 1300 0 0 last
+";
+        let object = BreakpadObject::parse(data)?;
+        let mut session = object.debug_session()?;
+
+        let sizes = |session: &BreakpadDebugSession| {
+            session
+                .functions()
+                .map(|f| f.map(|f| (f.name.as_str().to_owned(), f.size)))
+                .collect::<Result<Vec<_>, _>>()
+        };
 
-        insta::assert_debug_snapshot!(record, @r###"
-       ⋮BreakpadFileRecord {
-       ⋮    id: 38,
-       ⋮    name: "/usr/local/src/filename with spaces.c",
-       ⋮}
-        "###);
+        assert_eq!(
+            sizes(&session)?,
+            [
+                ("zero_sized".to_owned(), 0),
+                ("sized".to_owned(), 0x10),
+                ("capped".to_owned(), 0),
+                ("last".to_owned(), 0),
+            ]
+        );
+
+        session.infer_function_sizes(0x80)?;
+        assert_eq!(
+            sizes(&session)?,
+            [
+                ("zero_sized".to_owned(), 0x20),
+                ("sized".to_owned(), 0x10),
+                ("capped".to_owned(), 0x80),
+                ("last".to_owned(), 0),
+            ]
+        );
+
+        assert_eq!(session.inferred_size(0x1000), Some(0x20));
+        assert_eq!(session.inferred_size(0x1020), None);
+        assert_eq!(session.inferred_size(0x1100), Some(0x80));
+        assert_eq!(session.inferred_size(0x1300), None);
+
+        // The raw records still report the size from the file.
+        let record = object.func_records().next().unwrap()?;
+        assert_eq!(record.size, 0);
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_func_record() -> Result<(), BreakpadError> {
-        // Lines will be tested separately
-        let string = b"FUNC 1730 1a 0 <name omitted>";
-        let record = BreakpadFuncRecord::parse(string, Lines::default())?;
+    fn test_file_id_remapper_reuse() -> Result<(), BreakpadError> {
+        let mut remapper = BreakpadFileIdRemapper::new(BreakpadFileMap::new());
+        remapper.add_files(vec![
+            BreakpadFileRecord {
+                id: 1,
+                name: "a.c",
+                checksum: None,
+            },
+            BreakpadFileRecord {
+                id: 2,
+                name: "b.c",
+                checksum: None,
+            },
+        ])?;
 
-        insta::assert_debug_snapshot!(record, @r###"
-       ⋮BreakpadFuncRecord {
-       ⋮    multiple: false,
-       ⋮    address: 5936,
-       ⋮    size: 26,
-       ⋮    parameter_size: 0,
-       ⋮    name: "<name omitted>",
-       ⋮}
-        "###);
+        let mapping = remapper.add_files(vec![BreakpadFileRecord {
+            id: 2,
+            name: "b.c",
+            checksum: None,
+        }])?;
+        assert_eq!(mapping.get(2), Some(2));
+        assert_eq!(remapper.file_map().len(), 2);
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_func_record_multiple() -> Result<(), BreakpadError> {
-        let string = b"FUNC m 1730 1a 0 <name omitted>";
-        let record = BreakpadFuncRecord::parse(string, Lines::default())?;
+    fn test_file_id_remapper_reassign() -> Result<(), BreakpadError> {
+        let mut files = BreakpadFileMap::new();
+        files.insert(1, "a.c");
+        files.insert(2, "b.c");
+
+        let mut remapper = BreakpadFileIdRemapper::new(files);
+        let mapping = remapper.add_files(vec![
+            BreakpadFileRecord {
+                id: 1,
+                name: "c.c",
+                checksum: None,
+            },
+            BreakpadFileRecord {
+                id: 2,
+                name: "a.c",
+                checksum: None,
+            },
+            BreakpadFileRecord {
+                id: 7,
+                name: "d.c",
+                checksum: None,
+            },
+        ])?;
 
-        insta::assert_debug_snapshot!(record, @r###"
-       ⋮BreakpadFuncRecord {
-       ⋮    multiple: true,
-       ⋮    address: 5936,
-       ⋮    size: 26,
-       ⋮    parameter_size: 0,
-       ⋮    name: "<name omitted>",
-       ⋮}
-        "###);
+        assert_eq!(mapping.get(1), Some(3));
+        assert_eq!(mapping.get(2), Some(1));
+        assert_eq!(mapping.get(7), Some(7));
+        assert_eq!(mapping.get(4), None);
+
+        let file_map = remapper.into_file_map();
+        assert_eq!(
+            file_map.into_iter().collect::<Vec<_>>(),
+            [(1, "a.c"), (2, "b.c"), (3, "c.c"), (7, "d.c")]
+        );
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_func_record_no_name() -> Result<(), BreakpadError> {
-        let string = b"FUNC 0 f 0";
-        let record = BreakpadFuncRecord::parse(string, Lines::default())?;
-
-        insta::assert_debug_snapshot!(record, @r###"
-       ⋮BreakpadFuncRecord {
-       ⋮    multiple: false,
-       ⋮    address: 0,
-       ⋮    size: 15,
-       ⋮    parameter_size: 0,
-       ⋮    name: "<unknown>",
-       ⋮}
-        "###);
+    fn test_file_id_remapper_overflow() {
+        let mut files = BreakpadFileMap::new();
+        files.insert(u64::MAX, "a.c");
+
+        let mut remapper = BreakpadFileIdRemapper::new(files);
+        let record = BreakpadFileRecord {
+            id: u64::MAX,
+            name: "b.c",
+            checksum: None,
+        };
+        let error = remapper.add_file(&record).unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::FileIdOverflow);
 
-        Ok(())
+        let record = BreakpadFileRecord {
+            id: 0,
+            name: "b.c",
+            checksum: None,
+        };
+        assert_eq!(remapper.add_file(&record).unwrap(), 0);
     }
 
     #[test]
-    fn test_parse_line_record() -> Result<(), BreakpadError> {
-        let string = b"1730 6 93 20";
-        let record = BreakpadLineRecord::parse(string)?;
+    fn test_file_id_remapper_lines() -> Result<(), BreakpadError> {
+        let first = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 first
+FILE 0 shared.c
+FILE 1 first.c
+FUNC 1000 10 0 first
+1000 10 1 1
+";
+        let second = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 second
+FILE 0 second.c
+FILE 1 shared.c
+FUNC 2000 10 0 second
+2000 8 1 0
+2008 8 2 1
+";
 
-        insta::assert_debug_snapshot!(record, @r###"
-       ⋮BreakpadLineRecord {
-       ⋮    address: 5936,
-       ⋮    size: 6,
-       ⋮    line: 93,
-       ⋮    file_id: 20,
-       ⋮}
-        "###);
+        let first = BreakpadObject::parse(first)?;
+        let second = BreakpadObject::parse(second)?;
+
+        let mut remapper = BreakpadFileIdRemapper::new(first.file_map());
+        let records = second.file_records().collect::<Result<Vec<_>, _>>()?;
+        let mapping = remapper.add_files(records)?;
+        let file_map = remapper.file_map();
 
+        let func = second.func_records().next().unwrap()?;
+        let paths = func
+            .lines()
+            .map(|line| {
+                let line = mapping.remap_line(&line?).unwrap();
+                Ok(line.filename(file_map).unwrap())
+            })
+            .collect::<Result<Vec<_>, BreakpadError>>()?;
+
+        assert_eq!(paths, ["second.c", "shared.c"]);
         Ok(())
     }
 
     #[test]
-    fn test_parse_line_record_negative_line() -> Result<(), BreakpadError> {
-        let string = b"e0fd10 5 -376 2225";
-        let record = BreakpadLineRecord::parse(string)?;
+    #[cfg(feature = "json")]
+    fn test_convert_to_json() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+INFO CODE_ID 492E2DD23CC306CA9C494EEF1533A381
+FILE 0 foo.c
+FUNC 1000 10 0 main
+1000 10 1 0
+PUBLIC 2000 0 public
+STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+STACK CFI 1004 .cfa: $rsp 16 +
+STACK WIN 4 2170 14 1 0 0 0 0 0 1 $eip 4 + ^ = $esp $ebp 8 + = $ebp $ebp ^ =
+";
+        let object = BreakpadObject::parse(data)?;
+        let mut json = Vec::new();
+        object.convert_to_json(&mut json).unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        insta::assert_snapshot!(serde_json::to_string_pretty(&value).unwrap(), @r###"
+        {
+          "files": [
+            {
+              "id": 0,
+              "name": "foo.c"
+            }
+          ],
+          "format_version": 1,
+          "functions": [
+            {
+              "address": 4096,
+              "lines": [
+                {
+                  "address": 4096,
+                  "file_id": 0,
+                  "line": 1,
+                  "size": 16
+                }
+              ],
+              "multiple": false,
+              "name": "main",
+              "parameter_size": 0,
+              "size": 16
+            }
+          ],
+          "info": [
+            {
+              "CodeId": {
+                "code_file": "<unknown>",
+                "code_id": "492E2DD23CC306CA9C494EEF1533A381"
+              }
+            }
+          ],
+          "module": {
+            "arch": "x86_64",
+            "id": "492E2DD23CC306CA9C494EEF1533A3810",
+            "name": "crash",
+            "os": "Linux"
+          },
+          "publics": [
+            {
+              "address": 8192,
+              "multiple": false,
+              "name": "public",
+              "parameter_size": 0
+            }
+          ],
+          "stack_cfi": [
+            {
+              "deltas": [
+                {
+                  "address": 4100,
+                  "rules": ".cfa: $rsp 16 +"
+                }
+              ],
+              "init_rules": ".cfa: $rsp 8 + .ra: .cfa -8 + ^",
+              "size": 16,
+              "start": 4096
+            }
+          ],
+          "stack_win": [
+            {
+              "code_size": 20,
+              "code_start": 8560,
+              "epilog_size": 0,
+              "locals_size": 0,
+              "max_stack_size": 0,
+              "params_size": 0,
+              "program_string": "$eip 4 + ^ = $esp $ebp 8 + = $ebp $ebp ^ =",
+              "prolog_size": 1,
+              "saved_regs_size": 0,
+              "ty": "FrameData",
+              "uses_base_pointer": false
+            }
+          ]
+        }
+        "###);
 
-        insta::assert_debug_snapshot!(record, @r###"
-        BreakpadLineRecord {
-            address: 14744848,
-            size: 5,
-            line: 0,
-            file_id: 2225,
+        // Records are exported regardless of their position in the file.
+        let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+STACK CFI INIT 1000 10 .cfa: $rsp 8 +
+FUNC 1000 10 0 main
+1000 10 1 0
+FILE 0 foo.c
+PUBLIC 2000 0 public
+INFO RELEASE 1.2.3
+";
+        let object = BreakpadObject::parse(data)?;
+        let mut json = Vec::new();
+        object.convert_to_json(&mut json).unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        insta::assert_snapshot!(serde_json::to_string_pretty(&value).unwrap(), @r###"
+        {
+          "files": [
+            {
+              "id": 0,
+              "name": "foo.c"
+            }
+          ],
+          "format_version": 1,
+          "functions": [
+            {
+              "address": 4096,
+              "lines": [
+                {
+                  "address": 4096,
+                  "file_id": 0,
+                  "line": 1,
+                  "size": 16
+                }
+              ],
+              "multiple": false,
+              "name": "main",
+              "parameter_size": 0,
+              "size": 16
+            }
+          ],
+          "info": [
+            {
+              "Other": {
+                "info": "1.2.3",
+                "scope": "RELEASE"
+              }
+            }
+          ],
+          "module": {
+            "arch": "x86_64",
+            "id": "492E2DD23CC306CA9C494EEF1533A3810",
+            "name": "crash",
+            "os": "Linux"
+          },
+          "publics": [
+            {
+              "address": 8192,
+              "multiple": false,
+              "name": "public",
+              "parameter_size": 0
+            }
+          ],
+          "stack_cfi": [
+            {
+              "deltas": [],
+              "init_rules": ".cfa: $rsp 8 +",
+              "size": 16,
+              "start": 4096
+            }
+          ],
+          "stack_win": []
         }
         "###);
 
@@ -1985,18 +10906,19 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_line_record_whitespace() -> Result<(), BreakpadError> {
-        let string = b"    1000 1c 2972 2
-";
-        let record = BreakpadLineRecord::parse(string)?;
-
-        insta::assert_debug_snapshot!(
-            record, @r###"
-        BreakpadLineRecord {
-            address: 4096,
-            size: 28,
-            line: 2972,
-            file_id: 2,
+    #[cfg(feature = "serde")]
+    fn test_serialize_module_record() -> Result<(), BreakpadError> {
+        let record = BreakpadModuleRecord::parse(
+            b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash",
+        )?;
+        let json = serde_json::to_string_pretty(&record).unwrap();
+
+        insta::assert_snapshot!(json, @r###"
+        {
+          "os": "Linux",
+          "arch": "x86_64",
+          "id": "492E2DD23CC306CA9C494EEF1533A3810",
+          "name": "crash"
         }
         "###);
 
@@ -2004,128 +10926,131 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_public_record() -> Result<(), BreakpadError> {
-        let string = b"PUBLIC 5180 0 __clang_call_terminate";
-        let record = BreakpadPublicRecord::parse(string)?;
-
-        insta::assert_debug_snapshot!(record, @r###"
-       ⋮BreakpadPublicRecord {
-       ⋮    multiple: false,
-       ⋮    address: 20864,
-       ⋮    parameter_size: 0,
-       ⋮    name: "__clang_call_terminate",
-       ⋮}
+    #[cfg(feature = "serde")]
+    fn test_serialize_info_record() -> Result<(), BreakpadError> {
+        let record = BreakpadInfoRecord::parse(b"INFO CODE_ID 5F2D4E3A crash.so")?;
+        let json = serde_json::to_string_pretty(&record).unwrap();
+
+        insta::assert_snapshot!(json, @r###"
+        {
+          "CodeId": {
+            "code_id": "5F2D4E3A",
+            "code_file": "crash.so"
+          }
+        }
         "###);
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_public_record_multiple() -> Result<(), BreakpadError> {
-        let string = b"PUBLIC m 5180 0 __clang_call_terminate";
-        let record = BreakpadPublicRecord::parse(string)?;
-
-        insta::assert_debug_snapshot!(record, @r###"
-       ⋮BreakpadPublicRecord {
-       ⋮    multiple: true,
-       ⋮    address: 20864,
-       ⋮    parameter_size: 0,
-       ⋮    name: "__clang_call_terminate",
-       ⋮}
+    #[cfg(feature = "serde")]
+    fn test_serialize_file_record() -> Result<(), BreakpadError> {
+        let record =
+            BreakpadFileRecord::parse(b"FILE 37 /usr/include/libkern/i386/_OSByteOrder.h")?;
+        let json = serde_json::to_string_pretty(&record).unwrap();
+
+        insta::assert_snapshot!(json, @r###"
+        {
+          "id": 37,
+          "name": "/usr/include/libkern/i386/_OSByteOrder.h"
+        }
         "###);
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_public_record_no_name() -> Result<(), BreakpadError> {
-        let string = b"PUBLIC 5180 0";
-        let record = BreakpadPublicRecord::parse(string)?;
-
-        insta::assert_debug_snapshot!(record, @r###"
-       ⋮BreakpadPublicRecord {
-       ⋮    multiple: false,
-       ⋮    address: 20864,
-       ⋮    parameter_size: 0,
-       ⋮    name: "<unknown>",
-       ⋮}
+    #[cfg(feature = "serde")]
+    fn test_serialize_func_record() -> Result<(), BreakpadError> {
+        let record = BreakpadFuncRecord::parse(
+            b"FUNC m 1730 1a 0 <name omitted>",
+            Lines::new(b"1730 6 93 20\n1736 14 94 20 7"),
+        )?;
+        let json = serde_json::to_string_pretty(&record).unwrap();
+
+        insta::assert_snapshot!(json, @r###"
+        {
+          "multiple": true,
+          "address": 5936,
+          "size": 26,
+          "parameter_size": 0,
+          "name": "<name omitted>",
+          "lines": [
+            {
+              "address": 5936,
+              "size": 6,
+              "line": 93,
+              "file_id": 20
+            },
+            {
+              "address": 5942,
+              "size": 20,
+              "line": 94,
+              "file_id": 20,
+              "column": 7
+            }
+          ]
+        }
         "###);
 
+        let record = BreakpadFuncRecord::parse(b"FUNC 1730 1a 0 f", Lines::new(b"1730 6"))?;
+        assert!(serde_json::to_string(&record).is_err());
+
         Ok(())
     }
 
     #[test]
-    fn test_parse_stack_cfi_init_record() -> Result<(), BreakpadError> {
-        let string = b"STACK CFI INIT 1880 2d .cfa: $rsp 8 + .ra: .cfa -8 + ^";
-        let record = BreakpadStackRecord::parse(string)?;
-
-        insta::assert_debug_snapshot!(record, @r###"
-        Cfi(
-            BreakpadStackCfiRecord {
-                start: 6272,
-                size: 45,
-                init_rules: ".cfa: $rsp 8 + .ra: .cfa -8 + ^",
-                deltas: Lines(
-                    LineOffsets {
-                        data: [],
-                        finished: true,
-                        index: 0,
-                    },
-                ),
-            },
-        )
+    #[cfg(feature = "serde")]
+    fn test_serialize_line_record() -> Result<(), BreakpadError> {
+        let record = BreakpadLineRecord::parse(b"1730 6 93 20")?;
+        let json = serde_json::to_string_pretty(&record).unwrap();
+
+        insta::assert_snapshot!(json, @r###"
+        {
+          "address": 5936,
+          "size": 6,
+          "line": 93,
+          "file_id": 20
+        }
         "###);
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_stack_win_record() -> Result<(), BreakpadError> {
-        let string =
-            b"STACK WIN 4 371a c 0 0 0 0 0 0 1 $T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + =";
-        let record = BreakpadStackRecord::parse(string)?;
-
-        insta::assert_debug_snapshot!(record, @r###"
-        Win(
-            BreakpadStackWinRecord {
-                ty: FrameData,
-                code_start: 14106,
-                code_size: 12,
-                prolog_size: 0,
-                epilog_size: 0,
-                params_size: 0,
-                saved_regs_size: 0,
-                locals_size: 0,
-                max_stack_size: 0,
-                uses_base_pointer: false,
-                program_string: Some(
-                    "$T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + =",
-                ),
-            },
-        )
+    #[cfg(feature = "serde")]
+    fn test_serialize_public_record() -> Result<(), BreakpadError> {
+        let record = BreakpadPublicRecord::parse(b"PUBLIC m 5180 0 __clang_call_terminate")?;
+        let json = serde_json::to_string_pretty(&record).unwrap();
+
+        insta::assert_snapshot!(json, @r###"
+        {
+          "multiple": true,
+          "address": 20864,
+          "parameter_size": 0,
+          "name": "__clang_call_terminate"
+        }
         "###);
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_stack_win_record_type_3() -> Result<(), BreakpadError> {
-        let string = b"STACK WIN 3 8a10b ec b 0 c c 4 0 0 1";
-        let record = BreakpadStackWinRecord::parse(string)?;
-
-        insta::assert_debug_snapshot!(record, @r###"
-        BreakpadStackWinRecord {
-            ty: Standard,
-            code_start: 565515,
-            code_size: 236,
-            prolog_size: 11,
-            epilog_size: 0,
-            params_size: 12,
-            saved_regs_size: 12,
-            locals_size: 4,
-            max_stack_size: 0,
-            uses_base_pointer: true,
-            program_string: None,
+    #[cfg(feature = "serde")]
+    fn test_serialize_stack_cfi_record() -> Result<(), BreakpadError> {
+        let record =
+            BreakpadStackRecord::parse(b"STACK CFI INIT 1880 2d .cfa: $rsp 8 + .ra: .cfa -8 + ^")?;
+        let json = serde_json::to_string_pretty(&record).unwrap();
+
+        insta::assert_snapshot!(json, @r###"
+        {
+          "Cfi": {
+            "start": 6272,
+            "size": 45,
+            "init_rules": ".cfa: $rsp 8 + .ra: .cfa -8 + ^",
+            "deltas": []
+          }
         }
         "###);
 
@@ -2133,94 +11058,60 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_stack_win_whitespace() -> Result<(), BreakpadError> {
-        let string =
-            b"     STACK WIN 4 371a c 0 0 0 0 0 0 1 $T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + =
-                ";
-        let record = BreakpadStackRecord::parse(string)?;
-
-        insta::assert_debug_snapshot!(record, @r###"
-        Win(
-            BreakpadStackWinRecord {
-                ty: FrameData,
-                code_start: 14106,
-                code_size: 12,
-                prolog_size: 0,
-                epilog_size: 0,
-                params_size: 0,
-                saved_regs_size: 0,
-                locals_size: 0,
-                max_stack_size: 0,
-                uses_base_pointer: false,
-                program_string: Some(
-                    "$T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + =",
-                ),
-            },
-        )
+    #[cfg(feature = "serde")]
+    fn test_serialize_stack_cfi_delta_record() -> Result<(), BreakpadError> {
+        let record = BreakpadStackCfiDeltaRecord::parse(
+            b"STACK CFI 804c4b1 .cfa: $esp 8 + $ebp: .cfa 8 - ^",
+        )?;
+        let json = serde_json::to_string_pretty(&record).unwrap();
+
+        insta::assert_snapshot!(json, @r###"
+        {
+          "address": 134530225,
+          "rules": ".cfa: $esp 8 + $ebp: .cfa 8 - ^"
+        }
         "###);
+
         Ok(())
     }
 
-    use similar_asserts::assert_eq;
-
     #[test]
-    fn test_lineoffsets_fused() {
-        let data = b"";
-        let mut offsets = LineOffsets::new(data);
+    #[cfg(feature = "serde")]
+    fn test_serialize_stack_win_record() -> Result<(), BreakpadError> {
+        let record = BreakpadStackRecord::parse(
+            b"STACK WIN 4 371a c 0 0 0 0 0 0 1 $T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + =",
+        )?;
+        let json = serde_json::to_string_pretty(&record).unwrap();
+
+        insta::assert_snapshot!(json, @r###"
+        {
+          "Win": {
+            "ty": "FrameData",
+            "code_start": 14106,
+            "code_size": 12,
+            "prolog_size": 0,
+            "epilog_size": 0,
+            "params_size": 0,
+            "saved_regs_size": 0,
+            "locals_size": 0,
+            "max_stack_size": 0,
+            "uses_base_pointer": false,
+            "program_string": "$T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + ="
+          }
+        }
+        "###);
 
-        offsets.next();
-        assert_eq!(None, offsets.next());
-        assert_eq!(None, offsets.next());
-        assert_eq!(None, offsets.next());
+        Ok(())
     }
 
-    macro_rules! test_lineoffsets {
-        ($name:ident, $data:literal, $( ($index:literal, $line:literal) ),*) => {
-            #[test]
-            fn $name() {
-                let mut offsets = LineOffsets::new($data);
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_deserialize_line_record() -> Result<(), BreakpadError> {
+        let record = BreakpadLineRecord::parse(b"1730 6 93 20")?;
+        let json = serde_json::to_string(&record).unwrap();
+        let deserialized: BreakpadLineRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, record);
 
-                $(
-                    assert_eq!(Some(($index, &$line[..])), offsets.next());
-                )*
-                assert_eq!(None, offsets.next());
-            }
-        };
+        Ok(())
     }
-
-    test_lineoffsets!(test_lineoffsets_empty, b"", (0, b""));
-    test_lineoffsets!(test_lineoffsets_oneline, b"hello", (0, b"hello"));
-    test_lineoffsets!(
-        test_lineoffsets_trailing_n,
-        b"hello\n",
-        (0, b"hello"),
-        (6, b"")
-    );
-    test_lineoffsets!(
-        test_lineoffsets_trailing_rn,
-        b"hello\r\n",
-        (0, b"hello"),
-        (7, b"")
-    );
-    test_lineoffsets!(
-        test_lineoffsets_n,
-        b"hello\nworld\nyo",
-        (0, b"hello"),
-        (6, b"world"),
-        (12, b"yo")
-    );
-    test_lineoffsets!(
-        test_lineoffsets_rn,
-        b"hello\r\nworld\r\nyo",
-        (0, b"hello"),
-        (7, b"world"),
-        (14, b"yo")
-    );
-    test_lineoffsets!(
-        test_lineoffsets_mixed,
-        b"hello\r\nworld\nyo",
-        (0, b"hello"),
-        (7, b"world"),
-        (13, b"yo")
-    );
 }
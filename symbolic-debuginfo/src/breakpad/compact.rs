@@ -0,0 +1,623 @@
+//! A compact binary encoding of Breakpad symbol files.
+use std::convert::TryFrom;
+use std::ops::Deref;
+use std::str;
+
+use super::*;
+
+/// The magic at the start of the compact binary format.
+const MAGIC: &[u8; 4] = b"BPCB";
+
+/// The sections of the compact binary format, in the order they appear.
+const INFO: usize = 0;
+const FILE: usize = 1;
+const FUNC: usize = 2;
+const PUBLIC: usize = 3;
+const STACK_CFI: usize = 4;
+const STACK_WIN: usize = 5;
+const SECTION_COUNT: usize = 6;
+
+fn invalid(message: &'static str) -> BreakpadError {
+    BreakpadError::new(BreakpadErrorKind::InvalidCompactBinary, message)
+}
+
+/// Appends values in the compact binary format to a buffer.
+#[derive(Default)]
+struct Writer {
+    buffer: Vec<u8>,
+}
+
+impl Writer {
+    fn varint(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.buffer.push(byte);
+                return;
+            }
+            self.buffer.push(byte | 0x80);
+        }
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.buffer.push(value as u8);
+    }
+
+    fn str(&mut self, value: &str) {
+        self.varint(value.len() as u64);
+        self.buffer.extend_from_slice(value.as_bytes());
+    }
+
+    fn opt_str(&mut self, value: Option<&str>) {
+        self.bool(value.is_some());
+        if let Some(value) = value {
+            self.str(value);
+        }
+    }
+
+    fn flags(&mut self, multiple: bool, unknown_flags: &[char]) {
+        self.bool(multiple);
+        self.str(&unknown_flags.iter().collect::<String>());
+    }
+}
+
+/// Reads values in the compact binary format from a buffer.
+#[derive(Clone, Debug)]
+struct Reader<'d> {
+    data: &'d [u8],
+}
+
+impl<'d> Reader<'d> {
+    fn bytes(&mut self, len: usize) -> Result<&'d [u8], BreakpadError> {
+        if len > self.data.len() {
+            return Err(invalid("unexpected end of data"));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> Result<u64, BreakpadError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.bytes(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint overflows"))
+    }
+
+    fn len(&mut self) -> Result<usize, BreakpadError> {
+        usize::try_from(self.varint()?).map_err(|_| invalid("length overflows"))
+    }
+
+    fn u64(&mut self) -> Result<u64, BreakpadError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.bytes(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn int<T: TryFrom<u64>>(&mut self) -> Result<T, BreakpadError> {
+        T::try_from(self.u64()?).map_err(|_| invalid("integer out of range"))
+    }
+
+    fn bool(&mut self) -> Result<bool, BreakpadError> {
+        match self.bytes(1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid("invalid boolean")),
+        }
+    }
+
+    fn str(&mut self) -> Result<&'d str, BreakpadError> {
+        let len = self.len()?;
+        Ok(str::from_utf8(self.bytes(len)?)?)
+    }
+
+    fn opt_str(&mut self) -> Result<Option<&'d str>, BreakpadError> {
+        match self.bool()? {
+            true => self.str().map(Some),
+            false => Ok(None),
+        }
+    }
+
+    fn flags(&mut self) -> Result<(bool, Vec<char>), BreakpadError> {
+        Ok((self.bool()?, self.str()?.chars().collect()))
+    }
+}
+
+/// A `FUNC` record along with its line records, read from the compact binary format.
+///
+/// This dereferences to the [`BreakpadFuncRecord`]. Since the line records are stored separately
+/// in the compact format, they are returned by [`lines`](Self::lines) rather than by the record.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CompactBreakpadFunc<'d> {
+    record: BreakpadFuncRecord<'d>,
+    lines: Vec<BreakpadLineRecord>,
+}
+
+impl<'d> CompactBreakpadFunc<'d> {
+    /// Returns the function record.
+    ///
+    /// The [`lines`](BreakpadFuncRecord::lines) iterator of this record is always empty.
+    pub fn record(&self) -> &BreakpadFuncRecord<'d> {
+        &self.record
+    }
+
+    /// Returns an iterator over the line records of this function.
+    ///
+    /// This yields the same records as [`BreakpadFuncRecord::lines`] of the original record.
+    pub fn lines(&self) -> impl Iterator<Item = Result<BreakpadLineRecord, BreakpadError>> + '_ {
+        self.lines.iter().cloned().map(Ok)
+    }
+
+    /// Converts this record into an owned record with all of its line records.
+    pub fn into_owned(self) -> BreakpadFuncRecordOwned {
+        BreakpadFuncRecordOwned {
+            multiple: self.record.multiple,
+            unknown_flags: self.record.unknown_flags,
+            address: self.record.address,
+            size: self.record.size,
+            parameter_size: self.record.parameter_size,
+            name: self.record.name.to_owned(),
+            lines: self.lines,
+        }
+    }
+}
+
+impl<'d> Deref for CompactBreakpadFunc<'d> {
+    type Target = BreakpadFuncRecord<'d>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.record
+    }
+}
+
+/// A `STACK CFI INIT` record along with its `STACK CFI` records, read from the compact binary
+/// format.
+///
+/// This dereferences to the [`BreakpadStackCfiRecord`]. Since the `STACK CFI` records are stored
+/// separately in the compact format, they are returned by [`deltas`](Self::deltas) rather than by
+/// the record.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CompactBreakpadStackCfi<'d> {
+    record: BreakpadStackCfiRecord<'d>,
+    deltas: Vec<BreakpadStackCfiDeltaRecord<'d>>,
+}
+
+impl<'d> CompactBreakpadStackCfi<'d> {
+    /// Returns the `STACK CFI INIT` record.
+    ///
+    /// The [`deltas`](BreakpadStackCfiRecord::deltas) iterator of this record is always empty.
+    pub fn record(&self) -> &BreakpadStackCfiRecord<'d> {
+        &self.record
+    }
+
+    /// Returns an iterator over the `STACK CFI` records belonging to this record.
+    ///
+    /// This yields the same records as [`BreakpadStackCfiRecord::deltas`] of the original record.
+    pub fn deltas(
+        &self,
+    ) -> impl Iterator<Item = Result<BreakpadStackCfiDeltaRecord<'d>, BreakpadError>> + '_ {
+        self.deltas.iter().cloned().map(Ok)
+    }
+}
+
+impl<'d> Deref for CompactBreakpadStackCfi<'d> {
+    type Target = BreakpadStackCfiRecord<'d>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.record
+    }
+}
+
+/// A Breakpad object read from the compact binary format.
+///
+/// This is created by [`BreakpadObject::from_compact_binary`]. Only the header is read when the
+/// object is created, and records are decoded as they are iterated.
+///
+/// **The format is specific to the version of this crate.** It is meant as a cache next to the
+/// original symbol file and must not be used for long-term storage or exchange.
+///
+/// # Format
+///
+/// All integers are encoded as 8-byte little-endian values, except for counts and lengths, which
+/// are unsigned LEB128 varints. Strings are varint length-prefixed UTF-8. Optional values are
+/// prefixed with a byte that is `1` if the value is present and `0` otherwise.
+///
+/// The data starts with the magic `BPCB`, followed by the `MODULE` record as four strings and a
+/// header of six sections, each given by its record count and byte length. The sections contain
+/// the `INFO`, `FILE`, `FUNC` (including line records), `PUBLIC`, `STACK CFI INIT` (including
+/// their `STACK CFI` records) and `STACK WIN` records in this order.
+#[derive(Clone, Debug)]
+pub struct CompactBreakpadObject<'data> {
+    module: BreakpadModuleRecord<'data>,
+    sections: [(usize, &'data [u8]); SECTION_COUNT],
+}
+
+impl<'data> CompactBreakpadObject<'data> {
+    /// Tests whether the buffer could contain a Breakpad object in the compact binary format.
+    pub fn test(data: &[u8]) -> bool {
+        data.starts_with(MAGIC)
+    }
+
+    /// Reads the header of a Breakpad object in the compact binary format.
+    ///
+    /// Fails with [`BreakpadErrorKind::InvalidCompactBinary`] if the header is malformed or the
+    /// sections exceed the data. Malformed records are only detected when they are iterated.
+    pub fn parse(data: &'data [u8]) -> Result<Self, BreakpadError> {
+        let data = data
+            .strip_prefix(&MAGIC[..])
+            .ok_or_else(|| invalid("missing magic"))?;
+        let mut reader = Reader { data };
+
+        let module = BreakpadModuleRecord {
+            os: reader.str()?,
+            arch: reader.str()?,
+            id: reader.str()?,
+            name: reader.str()?,
+        };
+
+        let mut header = [(0, 0); SECTION_COUNT];
+        for section in &mut header {
+            *section = (reader.len()?, reader.len()?);
+        }
+
+        let mut sections = [(0, &[][..]); SECTION_COUNT];
+        for (section, (count, len)) in sections.iter_mut().zip(header) {
+            *section = (count, reader.bytes(len)?);
+        }
+
+        if !reader.data.is_empty() {
+            return Err(invalid("trailing data"));
+        }
+
+        Ok(Self { module, sections })
+    }
+
+    /// The `MODULE` record of the original symbol file.
+    pub fn module_record(&self) -> &BreakpadModuleRecord<'data> {
+        &self.module
+    }
+
+    /// The CPU architecture of the original symbol file.
+    ///
+    /// Returns [`Arch::Unknown`] if the architecture is not recognized.
+    pub fn arch(&self) -> Arch {
+        self.module.arch.parse().unwrap_or_default()
+    }
+
+    fn records<T>(
+        &self,
+        section: usize,
+        decode: fn(&mut Reader<'data>) -> Result<T, BreakpadError>,
+    ) -> CompactBreakpadRecords<'data, T> {
+        let (remaining, data) = self.sections[section];
+        CompactBreakpadRecords {
+            reader: Reader { data },
+            remaining,
+            decode,
+        }
+    }
+
+    /// Returns an iterator over the `INFO` records.
+    pub fn info_records(&self) -> CompactBreakpadRecords<'data, BreakpadInfoRecord<'data>> {
+        self.records(INFO, |reader| {
+            Ok(match reader.bool()? {
+                true => BreakpadInfoRecord::CodeId {
+                    code_id: reader.str()?,
+                    code_file: reader.str()?,
+                },
+                false => BreakpadInfoRecord::Other {
+                    scope: reader.str()?,
+                    info: reader.str()?,
+                },
+            })
+        })
+    }
+
+    /// Returns an iterator over the `FILE` records.
+    pub fn file_records(&self) -> CompactBreakpadRecords<'data, BreakpadFileRecord<'data>> {
+        self.records(FILE, |reader| {
+            Ok(BreakpadFileRecord {
+                id: reader.u64()?,
+                name: reader.str()?,
+                checksum: reader.opt_str()?,
+            })
+        })
+    }
+
+    /// Returns a map for file name lookups by id.
+    pub fn file_map(&self) -> BreakpadFileMap<'data> {
+        self.file_records()
+            .filter_map(Result::ok)
+            .map(|file| (file.id, file.name))
+            .collect()
+    }
+
+    /// Returns an iterator over the `FUNC` records and their line records.
+    pub fn func_records(&self) -> CompactBreakpadRecords<'data, CompactBreakpadFunc<'data>> {
+        self.records(FUNC, |reader| {
+            let (multiple, unknown_flags) = reader.flags()?;
+            let record = BreakpadFuncRecord {
+                multiple,
+                unknown_flags,
+                address: reader.u64()?,
+                size: reader.u64()?,
+                parameter_size: reader.u64()?,
+                name: reader.str()?,
+                lines: Lines::default(),
+            };
+
+            let count = reader.len()?;
+            let mut lines = Vec::with_capacity(count.min(reader.data.len()));
+            for _ in 0..count {
+                lines.push(BreakpadLineRecord {
+                    address: reader.u64()?,
+                    size: reader.u64()?,
+                    line: reader.u64()?,
+                    file_id: reader.u64()?,
+                    column: match reader.bool()? {
+                        true => Some(reader.u64()?),
+                        false => None,
+                    },
+                });
+            }
+
+            Ok(CompactBreakpadFunc { record, lines })
+        })
+    }
+
+    /// Returns an iterator over the `PUBLIC` records.
+    pub fn public_records(&self) -> CompactBreakpadRecords<'data, BreakpadPublicRecord<'data>> {
+        self.records(PUBLIC, |reader| {
+            let (multiple, unknown_flags) = reader.flags()?;
+            Ok(BreakpadPublicRecord {
+                multiple,
+                unknown_flags,
+                address: reader.u64()?,
+                parameter_size: reader.u64()?,
+                name: reader.str()?,
+            })
+        })
+    }
+
+    /// Returns an iterator over the `STACK CFI INIT` records and their `STACK CFI` records.
+    pub fn cfi_records(&self) -> CompactBreakpadRecords<'data, CompactBreakpadStackCfi<'data>> {
+        self.records(STACK_CFI, |reader| {
+            let record = BreakpadStackCfiRecord {
+                start: reader.u64()?,
+                size: reader.u64()?,
+                init_rules: reader.str()?,
+                deltas: Lines::default(),
+            };
+
+            let count = reader.len()?;
+            let mut deltas = Vec::with_capacity(count.min(reader.data.len()));
+            for _ in 0..count {
+                deltas.push(BreakpadStackCfiDeltaRecord {
+                    address: reader.u64()?,
+                    rules: reader.str()?,
+                });
+            }
+
+            Ok(CompactBreakpadStackCfi { record, deltas })
+        })
+    }
+
+    /// Returns an iterator over the `STACK WIN` records.
+    pub fn win_records(&self) -> CompactBreakpadRecords<'data, BreakpadStackWinRecord<'data>> {
+        self.records(STACK_WIN, |reader| {
+            let ty = match reader.bytes(1)?[0] {
+                0 => BreakpadStackWinRecordType::Fpo,
+                1 => BreakpadStackWinRecordType::Trap,
+                2 => BreakpadStackWinRecordType::Tss,
+                3 => BreakpadStackWinRecordType::Standard,
+                4 => BreakpadStackWinRecordType::FrameData,
+                0xff => BreakpadStackWinRecordType::Unknown,
+                _ => return Err(invalid("invalid stack win record type")),
+            };
+
+            Ok(BreakpadStackWinRecord {
+                ty,
+                code_start: reader.int()?,
+                code_size: reader.int()?,
+                prolog_size: reader.int()?,
+                epilog_size: reader.int()?,
+                params_size: reader.int()?,
+                saved_regs_size: reader.int()?,
+                locals_size: reader.int()?,
+                max_stack_size: reader.int()?,
+                uses_base_pointer: reader.bool()?,
+                program_string: reader.opt_str()?,
+            })
+        })
+    }
+}
+
+/// An iterator over the records of a [`CompactBreakpadObject`].
+///
+/// Iteration stops after the first record that cannot be decoded. If data remains in a section
+/// after its last record, an error is returned after that record.
+#[derive(Clone)]
+pub struct CompactBreakpadRecords<'d, T> {
+    reader: Reader<'d>,
+    remaining: usize,
+    decode: fn(&mut Reader<'d>) -> Result<T, BreakpadError>,
+}
+
+impl<T> fmt::Debug for CompactBreakpadRecords<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompactBreakpadRecords")
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
+
+impl<'d, T> Iterator for CompactBreakpadRecords<'d, T> {
+    type Item = Result<T, BreakpadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            if self.reader.data.is_empty() {
+                return None;
+            }
+            self.reader.data = &[];
+            return Some(Err(invalid("trailing data in section")));
+        }
+
+        let result = (self.decode)(&mut self.reader);
+        match result {
+            Ok(_) => self.remaining -= 1,
+            Err(_) => {
+                self.remaining = 0;
+                self.reader.data = &[];
+            }
+        }
+
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let trailing = usize::from(!self.reader.data.is_empty());
+        (0, Some(self.remaining + trailing))
+    }
+}
+
+impl<'data> BreakpadObject<'data> {
+    /// Writes all records of this object in the compact binary format.
+    ///
+    /// The result can be loaded with [`from_compact_binary`](Self::from_compact_binary) much faster
+    /// than the text format can be parsed. **The format is specific to the version of this crate**,
+    /// see [`CompactBreakpadObject`] for details. Fails if any record cannot be parsed.
+    pub fn to_compact_binary(&self) -> Result<Vec<u8>, BreakpadError> {
+        let mut sections: [(usize, Writer); SECTION_COUNT] = Default::default();
+
+        for record in self.info_records() {
+            let (count, w) = &mut sections[INFO];
+            *count += 1;
+            match record? {
+                BreakpadInfoRecord::CodeId { code_id, code_file } => {
+                    w.bool(true);
+                    w.str(code_id);
+                    w.str(code_file);
+                }
+                BreakpadInfoRecord::Other { scope, info } => {
+                    w.bool(false);
+                    w.str(scope);
+                    w.str(info);
+                }
+            }
+        }
+
+        for record in self.file_records() {
+            let record = record?;
+            let (count, w) = &mut sections[FILE];
+            *count += 1;
+            w.u64(record.id);
+            w.str(record.name);
+            w.opt_str(record.checksum);
+        }
+
+        for record in self.func_records() {
+            let record = record?;
+            let lines = record.lines().collect::<Result<Vec<_>, _>>()?;
+            let (count, w) = &mut sections[FUNC];
+            *count += 1;
+            w.flags(record.multiple, &record.unknown_flags);
+            w.u64(record.address);
+            w.u64(record.size);
+            w.u64(record.parameter_size);
+            w.str(record.name);
+            w.varint(lines.len() as u64);
+            for line in lines {
+                w.u64(line.address);
+                w.u64(line.size);
+                w.u64(line.line);
+                w.u64(line.file_id);
+                w.bool(line.column.is_some());
+                if let Some(column) = line.column {
+                    w.u64(column);
+                }
+            }
+        }
+
+        for record in self.public_records() {
+            let record = record?;
+            let (count, w) = &mut sections[PUBLIC];
+            *count += 1;
+            w.flags(record.multiple, &record.unknown_flags);
+            w.u64(record.address);
+            w.u64(record.parameter_size);
+            w.str(record.name);
+        }
+
+        for record in self.stack_records() {
+            match record? {
+                BreakpadStackRecord::Cfi(record) => {
+                    let deltas = record.deltas().collect::<Result<Vec<_>, _>>()?;
+                    let (count, w) = &mut sections[STACK_CFI];
+                    *count += 1;
+                    w.u64(record.start);
+                    w.u64(record.size);
+                    w.str(record.init_rules);
+                    w.varint(deltas.len() as u64);
+                    for delta in deltas {
+                        w.u64(delta.address);
+                        w.str(delta.rules);
+                    }
+                }
+                BreakpadStackRecord::Win(record) => {
+                    let (count, w) = &mut sections[STACK_WIN];
+                    *count += 1;
+                    w.buffer.push(match record.ty {
+                        BreakpadStackWinRecordType::Unknown => 0xff,
+                        ty => ty as u8,
+                    });
+                    w.u64(record.code_start.into());
+                    w.u64(record.code_size.into());
+                    w.u64(record.prolog_size.into());
+                    w.u64(record.epilog_size.into());
+                    w.u64(record.params_size.into());
+                    w.u64(record.saved_regs_size.into());
+                    w.u64(record.locals_size.into());
+                    w.u64(record.max_stack_size.into());
+                    w.bool(record.uses_base_pointer);
+                    w.opt_str(record.program_string);
+                }
+            }
+        }
+
+        let mut output = Writer::default();
+        output.buffer.extend_from_slice(MAGIC);
+        output.str(self.module.os);
+        output.str(self.module.arch);
+        output.str(self.module.id);
+        output.str(self.module.name);
+        for (count, section) in &sections {
+            output.varint(*count as u64);
+            output.varint(section.buffer.len() as u64);
+        }
+        for (_, section) in sections {
+            output.buffer.extend_from_slice(&section.buffer);
+        }
+
+        Ok(output.buffer)
+    }
+
+    /// Reads a Breakpad object written by [`to_compact_binary`](Self::to_compact_binary).
+    ///
+    /// This is a shorthand for [`CompactBreakpadObject::parse`].
+    pub fn from_compact_binary(
+        data: &'data [u8],
+    ) -> Result<CompactBreakpadObject<'data>, BreakpadError> {
+        CompactBreakpadObject::parse(data)
+    }
+}
@@ -0,0 +1,210 @@
+//! A persistent binary format for [`BreakpadIndex`].
+//!
+//! The format starts with a magic and version, followed by the length and 64-bit FNV-1a hash of the
+//! indexed data and the debug id of the object, which are checked when loading the index. After
+//! that, it contains the offsets of `FILE` records, followed by `FUNC`, `PUBLIC` and `STACK`
+//! entries. All integers are stored as 64-bit little endian values.
+use std::convert::TryFrom;
+use std::str;
+
+use super::*;
+
+/// The magic at the start of a persisted index.
+const MAGIC: &[u8; 4] = b"BPIX";
+
+/// The version of the persisted index format.
+const VERSION: u64 = 2;
+
+fn invalid<E>(message: E) -> BreakpadError
+where
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    BreakpadError::new(BreakpadErrorKind::InvalidIndex, message)
+}
+
+/// Computes the 64-bit FNV-1a hash of the indexed data.
+///
+/// The hash must not change between releases, so it cannot use the hashers of the standard library.
+fn content_hash(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Reads 64-bit little endian values from a buffer.
+struct Reader<'d> {
+    data: &'d [u8],
+}
+
+impl<'d> Reader<'d> {
+    fn bytes(&mut self, len: usize) -> Result<&'d [u8], BreakpadError> {
+        if len > self.data.len() {
+            return Err(invalid("unexpected end of data"));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, BreakpadError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.bytes(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn usize(&mut self) -> Result<usize, BreakpadError> {
+        usize::try_from(self.u64()?).map_err(|_| invalid("integer out of range"))
+    }
+
+    /// Reads the number of entries of a section, each taking up `fields` values.
+    fn count(&mut self, fields: usize) -> Result<usize, BreakpadError> {
+        let count = self.usize()?;
+        if count.saturating_mul(fields * 8) > self.data.len() {
+            return Err(invalid("unexpected end of data"));
+        }
+        Ok(count)
+    }
+}
+
+/// Checks that `offset` points to the start of a record with the given prefix.
+fn check_offset(data: &[u8], offset: usize, prefix: &[u8]) -> Result<(), BreakpadError> {
+    let at_line_start = offset == 0 || data.get(offset - 1) == Some(&b'\n');
+    match data.get(offset..) {
+        Some(record) if at_line_start && record.starts_with(prefix) => Ok(()),
+        _ => Err(invalid(format!("no record at offset {}", offset))),
+    }
+}
+
+/// Checks that the entries are sorted by their address.
+fn check_sorted<T>(entries: &[T], address: impl Fn(&T) -> u64) -> Result<(), BreakpadError> {
+    if entries.windows(2).all(|w| address(&w[0]) <= address(&w[1])) {
+        Ok(())
+    } else {
+        Err(invalid("entries are not sorted"))
+    }
+}
+
+impl<'data> BreakpadIndex<'data> {
+    /// Writes this index in a compact binary format.
+    ///
+    /// The index can be loaded again with [`load`](Self::load) for the same symbol file, which
+    /// avoids parsing the entire file. The format stores the size and a hash of the file, as well
+    /// as the debug id of the object, so that stale indexes are rejected when loading.
+    pub fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let debug_id = self.debug_id.to_string();
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(self.data.len() as u64).to_le_bytes())?;
+        writer.write_all(&content_hash(self.data).to_le_bytes())?;
+        writer.write_all(&(debug_id.len() as u64).to_le_bytes())?;
+        writer.write_all(debug_id.as_bytes())?;
+
+        let mut write = |values: &[u64]| -> io::Result<()> {
+            for value in values {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+            Ok(())
+        };
+
+        write(&[self.files.len() as u64])?;
+        for &offset in &self.files {
+            write(&[offset as u64])?;
+        }
+
+        write(&[self.functions.len() as u64])?;
+        for &(address, size, offset) in &self.functions {
+            write(&[address, size, offset as u64])?;
+        }
+
+        write(&[self.publics.len() as u64])?;
+        for &(address, offset) in &self.publics {
+            write(&[address, offset as u64])?;
+        }
+
+        write(&[self.stack_records.len() as u64])?;
+        for &(start, end, offset) in &self.stack_records {
+            write(&[start, end, offset as u64])?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads an index written by [`serialize`](Self::serialize) for the given object.
+    ///
+    /// This fails with [`BreakpadErrorKind::InvalidIndex`] if the index is malformed, or if it was
+    /// written for an object with a different debug id, size or content. Checking the content
+    /// hashes the data once, which is still much faster than parsing it. Every indexed offset must
+    /// point to the start of a record of the expected type. `FILE` records are parsed again to
+    /// restore the file map, but all other records are only parsed when they are looked up.
+    pub fn load(data: &[u8], object: &BreakpadObject<'data>) -> Result<Self, BreakpadError> {
+        let mut reader = Reader { data };
+        if reader.bytes(MAGIC.len())? != MAGIC {
+            return Err(invalid("invalid magic"));
+        }
+
+        let version = reader.u64()?;
+        if version != VERSION {
+            return Err(invalid(format!("unsupported version {}", version)));
+        }
+
+        let object_data = object.data();
+        if reader.usize()? != object_data.len() {
+            return Err(invalid("index was created for data of a different size"));
+        }
+        if reader.u64()? != content_hash(object_data) {
+            return Err(invalid("index was created for different data"));
+        }
+
+        let debug_id_len = reader.usize()?;
+        let debug_id = str::from_utf8(reader.bytes(debug_id_len)?).map_err(invalid)?;
+        if debug_id != object.debug_id().to_string() {
+            return Err(invalid("index was created for a different debug id"));
+        }
+
+        let mut index = Self {
+            data: object_data,
+            debug_id: object.debug_id(),
+            ..Self::default()
+        };
+
+        for _ in 0..reader.count(1)? {
+            let offset = reader.usize()?;
+            check_offset(object_data, offset, b"FILE ")?;
+            let line = Lines::new(&object_data[offset..])
+                .next()
+                .unwrap_or_default();
+            let record = BreakpadFileRecord::parse(line).map_err(invalid)?;
+            index.file_map.insert(record.id, record.name);
+            index.files.push(offset);
+        }
+
+        for _ in 0..reader.count(3)? {
+            let entry = (reader.u64()?, reader.u64()?, reader.usize()?);
+            check_offset(object_data, entry.2, b"FUNC ")?;
+            index.functions.push(entry);
+        }
+
+        for _ in 0..reader.count(2)? {
+            let entry = (reader.u64()?, reader.usize()?);
+            check_offset(object_data, entry.1, b"PUBLIC ")?;
+            index.publics.push(entry);
+        }
+
+        for _ in 0..reader.count(3)? {
+            let entry = (reader.u64()?, reader.u64()?, reader.usize()?);
+            check_offset(object_data, entry.2, b"STACK ")?;
+            index.stack_records.push(entry);
+        }
+
+        if !reader.data.is_empty() {
+            return Err(invalid("trailing data"));
+        }
+
+        check_sorted(&index.functions, |&(address, _, _)| address)?;
+        check_sorted(&index.publics, |&(address, _)| address)?;
+        check_sorted(&index.stack_records, |&(start, _, _)| start)?;
+
+        Ok(index)
+    }
+}
@@ -0,0 +1,315 @@
+//! JSON export of Breakpad objects.
+//!
+//! The document is built from the data transfer objects in this module rather than the
+//! `Serialize` implementations of the records, so that changes to the records do not change the
+//! document. Its structure is frozen for a `format_version`; any change requires a new version.
+use serde::ser::{Error as _, SerializeSeq};
+use serde::{Serialize, Serializer};
+
+use super::*;
+
+/// The version of the JSON document written by [`BreakpadObject::convert_to_json`].
+const FORMAT_VERSION: u32 = 1;
+
+/// Converts a record into its data transfer object for version 1 of the JSON document.
+trait ToJsonV1 {
+    type Json: Serialize;
+
+    fn to_json_v1(self) -> Self::Json;
+}
+
+/// Serializes all records of an iterator as a sequence, one record at a time.
+///
+/// The iterator is cloned for serialization, so records are parsed lazily and never collected.
+/// Serialization fails with the first record that cannot be parsed.
+struct SeqV1<I>(I);
+
+impl<I, R> Serialize for SeqV1<I>
+where
+    I: Iterator<Item = Result<R, BreakpadError>> + Clone,
+    R: ToJsonV1,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(None)?;
+        for record in self.0.clone() {
+            let record = record.map_err(S::Error::custom)?;
+            seq.serialize_element(&record.to_json_v1())?;
+        }
+        seq.end()
+    }
+}
+
+#[derive(Serialize)]
+struct ObjectV1<'d> {
+    format_version: u32,
+    module: ModuleV1<'d>,
+    info: SeqV1<BreakpadInfoRecords<'d>>,
+    files: SeqV1<BreakpadFileRecords<'d>>,
+    functions: SeqV1<BreakpadFuncRecords<'d>>,
+    publics: SeqV1<BreakpadPublicRecords<'d>>,
+    stack_cfi: SeqV1<BreakpadStackCfiRecords<'d>>,
+    stack_win: SeqV1<BreakpadStackWinRecords<'d>>,
+}
+
+#[derive(Serialize)]
+struct ModuleV1<'d> {
+    os: &'d str,
+    arch: &'d str,
+    id: &'d str,
+    name: &'d str,
+}
+
+#[derive(Serialize)]
+enum InfoV1<'d> {
+    CodeId {
+        code_id: &'d str,
+        code_file: &'d str,
+    },
+    Other {
+        scope: &'d str,
+        info: &'d str,
+    },
+}
+
+impl<'d> ToJsonV1 for BreakpadInfoRecord<'d> {
+    type Json = InfoV1<'d>;
+
+    fn to_json_v1(self) -> Self::Json {
+        match self {
+            Self::CodeId { code_id, code_file } => InfoV1::CodeId { code_id, code_file },
+            Self::Other { scope, info } => InfoV1::Other { scope, info },
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct FileV1<'d> {
+    id: u64,
+    name: &'d str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checksum: Option<&'d str>,
+}
+
+impl<'d> ToJsonV1 for BreakpadFileRecord<'d> {
+    type Json = FileV1<'d>;
+
+    fn to_json_v1(self) -> Self::Json {
+        FileV1 {
+            id: self.id,
+            name: self.name,
+            checksum: self.checksum,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct FunctionV1<'d> {
+    multiple: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unknown_flags: Vec<char>,
+    address: u64,
+    size: u64,
+    parameter_size: u64,
+    name: &'d str,
+    lines: SeqV1<BreakpadLineRecords<'d>>,
+}
+
+impl<'d> ToJsonV1 for BreakpadFuncRecord<'d> {
+    type Json = FunctionV1<'d>;
+
+    fn to_json_v1(self) -> Self::Json {
+        FunctionV1 {
+            lines: SeqV1(self.lines()),
+            multiple: self.multiple,
+            unknown_flags: self.unknown_flags,
+            address: self.address,
+            size: self.size,
+            parameter_size: self.parameter_size,
+            name: self.name,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct LineV1 {
+    address: u64,
+    size: u64,
+    line: u64,
+    file_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<u64>,
+}
+
+impl ToJsonV1 for BreakpadLineRecord {
+    type Json = LineV1;
+
+    fn to_json_v1(self) -> Self::Json {
+        LineV1 {
+            address: self.address,
+            size: self.size,
+            line: self.line,
+            file_id: self.file_id,
+            column: self.column,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PublicV1<'d> {
+    multiple: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unknown_flags: Vec<char>,
+    address: u64,
+    parameter_size: u64,
+    name: &'d str,
+}
+
+impl<'d> ToJsonV1 for BreakpadPublicRecord<'d> {
+    type Json = PublicV1<'d>;
+
+    fn to_json_v1(self) -> Self::Json {
+        PublicV1 {
+            multiple: self.multiple,
+            unknown_flags: self.unknown_flags,
+            address: self.address,
+            parameter_size: self.parameter_size,
+            name: self.name,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StackCfiV1<'d> {
+    start: u64,
+    size: u64,
+    init_rules: &'d str,
+    deltas: SeqV1<BreakpadStackCfiDeltaRecords<'d>>,
+}
+
+impl<'d> ToJsonV1 for BreakpadStackCfiRecord<'d> {
+    type Json = StackCfiV1<'d>;
+
+    fn to_json_v1(self) -> Self::Json {
+        StackCfiV1 {
+            deltas: SeqV1(self.deltas()),
+            start: self.start,
+            size: self.size,
+            init_rules: self.init_rules,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StackCfiDeltaV1<'d> {
+    address: u64,
+    rules: &'d str,
+}
+
+impl<'d> ToJsonV1 for BreakpadStackCfiDeltaRecord<'d> {
+    type Json = StackCfiDeltaV1<'d>;
+
+    fn to_json_v1(self) -> Self::Json {
+        StackCfiDeltaV1 {
+            address: self.address,
+            rules: self.rules,
+        }
+    }
+}
+
+#[derive(Serialize)]
+enum StackWinTypeV1 {
+    Fpo,
+    Trap,
+    Tss,
+    Standard,
+    FrameData,
+    Unknown,
+}
+
+#[derive(Serialize)]
+struct StackWinV1<'d> {
+    ty: StackWinTypeV1,
+    code_start: u32,
+    code_size: u32,
+    prolog_size: u16,
+    epilog_size: u16,
+    params_size: u32,
+    saved_regs_size: u16,
+    locals_size: u32,
+    max_stack_size: u32,
+    uses_base_pointer: bool,
+    program_string: Option<&'d str>,
+}
+
+impl<'d> ToJsonV1 for BreakpadStackWinRecord<'d> {
+    type Json = StackWinV1<'d>;
+
+    fn to_json_v1(self) -> Self::Json {
+        StackWinV1 {
+            ty: match self.ty {
+                BreakpadStackWinRecordType::Fpo => StackWinTypeV1::Fpo,
+                BreakpadStackWinRecordType::Trap => StackWinTypeV1::Trap,
+                BreakpadStackWinRecordType::Tss => StackWinTypeV1::Tss,
+                BreakpadStackWinRecordType::Standard => StackWinTypeV1::Standard,
+                BreakpadStackWinRecordType::FrameData => StackWinTypeV1::FrameData,
+                BreakpadStackWinRecordType::Unknown => StackWinTypeV1::Unknown,
+            },
+            code_start: self.code_start,
+            code_size: self.code_size,
+            prolog_size: self.prolog_size,
+            epilog_size: self.epilog_size,
+            params_size: self.params_size,
+            saved_regs_size: self.saved_regs_size,
+            locals_size: self.locals_size,
+            max_stack_size: self.max_stack_size,
+            uses_base_pointer: self.uses_base_pointer,
+            program_string: self.program_string,
+        }
+    }
+}
+
+impl<'data> BreakpadObject<'data> {
+    /// Writes all records of this object as a JSON document.
+    ///
+    /// The document has the following stable structure, which is versioned by `format_version`:
+    ///
+    /// ```json
+    /// {
+    ///   "format_version": 1,
+    ///   "module": { "os": "...", "arch": "...", "id": "...", "name": "..." },
+    ///   "info": [...],
+    ///   "files": [...],
+    ///   "functions": [{ "address": 4096, ..., "lines": [...] }],
+    ///   "publics": [...],
+    ///   "stack_cfi": [{ "start": 4096, ..., "deltas": [...] }],
+    ///   "stack_win": [...]
+    /// }
+    /// ```
+    ///
+    /// Records are written with the same fields as their `Serialize` implementations at version 1,
+    /// with line records nested in their functions and delta records nested in their
+    /// `STACK CFI INIT` records. Records are collected from the entire file, regardless of their
+    /// position, and listed in the order they appear. The document is written while the records are
+    /// parsed, without collecting them in memory. If a record fails to parse, an error is returned
+    /// and the output is incomplete.
+    pub fn convert_to_json<W: Write>(&self, writer: &mut W) -> Result<(), serde_json::Error> {
+        let module = &self.module;
+        let object = ObjectV1 {
+            format_version: FORMAT_VERSION,
+            module: ModuleV1 {
+                os: module.os,
+                arch: module.arch,
+                id: module.id,
+                name: module.name,
+            },
+            info: SeqV1(self.info_records_full()),
+            files: SeqV1(self.file_records_full()),
+            functions: SeqV1(self.func_records_full()),
+            publics: SeqV1(self.public_records_full()),
+            stack_cfi: SeqV1(self.cfi_records()),
+            stack_win: SeqV1(self.win_records()),
+        };
+
+        serde_json::to_writer(writer, &object)
+    }
+}
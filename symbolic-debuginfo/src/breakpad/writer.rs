@@ -0,0 +1,231 @@
+//! Streaming output of Breakpad symbol files.
+use std::collections::HashSet;
+
+use super::*;
+
+/// The sections of a Breakpad symbol file, in the order they appear.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum Section {
+    Start,
+    Module,
+    Info,
+    File,
+    Func,
+    Public,
+    Stack,
+}
+
+/// Writes a Breakpad symbol file one record at a time.
+///
+/// Records are written to the underlying writer immediately, so that large symbol files can be
+/// produced without holding all records in memory. The writer enforces the canonical order of
+/// records: a single `MODULE` record, followed by `INFO`, `FILE`, `FUNC` with their line records,
+/// `PUBLIC` and finally `STACK` records. Writing a record out of order fails with
+/// [`BreakpadErrorKind::RecordOutOfOrder`] and does not write anything.
+///
+/// Line records belong to the preceding `FUNC` record, and delta records to the preceding
+/// `STACK CFI INIT` record. They can only be written directly after it or after other line or delta
+/// records of the same record.
+///
+/// # Example
+///
+/// ```
+/// use symbolic_debuginfo::breakpad::{
+///     BreakpadFileRecord, BreakpadFuncRecord, BreakpadLineRecord, BreakpadModuleRecord,
+///     BreakpadObject, BreakpadStreamWriter,
+/// };
+///
+/// let mut writer = BreakpadStreamWriter::new(Vec::new()).strict_file_ids();
+/// writer.write_module(&BreakpadModuleRecord {
+///     os: "Linux",
+///     arch: "x86_64",
+///     id: "492E2DD23CC306CA9C494EEF1533A3810",
+///     name: "crash",
+/// })?;
+/// writer.write_file(&BreakpadFileRecord {
+///     id: 0,
+///     name: "main.c",
+///     checksum: None,
+/// })?;
+/// writer.write_func(&BreakpadFuncRecord::parse(b"FUNC 1000 10 0 main", Default::default())?)?;
+/// writer.write_line(&BreakpadLineRecord::new(0x1000, 0x10, 42, 0, None)?)?;
+///
+/// let data = writer.into_inner();
+/// let object = BreakpadObject::parse(&data)?;
+/// assert_eq!(object.func_records().count(), 1);
+/// # Ok::<(), symbolic_debuginfo::breakpad::BreakpadError>(())
+/// ```
+#[derive(Debug)]
+pub struct BreakpadStreamWriter<W: Write> {
+    inner: W,
+    section: Section,
+    previous: Option<&'static str>,
+    func_address: Option<u64>,
+    in_cfi: bool,
+    file_ids: HashSet<u64>,
+    strict_file_ids: bool,
+}
+
+impl<W: Write> BreakpadStreamWriter<W> {
+    /// Creates a new `BreakpadStreamWriter` that outputs to a writer.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            section: Section::Start,
+            previous: None,
+            func_address: None,
+            in_cfi: false,
+            file_ids: HashSet::new(),
+            strict_file_ids: false,
+        }
+    }
+
+    /// Rejects line records that reference a file id without a preceding `FILE` record.
+    ///
+    /// Such line records fail with [`BreakpadErrorKind::UnknownFileId`]. This requires keeping the
+    /// ids of all written `FILE` records in memory.
+    pub fn strict_file_ids(self) -> Self {
+        Self {
+            strict_file_ids: true,
+            ..self
+        }
+    }
+
+    /// Returns the wrapped writer from this instance.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Checks that a record of the given type may be written in `section`, and enters it.
+    fn enter(&mut self, section: Section, record: &'static str) -> Result<(), BreakpadError> {
+        let valid = match section {
+            Section::Module => self.section == Section::Start,
+            _ => self.section != Section::Start && self.section <= section,
+        };
+
+        if !valid {
+            let previous = self.previous;
+            return Err(BreakpadErrorKind::RecordOutOfOrder { record, previous }.into());
+        }
+
+        self.section = section;
+        self.previous = Some(record);
+        self.func_address = None;
+        self.in_cfi = false;
+        Ok(())
+    }
+
+    /// Writes a single record followed by a line break to the underlying writer.
+    fn write_record(&mut self, record: &dyn fmt::Display) -> Result<(), BreakpadError> {
+        writeln!(self.inner, "{}", record)
+            .map_err(|e| BreakpadError::new(BreakpadErrorKind::WriteFailed, e))
+    }
+
+    /// Writes the `MODULE` record, which must be the first record.
+    pub fn write_module(&mut self, record: &BreakpadModuleRecord<'_>) -> Result<(), BreakpadError> {
+        self.enter(Section::Module, "MODULE")?;
+        self.write_record(record)?;
+        Ok(())
+    }
+
+    /// Writes an `INFO` record.
+    pub fn write_info(&mut self, record: &BreakpadInfoRecord<'_>) -> Result<(), BreakpadError> {
+        self.enter(Section::Info, "INFO")?;
+        self.write_record(record)?;
+        Ok(())
+    }
+
+    /// Writes a `FILE` record.
+    pub fn write_file(&mut self, record: &BreakpadFileRecord<'_>) -> Result<(), BreakpadError> {
+        self.enter(Section::File, "FILE")?;
+        self.write_record(record)?;
+        if self.strict_file_ids {
+            self.file_ids.insert(record.id);
+        }
+        Ok(())
+    }
+
+    /// Writes a `FUNC` record without its line records.
+    ///
+    /// Line records of this function can be written with [`write_line`](Self::write_line) until
+    /// the next record of another type is written.
+    pub fn write_func(&mut self, record: &BreakpadFuncRecord<'_>) -> Result<(), BreakpadError> {
+        self.enter(Section::Func, "FUNC")?;
+        self.write_record(record)?;
+        self.func_address = Some(record.address);
+        Ok(())
+    }
+
+    /// Writes a line record of the previously written `FUNC` record.
+    pub fn write_line(&mut self, record: &BreakpadLineRecord) -> Result<(), BreakpadError> {
+        let function_address = match self.func_address {
+            Some(address) => address,
+            None => {
+                let previous = self.previous;
+                let record = "line";
+                return Err(BreakpadErrorKind::RecordOutOfOrder { record, previous }.into());
+            }
+        };
+
+        if self.strict_file_ids && !self.file_ids.contains(&record.file_id) {
+            return Err(BreakpadErrorKind::UnknownFileId {
+                function_address,
+                line_address: record.address,
+                file_id: record.file_id,
+            }
+            .into());
+        }
+
+        self.write_record(record)?;
+        self.previous = Some("line");
+        Ok(())
+    }
+
+    /// Writes a `PUBLIC` record.
+    pub fn write_public(&mut self, record: &BreakpadPublicRecord<'_>) -> Result<(), BreakpadError> {
+        self.enter(Section::Public, "PUBLIC")?;
+        self.write_record(record)?;
+        Ok(())
+    }
+
+    /// Writes a `STACK CFI INIT` record without its delta records.
+    ///
+    /// Delta records of this record can be written with
+    /// [`write_stack_cfi_delta`](Self::write_stack_cfi_delta) until the next record of another
+    /// type is written.
+    pub fn write_stack_cfi(
+        &mut self,
+        record: &BreakpadStackCfiRecord<'_>,
+    ) -> Result<(), BreakpadError> {
+        self.enter(Section::Stack, "STACK CFI INIT")?;
+        self.write_record(record)?;
+        self.in_cfi = true;
+        Ok(())
+    }
+
+    /// Writes a `STACK CFI` delta record of the previously written `STACK CFI INIT` record.
+    pub fn write_stack_cfi_delta(
+        &mut self,
+        record: &BreakpadStackCfiDeltaRecord<'_>,
+    ) -> Result<(), BreakpadError> {
+        if !self.in_cfi {
+            let previous = self.previous;
+            let record = "STACK CFI";
+            return Err(BreakpadErrorKind::RecordOutOfOrder { record, previous }.into());
+        }
+
+        self.write_record(record)?;
+        self.previous = Some("STACK CFI");
+        Ok(())
+    }
+
+    /// Writes a `STACK WIN` record.
+    pub fn write_stack_win(
+        &mut self,
+        record: &BreakpadStackWinRecord<'_>,
+    ) -> Result<(), BreakpadError> {
+        self.enter(Section::Stack, "STACK WIN")?;
+        self.write_record(record)?;
+        Ok(())
+    }
+}
@@ -9,7 +9,6 @@ use std::fmt;
 use core::cmp;
 use flate2::{Decompress, FlushDecompress};
 use goblin::elf::compression_header::{CompressionHeader, ELFCOMPRESS_ZLIB};
-use goblin::elf::SectionHeader;
 use goblin::elf64::sym::SymIterator;
 use goblin::strtab::Strtab;
 use goblin::{
@@ -186,10 +185,10 @@ impl<'data> ElfObject<'data> {
         }
 
         obj.section_headers =
-            SectionHeader::parse(data, header.e_shoff as usize, header.e_shnum as usize, ctx)
+            elf::SectionHeader::parse(data, header.e_shoff as usize, header.e_shnum as usize, ctx)
                 .map_err(|_| ElfError::new("unable to parse section headers"))?;
 
-        let get_strtab = |section_headers: &[SectionHeader], section_idx: usize| {
+        let get_strtab = |section_headers: &[elf::SectionHeader], section_idx: usize| {
             if section_idx >= section_headers.len() {
                 // FIXME: warn! here
                 Ok(Strtab::default())
@@ -552,6 +551,103 @@ impl<'data> ElfObject<'data> {
         self.data
     }
 
+    /// Returns an iterator over the headers of all sections in this ELF file.
+    pub fn section_headers(&self) -> impl Iterator<Item = SectionHeader<'data>> + '_ {
+        self.elf
+            .section_headers
+            .iter()
+            .map(move |header| SectionHeader {
+                name: self
+                    .elf
+                    .shdr_strtab
+                    .get_at(header.sh_name)
+                    .unwrap_or_default(),
+                virtual_addr: header.sh_addr,
+                file_offset: header.sh_offset as usize,
+                size: header.sh_size as usize,
+            })
+    }
+
+    /// Applies relocations to a DWARF section of a relocatable object file.
+    ///
+    /// In relocatable object files (`.o`), references between DWARF sections and to code are
+    /// stored as relocations in `.rela.debug_*` or `.rel.debug_*` sections, and the section data
+    /// only contains placeholders. This resolves the relocations targeting the given section using
+    /// the symbol table and returns the relocated data. The section must have been obtained from
+    /// this object, for instance through [`Dwarf::section`]. Compressed sections must have been
+    /// decompressed.
+    ///
+    /// Absolute relocations are supported on x86, x86_64 and AArch64. Other relocations are not
+    /// applied. Returns `None` if the section does not exist in this object.
+    pub fn relocate_dwarf_section(&self, section: DwarfSection<'data>) -> Option<Vec<u8>> {
+        let index = self.elf.section_headers.iter().position(|header| {
+            // Empty sections may share their offset with the following section.
+            header.sh_offset == section.offset
+                && header.sh_offset != 0
+                && header.sh_size != 0
+                && header.sh_type != elf::section_header::SHT_NOBITS
+        })?;
+
+        let mut data = section.data.into_owned();
+        let machine = self.elf.header.e_machine;
+        for (relocs_index, relocs) in &self.elf.shdr_relocs {
+            match self.elf.section_headers.get(*relocs_index) {
+                Some(header) if header.sh_info as usize == index => {}
+                _ => continue,
+            }
+
+            for reloc in relocs.iter() {
+                let width = match (machine, reloc.r_type) {
+                    (elf::header::EM_X86_64, elf::reloc::R_X86_64_64) => 8,
+                    (elf::header::EM_X86_64, elf::reloc::R_X86_64_32)
+                    | (elf::header::EM_X86_64, elf::reloc::R_X86_64_32S) => 4,
+                    (elf::header::EM_386, elf::reloc::R_386_32) => 4,
+                    (elf::header::EM_AARCH64, elf::reloc::R_AARCH64_ABS64) => 8,
+                    (elf::header::EM_AARCH64, elf::reloc::R_AARCH64_ABS32) => 4,
+                    _ => continue,
+                };
+
+                let offset = reloc.r_offset as usize;
+                let target = match data.get_mut(offset..offset.saturating_add(width)) {
+                    Some(target) => target,
+                    None => continue,
+                };
+
+                // REL relocations store their addend in the section data.
+                let addend = match reloc.r_addend {
+                    Some(addend) => addend as u64,
+                    None => self.read_reloc_value(target),
+                };
+                let symbol = self.elf.syms.get(reloc.r_sym).map_or(0, |sym| sym.st_value);
+                self.write_reloc_value(target, symbol.wrapping_add(addend));
+            }
+        }
+
+        Some(data)
+    }
+
+    /// Reads a 4 or 8 byte value in the byte order of this object.
+    fn read_reloc_value(&self, bytes: &[u8]) -> u64 {
+        let mut buf = [0; 8];
+        if self.elf.little_endian {
+            buf[..bytes.len()].copy_from_slice(bytes);
+            u64::from_le_bytes(buf)
+        } else {
+            buf[8 - bytes.len()..].copy_from_slice(bytes);
+            u64::from_be_bytes(buf)
+        }
+    }
+
+    /// Writes a value as 4 or 8 bytes in the byte order of this object, truncating it if needed.
+    fn write_reloc_value(&self, target: &mut [u8], value: u64) {
+        let width = target.len();
+        if self.elf.little_endian {
+            target.copy_from_slice(&value.to_le_bytes()[..width]);
+        } else {
+            target.copy_from_slice(&value.to_be_bytes()[8 - width..]);
+        }
+    }
+
     /// Decompresses the given compressed section data, if supported.
     fn decompress_section(&self, section_data: &[u8]) -> Option<Vec<u8>> {
         let (size, compressed) = if section_data.starts_with(b"ZLIB") {
@@ -844,7 +940,7 @@ impl<'data, 'object> Iterator for ElfSymbolIterator<'data, 'object> {
             symbols: &mut SymIterator,
             strtab: &Strtab<'data>,
             load_addr: u64,
-            sections: &[SectionHeader],
+            sections: &[elf::SectionHeader],
         ) -> Option<Symbol<'data>> {
             for symbol in symbols {
                 // Only check for function symbols.
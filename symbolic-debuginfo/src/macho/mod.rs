@@ -342,6 +342,56 @@ impl<'d> MachObject<'d> {
         self.data
     }
 
+    /// Returns an iterator over the headers of all sections in all segments of this MachO file.
+    pub fn section_headers(&self) -> impl Iterator<Item = SectionHeader<'d>> + '_ {
+        use mach::load_command::{self, CommandVariant};
+
+        // Goblin copies section names out of the file, so they are read from the raw section
+        // headers that directly follow each segment load command instead.
+        let layouts = self
+            .macho
+            .load_commands
+            .iter()
+            .filter_map(|command| match command.command {
+                CommandVariant::Segment32(_) => Some((
+                    command.offset + load_command::SIZEOF_SEGMENT_COMMAND_32,
+                    load_command::SIZEOF_SECTION_32,
+                )),
+                CommandVariant::Segment64(_) => Some((
+                    command.offset + load_command::SIZEOF_SEGMENT_COMMAND_64,
+                    load_command::SIZEOF_SECTION_64,
+                )),
+                _ => None,
+            });
+
+        let data = self.data;
+        self.macho.segments.iter().zip(layouts).flat_map(
+            move |(segment, (offset, section_size))| {
+                let sections = segment.sections().unwrap_or_default();
+                sections
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(index, (header, _))| {
+                        let start = offset + index * section_size;
+                        let name = data
+                            .get(start..start + 16)
+                            .map(|raw| {
+                                let len = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+                                std::str::from_utf8(&raw[..len]).unwrap_or_default()
+                            })
+                            .unwrap_or_default();
+
+                        SectionHeader {
+                            name,
+                            virtual_addr: header.addr,
+                            file_offset: header.offset as usize,
+                            size: header.size as usize,
+                        }
+                    })
+            },
+        )
+    }
+
     /// Checks whether this mach object contains hidden symbols.
     ///
     /// This is an indication that BCSymbolMaps are needed to symbolicate crash reports correctly.
@@ -351,6 +351,29 @@ impl<'data> Object<'data> {
     pub fn data(&self) -> &'data [u8] {
         match_inner!(self, Object(ref o) => o.data())
     }
+
+    /// Returns an iterator over the headers of all sections in this object.
+    ///
+    /// This yields sections for ELF and MachO objects, regardless of the file format. For all other
+    /// object file formats, the iterator is empty.
+    pub fn section_headers(&self) -> DynIterator<'_, SectionHeader<'data>> {
+        match *self {
+            Object::Elf(ref o) => Box::new(o.section_headers()),
+            Object::MachO(ref o) => Box::new(o.section_headers()),
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Applies relocations to a DWARF section of a relocatable object file.
+    ///
+    /// This is only supported for ELF objects. See [`ElfObject::relocate_dwarf_section`] for more
+    /// information. Returns `None` for all other object file formats.
+    pub fn relocate_dwarf_section(&self, section: DwarfSection<'data>) -> Option<Vec<u8>> {
+        match *self {
+            Object::Elf(ref o) => o.relocate_dwarf_section(section),
+            _ => None,
+        }
+    }
 }
 
 impl<'slf, 'data: 'slf> AsSelf<'slf> for Object<'data> {
@@ -1,7 +1,13 @@
 use std::{ffi::CString, fmt};
 
 use symbolic_common::ByteView;
-use symbolic_debuginfo::{elf::ElfObject, FileEntry, Function, Object, SymbolMap};
+use symbolic_debuginfo::{
+    breakpad::{
+        BreakpadErrorKind, BreakpadIndex, BreakpadObject, BreakpadStackRecord, BreakpadStreamWriter,
+    },
+    elf::ElfObject,
+    FileEntry, Function, Object, SymbolMap,
+};
 use symbolic_testutils::fixture;
 
 use similar_asserts::assert_eq;
@@ -104,6 +110,39 @@ fn test_breakpad() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_breakpad_bom() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.sym"))?;
+    let object = Object::parse(&view)?;
+    let bom_view = ByteView::open(fixture("windows/crash_bom.sym"))?;
+    let bom_object = Object::parse(&bom_view)?;
+
+    assert_eq!(format!("{:?}", bom_object), format!("{:?}", object));
+    assert_eq!(
+        format!("{:?}", SymbolsDebug(&bom_object.symbol_map())),
+        format!("{:?}", SymbolsDebug(&object.symbol_map()))
+    );
+
+    let session = object.debug_session()?;
+    let bom_session = bom_object.debug_session()?;
+
+    let files = session.files().collect::<Result<Vec<_>, _>>()?;
+    let bom_files = bom_session.files().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        format!("{:?}", FilesDebug(&bom_files)),
+        format!("{:?}", FilesDebug(&files))
+    );
+
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    let bom_functions = bom_session.functions().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        format!("{:?}", FunctionsDebug(&bom_functions, 0)),
+        format!("{:?}", FunctionsDebug(&functions, 0))
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_breakpad_symbols() -> Result<(), Error> {
     let view = ByteView::open(fixture("windows/crash.sym"))?;
@@ -140,6 +179,198 @@ fn test_breakpad_functions() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_breakpad_stream_writer() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash.sym"))?;
+    let object = BreakpadObject::parse(&view)?;
+
+    let mut writer = BreakpadStreamWriter::new(Vec::new()).strict_file_ids();
+    let mut written = 0;
+    writer.write_module(object.module_record())?;
+    for record in object.info_records() {
+        writer.write_info(&record?)?;
+        written += 1;
+    }
+    for record in object.file_records() {
+        writer.write_file(&record?)?;
+        written += 1;
+    }
+    for record in object.func_records() {
+        let record = record?;
+        writer.write_func(&record)?;
+        for line in record.lines() {
+            writer.write_line(&line?)?;
+            written += 1;
+        }
+        written += 1;
+    }
+    for record in object.public_records() {
+        writer.write_public(&record?)?;
+        written += 1;
+    }
+    for record in object.stack_records() {
+        match record? {
+            BreakpadStackRecord::Cfi(record) => {
+                writer.write_stack_cfi(&record)?;
+                for delta in record.deltas() {
+                    writer.write_stack_cfi_delta(&delta?)?;
+                    written += 1;
+                }
+            }
+            BreakpadStackRecord::Win(record) => writer.write_stack_win(&record)?,
+        }
+        written += 1;
+    }
+    assert!(written > 5000);
+
+    let data = writer.into_inner();
+    let streamed = BreakpadObject::parse(&data)?;
+    assert_eq!(streamed.module_record(), object.module_record());
+
+    let render = |object: &BreakpadObject<'_>| -> Result<Vec<String>, Error> {
+        let mut records = Vec::new();
+        for record in object.info_records() {
+            records.push(record?.to_string());
+        }
+        for record in object.file_records() {
+            records.push(record?.to_string());
+        }
+        for record in object.func_records() {
+            let record = record?;
+            records.push(record.to_string());
+            for line in record.lines() {
+                records.push(line?.to_string());
+            }
+        }
+        for record in object.public_records() {
+            records.push(record?.to_string());
+        }
+        for record in object.stack_records() {
+            records.push(record?.to_string());
+        }
+        Ok(records)
+    };
+    assert_eq!(render(&streamed)?, render(&object)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_breakpad_index_persistence() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.sym"))?;
+    let object = BreakpadObject::parse(&view)?;
+    let index = object.index();
+
+    let mut persisted = Vec::new();
+    index.serialize(&mut persisted)?;
+    let loaded = BreakpadIndex::load(&persisted, &object)?;
+
+    assert_eq!(loaded.file_map(), index.file_map());
+    for address in (0..0x20000).step_by(0x13) {
+        assert_eq!(loaded.function_at(address), index.function_at(address));
+        assert_eq!(loaded.public_at(address), index.public_at(address));
+        assert_eq!(loaded.lookup(address), index.lookup(address));
+        assert_eq!(
+            loaded.stack_record_at(address),
+            index.stack_record_at(address)
+        );
+    }
+    assert!(loaded.lookup(0x1010).is_some());
+
+    // An index for modified data is rejected.
+    let mut modified = view.to_vec();
+    modified.extend_from_slice(b"PUBLIC 1 0 appended\n");
+    let modified = BreakpadObject::parse(&modified)?;
+    let error = BreakpadIndex::load(&persisted, &modified).unwrap_err();
+    assert_eq!(error.kind(), BreakpadErrorKind::InvalidIndex);
+
+    // An index for data of the same size is rejected if a single address digit differs.
+    let mut modified = view.to_vec();
+    let func = modified
+        .windows(10)
+        .position(|window| window == b"\nFUNC 1000")
+        .unwrap();
+    modified[func + 6] = b'2';
+    let modified = BreakpadObject::parse(&modified)?;
+    let error = BreakpadIndex::load(&persisted, &modified).unwrap_err();
+    assert_eq!(error.kind(), BreakpadErrorKind::InvalidIndex);
+
+    // An index for another module is rejected.
+    let other_view = ByteView::open(fixture("linux/crash.sym"))?;
+    let other = BreakpadObject::parse(&other_view)?;
+    let error = BreakpadIndex::load(&persisted, &other).unwrap_err();
+    assert_eq!(error.kind(), BreakpadErrorKind::InvalidIndex);
+
+    // Truncated and corrupted indexes are rejected.
+    let error = BreakpadIndex::load(&persisted[..persisted.len() - 1], &object).unwrap_err();
+    assert_eq!(error.kind(), BreakpadErrorKind::InvalidIndex);
+    let mut corrupted = persisted.clone();
+    let last_offset = corrupted.len() - 8;
+    corrupted[last_offset] ^= 1;
+    let error = BreakpadIndex::load(&corrupted, &object).unwrap_err();
+    assert_eq!(error.kind(), BreakpadErrorKind::InvalidIndex);
+
+    Ok(())
+}
+
+/// Appends a raw record line returned by a Breakpad iterator, followed by a line break.
+fn push_raw_line(buffer: &mut Vec<u8>, line: Option<&[u8]>) {
+    buffer.extend_from_slice(line.expect("raw line of the last record"));
+    buffer.push(b'\n');
+}
+
+#[test]
+fn test_breakpad_raw_records() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash.sym"))?;
+    let object = BreakpadObject::parse(&view)?;
+
+    // Copy all records verbatim, dropping PUBLIC records.
+    let mut rebuilt = format!("{}\n", object.module_record()).into_bytes();
+
+    let mut files = object.file_records();
+    while let Some(record) = files.next() {
+        record?;
+        push_raw_line(&mut rebuilt, files.raw());
+    }
+
+    let mut funcs = object.func_records();
+    while let Some(record) = funcs.next() {
+        let record = record?;
+        push_raw_line(&mut rebuilt, funcs.raw());
+
+        let mut lines = record.lines();
+        while let Some(line) = lines.next() {
+            line?;
+            push_raw_line(&mut rebuilt, lines.raw());
+        }
+    }
+
+    let mut stack = object.stack_records();
+    while let Some(record) = stack.next() {
+        let record = record?;
+        push_raw_line(&mut rebuilt, stack.raw());
+
+        if let BreakpadStackRecord::Cfi(record) = record {
+            let mut deltas = record.deltas();
+            while let Some(delta) = deltas.next() {
+                delta?;
+                push_raw_line(&mut rebuilt, deltas.raw());
+            }
+        }
+    }
+
+    let expected: Vec<u8> = view
+        .split_inclusive(|&b| b == b'\n')
+        .filter(|line| !line.starts_with(b"PUBLIC "))
+        .flatten()
+        .copied()
+        .collect();
+
+    assert_eq!(rebuilt, expected);
+
+    Ok(())
+}
+
 #[test]
 fn test_elf_executable() -> Result<(), Error> {
     let view = ByteView::open(fixture("linux/crash"))?;
@@ -257,6 +488,58 @@ fn check_debug_info(filename: &'static str, debug_info: &'static str) -> Result<
     Ok(())
 }
 
+#[test]
+fn test_elf_section_headers() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash"))?;
+    let object = Object::parse(&view)?;
+
+    let text = object
+        .section_headers()
+        .find(|header| header.name == ".text")
+        .expect("text section");
+    assert_ne!(text.virtual_addr, 0);
+    assert_ne!(text.size, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_relocate_dwarf_section() -> Result<(), Error> {
+    use gimli::{constants, AttributeValue, DebugAbbrev, DebugInfo, DebugStr, LittleEndian};
+    use symbolic_debuginfo::dwarf::Dwarf;
+
+    let view = ByteView::open(fixture("linux/relocatable.o"))?;
+    let object = ElfObject::parse(&view)?;
+
+    let raw_info = object.section("debug_info").expect("debug_info section");
+    let relocated_info = object
+        .relocate_dwarf_section(raw_info.clone())
+        .expect("relocated debug_info");
+    assert_eq!(relocated_info.len(), raw_info.data.len());
+    assert_ne!(relocated_info, &raw_info.data[..]);
+
+    let abbrev = object
+        .section("debug_abbrev")
+        .expect("debug_abbrev section");
+    let strings = object.section("debug_str").expect("debug_str section");
+    let debug_info = DebugInfo::new(&relocated_info, LittleEndian);
+    let debug_abbrev = DebugAbbrev::new(&abbrev.data, LittleEndian);
+    let debug_str = DebugStr::new(&strings.data, LittleEndian);
+
+    let unit = debug_info.units().next()?.expect("compilation unit");
+    let abbreviations = unit.abbreviations(&debug_abbrev)?;
+    let mut entries = unit.entries(&abbreviations);
+    let (_, entry) = entries.next_dfs()?.expect("unit entry");
+
+    let name = match entry.attr_value(constants::DW_AT_name)? {
+        Some(AttributeValue::DebugStrRef(offset)) => debug_str.get_str(offset)?,
+        other => panic!("unexpected name attribute {:?}", other),
+    };
+    assert_eq!(name.to_string()?, "relocatable.c");
+
+    Ok(())
+}
+
 #[test]
 fn test_elf_debug_link() -> Result<(), Error> {
     check_debug_info("elf_with_debuglink", "debug_info.txt")
@@ -347,6 +630,22 @@ fn test_mach_dsym() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_mach_section_headers() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash"))?;
+    let object = Object::parse(&view)?;
+
+    let text = object
+        .section_headers()
+        .find(|header| header.name == "__text")
+        .expect("text section");
+    assert_ne!(text.virtual_addr, 0);
+    assert_ne!(text.file_offset, 0);
+    assert_ne!(text.size, 0);
+
+    Ok(())
+}
+
 #[test]
 fn test_mach_symbols() -> Result<(), Error> {
     let view = ByteView::open(fixture("macos/crash"))?;
@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use symbolic_unwind::evaluator::conversion::win_program_to_cfi_rules;
+use symbolic_unwind::evaluator::{Constant, Evaluator, Expr, Variable};
+use symbolic_unwind::{LittleEndian, MemoryRegion};
+
+/// Rules converted from a `STACK WIN` program that restores four registers relative to `$T0`.
+///
+/// Substituting `$T0` makes all rules repeat the subexpression computing it.
+const WIN_PROGRAM: &str = "$T0 $esp .cbSavedRegs + .cbLocals + = $eip $T0 ^ = \
+    $esp $T0 4 + = $ebx $T0 4 - ^ = $esi $T0 8 - ^ = $edi $T0 12 - ^ = $ebp $T0 16 - ^ =";
+
+pub fn evaluator(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Evaluator benchmarks");
+
+    let mut constants = BTreeMap::new();
+    constants.insert(".cbSavedRegs".parse::<Constant>().unwrap(), 16u32);
+    constants.insert(".cbLocals".parse::<Constant>().unwrap(), 32u32);
+    let exprs: Vec<Expr<u32>> = win_program_to_cfi_rules(WIN_PROGRAM, &constants)
+        .unwrap()
+        .iter()
+        .map(|rule| {
+            let rule = rule.to_string();
+            let (_, expr) = rule.split_once(": ").unwrap();
+            expr.parse().unwrap()
+        })
+        .collect();
+
+    let stack = [0u8; 0x100];
+    let memory = MemoryRegion {
+        base_addr: 0x1000,
+        contents: &stack,
+    };
+    let mut variables = BTreeMap::new();
+    variables.insert("$esp".parse::<Variable>().unwrap(), 0x1000u32);
+    let evaluator = Evaluator::new(LittleEndian)
+        .memory(memory)
+        .variables(variables);
+
+    group.bench_with_input(
+        BenchmarkId::new("evaluate", "converted win rules"),
+        &exprs,
+        |b, exprs| {
+            b.iter(|| {
+                for expr in exprs {
+                    evaluator.evaluate(expr).unwrap();
+                }
+            })
+        },
+    );
+
+    group.bench_with_input(
+        BenchmarkId::new("evaluate_expr_list", "converted win rules"),
+        &exprs,
+        |b, exprs| {
+            b.iter(|| {
+                for result in evaluator.evaluate_expr_list(exprs) {
+                    result.unwrap();
+                }
+            })
+        },
+    );
+
+    group.finish();
+}
+
+criterion_group!(benches, evaluator);
+criterion_main!(benches);
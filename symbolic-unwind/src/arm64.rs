@@ -0,0 +1,156 @@
+//! Unwinding functionality specific to AArch64.
+//!
+//! When code is compiled with frame pointers (e.g. `-fno-omit-frame-pointer`), the frame
+//! pointer register `x29` points to a frame record on the stack. The frame record consists of
+//! the caller's frame pointer followed by the return address:
+//! ```text
+//! x29 + 8: saved x30 (link register, return address)
+//! x29 + 0: saved x29 (caller's frame pointer)
+//! ```
+//! This allows unwinding a frame without any CFI information, which makes it the most reliable
+//! fallback if `STACK CFI` records are missing.
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+use super::base::{Endianness, MemoryRegion};
+use super::evaluator::Constant;
+
+/// The size of a frame record, consisting of the saved frame pointer and link register.
+const FRAME_RECORD_SIZE: u64 = 16;
+
+/// An error encountered while unwinding a frame with [`frame_pointer_unwind`].
+#[derive(Debug)]
+enum FramePointerErrorInner {
+    /// A register required for unwinding is not in the register map.
+    MissingRegister(&'static str),
+
+    /// The frame record is not contained in the memory region.
+    IllegalMemoryAccess {
+        /// The address at which the read was attempted.
+        address: u64,
+        /// The range of available addresses.
+        address_range: Range<u64>,
+    },
+}
+
+impl fmt::Display for FramePointerErrorInner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingRegister(register) => write!(f, "Register {} is not defined", register),
+            Self::IllegalMemoryAccess {
+                address,
+                address_range,
+            } => write!(
+                f,
+                "Tried to read a frame record at memory address {}. The available address range is [{}, {})",
+                address, address_range.start, address_range.end
+            ),
+        }
+    }
+}
+
+/// An error encountered while unwinding a frame with [`frame_pointer_unwind`].
+#[derive(Debug)]
+pub struct FramePointerError(FramePointerErrorInner);
+
+impl fmt::Display for FramePointerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for FramePointerError {}
+
+/// Computes the caller's registers by following the frame pointer.
+///
+/// This reads the frame record at `x29` from `memory` and returns a map containing the caller's
+/// frame pointer `x29`, its return address `x30`, and its stack pointer `sp`, which is the
+/// address right after the frame record. All other registers cannot be recovered this way and
+/// are not contained in the result.
+///
+/// A caller frame pointer of `0` usually denotes the outermost frame. It is up to the caller to
+/// stop unwinding in this case, as well as to detect frame pointers that do not move up the stack.
+pub fn frame_pointer_unwind<E: Endianness>(
+    registers: &BTreeMap<Constant, u64>,
+    memory: &MemoryRegion<'_>,
+    endian: E,
+) -> Result<BTreeMap<Constant, u64>, FramePointerError> {
+    let fp = registers
+        .get(&register("x29"))
+        .copied()
+        .ok_or(FramePointerError(FramePointerErrorInner::MissingRegister(
+            "x29",
+        )))?;
+
+    let read = |address: u64| {
+        memory.get(address, endian).ok_or_else(|| {
+            FramePointerError(FramePointerErrorInner::IllegalMemoryAccess {
+                address,
+                address_range: memory.base_addr..memory.base_addr + memory.len() as u64,
+            })
+        })
+    };
+
+    let sp = fp.checked_add(FRAME_RECORD_SIZE).ok_or_else(|| {
+        FramePointerError(FramePointerErrorInner::IllegalMemoryAccess {
+            address: fp,
+            address_range: memory.base_addr..memory.base_addr + memory.len() as u64,
+        })
+    })?;
+    let caller_fp = read(fp)?;
+    let caller_lr = read(fp + 8)?;
+
+    let mut caller_registers = BTreeMap::new();
+    caller_registers.insert(register("x29"), caller_fp);
+    caller_registers.insert(register("x30"), caller_lr);
+    caller_registers.insert(register("sp"), sp);
+    Ok(caller_registers)
+}
+
+/// Returns the constant for the register with the given name.
+fn register(name: &str) -> Constant {
+    Constant(name.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::base::LittleEndian;
+
+    #[test]
+    fn unwind_frame_record() {
+        let mut contents = vec![0u8; 32];
+        contents[16..24].copy_from_slice(&0x1020u64.to_le_bytes());
+        contents[24..32].copy_from_slice(&0xdead_beefu64.to_le_bytes());
+        let memory = MemoryRegion {
+            base_addr: 0x1000,
+            contents: &contents,
+        };
+
+        let mut registers = BTreeMap::new();
+        registers.insert(register("x29"), 0x1010);
+        registers.insert(register("sp"), 0x1000);
+
+        let caller = frame_pointer_unwind(&registers, &memory, LittleEndian).unwrap();
+        assert_eq!(caller[&register("x29")], 0x1020);
+        assert_eq!(caller[&register("x30")], 0xdead_beef);
+        assert_eq!(caller[&register("sp")], 0x1020);
+    }
+
+    #[test]
+    fn unwind_errors() {
+        let memory = MemoryRegion {
+            base_addr: 0x1000,
+            contents: &[0u8; 16],
+        };
+
+        let registers = BTreeMap::new();
+        assert!(frame_pointer_unwind(&registers, &memory, LittleEndian).is_err());
+
+        let mut registers = BTreeMap::new();
+        registers.insert(register("x29"), 0x1008);
+        assert!(frame_pointer_unwind(&registers, &memory, LittleEndian).is_err());
+    }
+}
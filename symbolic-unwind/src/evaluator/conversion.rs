@@ -0,0 +1,459 @@
+//! Conversion of `STACK WIN` program strings to `STACK CFI` rules.
+//!
+//! Breakpad symbol files for 32-bit Windows modules describe unwinding with `STACK WIN` records of
+//! type `FrameData`, whose program strings are sequences of [assignments](super::Assignment).
+//! Many of these programs only compute the caller's registers from the callee's registers and
+//! memory, and can be expressed as [CFI rules](super::Rule) instead:
+//!
+//! - The final value of `$eip` becomes the `.ra` rule.
+//! - The final value of `$esp` becomes the `.cfa` rule.
+//! - The final values of all other registers become rules for that register.
+//! - Temporaries, such as `$T0`, are substituted into the expressions that use them.
+//! - The pseudo-registers `$L` and `$P`, which MSVC programs use for the base of the locals and
+//!   parameters, are substituted like temporaries and do not become rules.
+//!
+//! Programs that search the stack for the return address using `.raSearch` or `.raSearchStart`
+//! cannot be expressed as CFI rules, since the result depends on the contents of the stack.
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+#[cfg(feature = "breakpad")]
+use symbolic_debuginfo::breakpad::BreakpadStackWinRecord;
+
+use super::parsing::{self, ParseExprError};
+use super::{Constant, Expr, Identifier, Rule, Variable};
+use crate::base::RegisterValue;
+
+/// The kind of a [`ConversionError`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ConversionErrorInner {
+    /// The program string could not be parsed.
+    Parsing(ParseExprError),
+
+    /// The program uses a constant that has no value, such as `.raSearch`.
+    UnsupportedConstant(Constant),
+
+    /// The program does not assign the given register.
+    MissingRegister(&'static str),
+
+    /// The `STACK WIN` record has no program string.
+    #[cfg(feature = "breakpad")]
+    MissingProgram,
+}
+
+impl fmt::Display for ConversionErrorInner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Parsing(e) => write!(f, "Error while parsing: {}", e),
+            Self::UnsupportedConstant(c) => {
+                write!(f, "Constant {} cannot be converted to a CFI rule", c)
+            }
+            Self::MissingRegister(r) => write!(f, "The program does not assign {}", r),
+            #[cfg(feature = "breakpad")]
+            Self::MissingProgram => write!(f, "The record does not contain a program string"),
+        }
+    }
+}
+
+/// An error returned when a `STACK WIN` program string cannot be converted to CFI rules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConversionError(ConversionErrorInner);
+
+impl ConversionError {
+    /// Returns true if the program was valid but cannot be expressed as CFI rules.
+    pub fn is_not_convertible(&self) -> bool {
+        !matches!(self.0, ConversionErrorInner::Parsing(_))
+    }
+}
+
+impl From<ParseExprError> for ConversionError {
+    fn from(other: ParseExprError) -> Self {
+        Self(ConversionErrorInner::Parsing(other))
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for ConversionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.0 {
+            ConversionErrorInner::Parsing(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Returns true if the variable is a temporary or pseudo-register of a `STACK WIN` program, such
+/// as `$T0`, `$L` or `$P`.
+fn is_temporary(var: &Variable) -> bool {
+    var.0.starts_with("$T") || matches!(&*var.0, "$L" | "$P")
+}
+
+/// Replaces variables and constants in an expression and folds operations on literal values.
+///
+/// Variables without an entry in `variables` keep referring to their value in the callee's frame.
+/// Constants must have an entry in `constants`.
+fn substitute<A: RegisterValue>(
+    expr: &Expr<A>,
+    variables: &BTreeMap<Variable, Expr<A>>,
+    constants: &BTreeMap<Constant, A>,
+) -> Result<Expr<A>, ConversionError> {
+    Ok(match expr {
+        Expr::Value(x) => Expr::Value(*x),
+        Expr::Var(v) => variables
+            .get(v)
+            .cloned()
+            .unwrap_or_else(|| Expr::Var(v.clone())),
+        Expr::Const(c) => match constants.get(c) {
+            Some(x) => Expr::Value(*x),
+            None => {
+                return Err(ConversionError(ConversionErrorInner::UnsupportedConstant(
+                    c.clone(),
+                )))
+            }
+        },
+        Expr::Op(e1, e2, op) => {
+            let e1 = substitute(e1, variables, constants)?;
+            let e2 = substitute(e2, variables, constants)?;
+            let folded = match (&e1, &e2) {
+                (Expr::Value(a), Expr::Value(b)) => op.apply(a, b),
+                _ => None,
+            };
+
+            match folded {
+                Some(x) => Expr::Value(x),
+                None => Expr::Op(Box::new(e1), Box::new(e2), *op),
+            }
+        }
+        Expr::Deref(address) => Expr::Deref(Box::new(substitute(address, variables, constants)?)),
+    })
+}
+
+/// Converts the program string of a `STACK WIN` `FrameData` record to equivalent CFI rules.
+///
+/// `constants` holds the values of constants the program may refer to, such as `.cbSavedRegs` or
+/// `.cbParams`, which are known from the `STACK WIN` record. The resulting rules only refer to the
+/// callee's registers, memory and the `.cfa`, so they can be passed to
+/// [`Evaluator::add_cfi_rule`](super::Evaluator::add_cfi_rule) or formatted into a `STACK CFI INIT`
+/// record.
+///
+/// Fails if the program cannot be parsed, uses a constant that is not in `constants`, such as
+/// `.raSearch`, or does not assign both `$eip` and `$esp`.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use symbolic_unwind::evaluator::conversion::win_program_to_cfi_rules;
+///
+/// let program = "$T0 $ebp = $eip $T0 4 + ^ = $ebp $T0 ^ = $esp $T0 8 + =";
+/// let rules = win_program_to_cfi_rules::<u32>(program, &BTreeMap::new()).unwrap();
+/// let rules: Vec<_> = rules.iter().map(ToString::to_string).collect();
+///
+/// assert_eq!(rules, [".cfa: $ebp 8 +", ".ra: $ebp 4 + ^", "$ebp: $ebp ^"]);
+/// ```
+pub fn win_program_to_cfi_rules<A: RegisterValue>(
+    program: &str,
+    constants: &BTreeMap<Constant, A>,
+) -> Result<Vec<Rule<A>>, ConversionError> {
+    let mut variables = BTreeMap::new();
+    for assignment in parsing::assignments_complete::<A>(program.trim())? {
+        let value = substitute(&assignment.1, &variables, constants)?;
+        variables.insert(assignment.0, value);
+    }
+
+    let mut take = |name: &'static str| {
+        variables
+            .remove(&Variable(name.into()))
+            .ok_or(ConversionError(ConversionErrorInner::MissingRegister(name)))
+    };
+
+    let mut rules = vec![
+        Rule(Identifier::Const(Constant::cfa()), take("$esp")?),
+        Rule(Identifier::Const(Constant::ra()), take("$eip")?),
+    ];
+
+    for (var, value) in variables {
+        if is_temporary(&var) || value == Expr::Var(var.clone()) {
+            continue;
+        }
+        rules.push(Rule(Identifier::Var(var), value));
+    }
+
+    Ok(rules)
+}
+
+/// Converts a `STACK WIN` `FrameData` record to equivalent CFI rules.
+///
+/// This works like [`win_program_to_cfi_rules`], but takes the values of the constants
+/// `.cbSavedRegs`, `.cbParams` and `.cbLocals` from the record. Fails if the record has no program
+/// string, such as records of type `FPO`.
+///
+/// # Example
+///
+/// ```
+/// use symbolic_debuginfo::breakpad::BreakpadStackWinRecord;
+/// use symbolic_unwind::evaluator::conversion::win_record_to_cfi_rules;
+///
+/// let record = BreakpadStackWinRecord::parse(
+///     b"STACK WIN 4 2170 14 1 0 0 0 8 0 1 $T0 $ebp = $eip $T0 4 + ^ = $ebp $T0 ^ = $esp $T0 8 + = $L $T0 .cbSavedRegs - =",
+/// )
+/// .unwrap();
+/// let rules = win_record_to_cfi_rules(&record).unwrap();
+/// let rules: Vec<_> = rules.iter().map(ToString::to_string).collect();
+///
+/// assert_eq!(rules, [".cfa: $ebp 8 +", ".ra: $ebp 4 + ^", "$ebp: $ebp ^"]);
+/// ```
+#[cfg(feature = "breakpad")]
+pub fn win_record_to_cfi_rules(
+    record: &BreakpadStackWinRecord<'_>,
+) -> Result<Vec<Rule<u32>>, ConversionError> {
+    let program = record
+        .program_string
+        .ok_or(ConversionError(ConversionErrorInner::MissingProgram))?;
+
+    let mut constants = BTreeMap::new();
+    constants.insert(
+        Constant(".cbSavedRegs".into()),
+        u32::from(record.saved_regs_size),
+    );
+    constants.insert(Constant(".cbParams".into()), record.params_size);
+    constants.insert(Constant(".cbLocals".into()), record.locals_size);
+
+    win_program_to_cfi_rules(program, &constants)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "breakpad")]
+    use crate::base::{LittleEndian, MemoryRegion};
+    #[cfg(feature = "breakpad")]
+    use crate::evaluator::{Assignment, Evaluator};
+
+    #[cfg(feature = "breakpad")]
+    use symbolic_debuginfo::breakpad::{
+        BreakpadObject, BreakpadStackCfiRecord, BreakpadStackRecord, BreakpadStackWinRecord,
+    };
+
+    /// Runs a `STACK WIN` program and returns the final values of all variables.
+    #[cfg(feature = "breakpad")]
+    fn run_program(
+        program: &str,
+        memory: MemoryRegion,
+        mut variables: BTreeMap<Variable, u32>,
+        constants: &BTreeMap<Constant, u32>,
+    ) -> BTreeMap<Variable, u32> {
+        let assignments: Vec<Assignment<u32>> = parsing::assignments_complete(program).unwrap();
+        for Assignment(var, expr) in assignments {
+            let value = Evaluator::new(LittleEndian)
+                .memory(memory)
+                .variables(variables.clone())
+                .constants(constants.clone())
+                .evaluate(&expr)
+                .unwrap();
+            variables.insert(var, value);
+        }
+        variables
+    }
+
+    #[cfg(feature = "breakpad")]
+    fn var(name: &str) -> Variable {
+        name.parse().unwrap()
+    }
+
+    /// Checks that the CFI rules compute the same caller registers as the `STACK WIN` program.
+    #[cfg(feature = "breakpad")]
+    fn assert_equivalent(
+        record: &BreakpadStackWinRecord,
+        rules: &[Rule<u32>],
+        memory: MemoryRegion,
+        callee: BTreeMap<Variable, u32>,
+    ) {
+        let rules_string = rules
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let cfi = BreakpadStackCfiRecord::from_rules(
+            record.code_start.into(),
+            record.code_size.into(),
+            &rules_string,
+        )
+        .unwrap();
+
+        let mut constants = BTreeMap::new();
+        constants.insert(
+            ".cbSavedRegs".parse().unwrap(),
+            u32::from(record.saved_regs_size),
+        );
+        constants.insert(".cbParams".parse().unwrap(), record.params_size);
+        constants.insert(".cbLocals".parse().unwrap(), record.locals_size);
+        let program = record.program_string.unwrap();
+        let win = run_program(program, memory, callee.clone(), &constants);
+
+        let mut evaluator = Evaluator::new(LittleEndian)
+            .memory(memory)
+            .variables(callee);
+        evaluator.add_cfi_rules_string(cfi.init_rules).unwrap();
+        let caller = evaluator.evaluate_cfi_rules().unwrap();
+
+        assert_eq!(
+            caller[&Identifier::Const(Constant::cfa())],
+            win[&var("$esp")]
+        );
+        assert_eq!(
+            caller[&Identifier::Const(Constant::ra())],
+            win[&var("$eip")]
+        );
+        for (identifier, value) in &caller {
+            if let Identifier::Var(register) = identifier {
+                assert_eq!(*value, win[register], "{}", register);
+            }
+        }
+    }
+
+    #[cfg(feature = "breakpad")]
+    fn win_record(symbols: &[u8]) -> BreakpadStackWinRecord<'_> {
+        let object = BreakpadObject::parse(symbols).unwrap();
+        match object.stack_records().next().unwrap().unwrap() {
+            BreakpadStackRecord::Win(record) => record,
+            BreakpadStackRecord::Cfi(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "breakpad")]
+    fn frame_data_docs_example() {
+        // The example from the Breakpad symbol file documentation, verbatim. Its first assignment
+        // lacks an operand, which makes the program invalid.
+        let symbols = b"MODULE windows x86 5A9832E5287241C1838ED98914E9B7FF1 crash.pdb
+STACK WIN 4 2170 14 1 0 0 0 0 0 1 $eip 4 + ^ = $esp $ebp 8 + = $ebp $ebp ^ =
+";
+        let record = win_record(symbols);
+        let err = win_record_to_cfi_rules(&record).unwrap_err();
+        assert!(!err.is_not_convertible());
+    }
+
+    #[test]
+    #[cfg(feature = "breakpad")]
+    fn frame_data_example() {
+        let symbols = b"MODULE windows x86 5A9832E5287241C1838ED98914E9B7FF1 crash.pdb
+STACK WIN 4 2170 14 1 0 0 0 0 0 1 $T0 $ebp = $eip $T0 4 + ^ = $ebp $T0 ^ = $esp $T0 8 + =
+";
+        let record = win_record(symbols);
+        let rules = win_record_to_cfi_rules(&record).unwrap();
+        let rules_string: Vec<_> = rules.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            rules_string,
+            [".cfa: $ebp 8 +", ".ra: $ebp 4 + ^", "$ebp: $ebp ^"]
+        );
+
+        // The callee's frame pointer points to the saved frame pointer, followed by the return
+        // address.
+        let stack: Vec<u8> = [0x9000u32, 0x4321, 0, 0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let memory = MemoryRegion {
+            base_addr: 0x8000,
+            contents: &stack,
+        };
+
+        let mut callee = BTreeMap::new();
+        callee.insert(var("$eip"), 0x2178u32);
+        callee.insert(var("$esp"), 0x7ff0);
+        callee.insert(var("$ebp"), 0x8000);
+
+        assert_equivalent(&record, &rules, memory, callee);
+    }
+
+    #[test]
+    #[cfg(feature = "breakpad")]
+    fn msvc_frame_data() {
+        // Programs emitted for MSVC binaries compute `$L` and `$P` from the record's sizes.
+        let symbols = b"MODULE windows x86 5A9832E5287241C1838ED98914E9B7FF1 crash.pdb
+STACK WIN 4 2170 14 1 0 8 4 10 0 1 $T0 $esp .cbSavedRegs + .cbLocals + = $eip $T0 ^ = $esp $T0 4 + = $ebx $T0 4 - ^ = $L $T0 .cbLocals - = $P $T0 4 + .cbParams + =
+";
+        let record = win_record(symbols);
+        let rules = win_record_to_cfi_rules(&record).unwrap();
+        let rules_string: Vec<_> = rules.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            rules_string,
+            [
+                ".cfa: $esp 4 + 16 + 4 +",
+                ".ra: $esp 4 + 16 + ^",
+                "$ebx: $esp 4 + 16 + 4 - ^"
+            ]
+        );
+
+        // The callee's stack holds the locals, the saved `$ebx` and the return address.
+        let stack: Vec<u8> = [0u32, 0, 0, 0, 0x1234, 0x4321]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let memory = MemoryRegion {
+            base_addr: 0x8000,
+            contents: &stack,
+        };
+
+        let mut callee = BTreeMap::new();
+        callee.insert(var("$eip"), 0x2178u32);
+        callee.insert(var("$esp"), 0x8000);
+        callee.insert(var("$ebx"), 0);
+
+        assert_equivalent(&record, &rules, memory, callee);
+    }
+
+    #[test]
+    #[cfg(feature = "breakpad")]
+    fn fpo_record() {
+        let symbols = b"MODULE windows x86 5A9832E5287241C1838ED98914E9B7FF1 crash.pdb
+STACK WIN 0 2170 14 1 0 0 0 0 0 0 1
+";
+        let record = win_record(symbols);
+        let err = win_record_to_cfi_rules(&record).unwrap_err();
+        assert!(err.is_not_convertible());
+    }
+
+    #[test]
+    fn substitute_constants() {
+        let program = "$T0 $esp .cbSavedRegs + = $eip $T0 ^ = $esp $T0 4 + = $ebx $T0 4 - ^ =";
+        let mut constants = BTreeMap::new();
+        constants.insert(".cbSavedRegs".parse::<Constant>().unwrap(), 8u32);
+
+        let rules: Vec<_> = win_program_to_cfi_rules(program, &constants)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            rules,
+            [
+                ".cfa: $esp 8 + 4 +",
+                ".ra: $esp 8 + ^",
+                "$ebx: $esp 8 + 4 - ^"
+            ]
+        );
+    }
+
+    #[test]
+    fn not_convertible() {
+        let program = "$T0 .raSearch = $eip $T0 ^ = $esp $T0 4 + =";
+        let err = win_program_to_cfi_rules::<u32>(program, &BTreeMap::new()).unwrap_err();
+        assert!(err.is_not_convertible());
+
+        let program = "$T0 $ebp = $ebp $T0 ^ =";
+        let err = win_program_to_cfi_rules::<u32>(program, &BTreeMap::new()).unwrap_err();
+        assert!(err.is_not_convertible());
+
+        let err = win_program_to_cfi_rules::<u32>("$eip =", &BTreeMap::new()).unwrap_err();
+        assert!(!err.is_not_convertible());
+    }
+}
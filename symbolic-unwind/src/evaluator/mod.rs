@@ -39,20 +39,33 @@
 //! [rule](parsing::rule), [rule_complete](parsing::rule_complete),
 //! [rules](parsing::rules),
 //! and [rules_complete](parsing::rules_complete) parsers.
-use std::collections::BTreeMap;
+//!
+//! # Interning
+//!
+//! Symbol files with many records name the same few registers over and over. To avoid allocating
+//! a new string for every occurrence, pass a [`RegisterInterner`] to
+//! [rules_complete_interned](parsing::rules_complete_interned) or
+//! [assignments_complete_interned](parsing::assignments_complete_interned). Identifiers parsed
+//! with the same interner share their names.
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
 use std::fmt;
 use std::ops::Range;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use super::base::{Endianness, MemoryRegion, RegisterValue};
 use parsing::ParseExprError;
 
+pub mod conversion;
 pub mod parsing;
 
 #[cfg(test)]
 mod strategies;
 
+/// The default maximum number of expression nodes visited while evaluating a single expression.
+pub const DEFAULT_MAX_STEPS: u64 = 1_000_000;
+
 /// Structure that encapsulates the information necessary to evaluate Breakpad
 /// RPN expressions.
 ///
@@ -86,6 +99,10 @@ pub struct Evaluator<'memory, A, E> {
     /// The rule for the CFA pseudoregister. It has its own field because it needs to
     /// be evaluated before any other rules.
     cfa_rule: Option<Expr<A>>,
+
+    /// The maximum number of expression nodes that may be visited while evaluating
+    /// a single expression.
+    max_steps: u64,
 }
 
 impl<'memory, A, E> Evaluator<'memory, A, E> {
@@ -99,6 +116,7 @@ impl<'memory, A, E> Evaluator<'memory, A, E> {
             endian,
             cfi_rules: BTreeMap::new(),
             cfa_rule: None,
+            max_steps: DEFAULT_MAX_STEPS,
         }
     }
 
@@ -123,6 +141,16 @@ impl<'memory, A, E> Evaluator<'memory, A, E> {
         self
     }
 
+    /// Sets the maximum number of expression nodes that may be visited while evaluating
+    /// a single expression.
+    ///
+    /// Evaluation fails once this budget is exhausted. Defaults to [`DEFAULT_MAX_STEPS`].
+    #[must_use]
+    pub fn with_max_steps(mut self, max_steps: u64) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
     /// Adds a rule for computing a register's value in the caller's frame
     /// to the evaluator.
     pub fn add_cfi_rule(&mut self, ident: Identifier, expr: Expr<A>) {
@@ -133,14 +161,98 @@ impl<'memory, A, E> Evaluator<'memory, A, E> {
             }
         }
     }
+
+    /// Advances the evaluator to the caller's frame.
+    ///
+    /// This takes the register values computed for the caller, such as the result of
+    /// [`evaluate_cfi_rules`](Self::evaluate_cfi_rules), and replaces all constants and variables
+    /// with them. All CFI rules, including the CFA rule, are removed, since they only apply to the
+    /// frame they were added for. Memory, endianness and the step budget are kept.
+    pub fn apply_frame_result(&mut self, registers: BTreeMap<Identifier, A>) {
+        self.constants.clear();
+        self.variables.clear();
+        for (ident, value) in registers {
+            match ident {
+                Identifier::Const(c) => {
+                    self.constants.insert(c, value);
+                }
+                Identifier::Var(v) => {
+                    self.variables.insert(v, value);
+                }
+            }
+        }
+
+        self.cfi_rules.clear();
+        self.cfa_rule = None;
+    }
+}
+
+impl<'memory, A: Clone, E> Evaluator<'memory, A, E> {
+    /// Captures the evaluator's constants, variables and CFI rules.
+    ///
+    /// The memory, endianness and step budget are not part of the snapshot. Use
+    /// [`restore`](Self::restore) to roll the evaluator back to this state, for instance to try
+    /// another set of rules after evaluating CFI rules failed.
+    pub fn snapshot(&self) -> EvaluatorSnapshot<A> {
+        EvaluatorSnapshot {
+            constants: self.constants.clone(),
+            variables: self.variables.clone(),
+            cfi_rules: self.cfi_rules.clone(),
+            cfa_rule: self.cfa_rule.clone(),
+        }
+    }
+
+    /// Restores the constants, variables and CFI rules from a snapshot.
+    ///
+    /// This discards all rules added and all values computed since the snapshot was taken.
+    pub fn restore(&mut self, snapshot: EvaluatorSnapshot<A>) {
+        self.constants = snapshot.constants;
+        self.variables = snapshot.variables;
+        self.cfi_rules = snapshot.cfi_rules;
+        self.cfa_rule = snapshot.cfa_rule;
+    }
+}
+
+/// The state of an [`Evaluator`], created by [`Evaluator::snapshot`].
+#[derive(Clone, Debug)]
+pub struct EvaluatorSnapshot<A> {
+    constants: BTreeMap<Constant, A>,
+    variables: BTreeMap<Variable, A>,
+    cfi_rules: BTreeMap<Identifier, Expr<A>>,
+    cfa_rule: Option<Expr<A>>,
 }
 
 impl<'memory, A: RegisterValue, E: Endianness> Evaluator<'memory, A, E> {
     /// Evaluates a single expression.
     ///
-    /// This may fail if the expression tries to dereference unavailable memory
-    /// or uses undefined constants or variables.
+    /// This may fail if the expression tries to dereference unavailable memory,
+    /// uses undefined constants or variables, or exceeds the evaluator's step budget.
     pub fn evaluate(&self, expr: &Expr<A>) -> Result<A, EvaluationError<A>> {
+        let mut steps_taken = 0;
+        self.evaluate_inner(expr, &mut steps_taken)
+    }
+
+    /// Evaluates a list of independent expressions against the same registers and memory.
+    ///
+    /// Every expression is evaluated on its own with the full step budget, so an expression that
+    /// fails does not affect the others.
+    pub fn evaluate_expr_list(&self, exprs: &[Expr<A>]) -> Vec<Result<A, EvaluationError<A>>> {
+        exprs.iter().map(|expr| self.evaluate(expr)).collect()
+    }
+
+    /// Evaluates a single expression, counting every visited node in `steps_taken`.
+    fn evaluate_inner(
+        &self,
+        expr: &Expr<A>,
+        steps_taken: &mut u64,
+    ) -> Result<A, EvaluationError<A>> {
+        if *steps_taken >= self.max_steps {
+            return Err(EvaluationError(EvaluationErrorInner::BudgetExceeded {
+                steps: *steps_taken,
+            }));
+        }
+        *steps_taken += 1;
+
         match expr {
             Expr::Value(x) => Ok(*x),
             Expr::Const(c) => {
@@ -154,26 +266,18 @@ impl<'memory, A: RegisterValue, E: Endianness> Evaluator<'memory, A, E> {
                 })
             }
             Expr::Op(e1, e2, op) => {
-                let e1 = self.evaluate(&*e1)?;
-                let e2 = self.evaluate(&*e2)?;
-                let result = match op {
-                    BinOp::Add => e1.checked_add(&e2),
-                    BinOp::Sub => e1.checked_sub(&e2),
-                    BinOp::Mul => e1.checked_mul(&e2),
-                    BinOp::Div => e1.checked_div(&e2),
-                    BinOp::Mod => e1.checked_rem(&e2),
-                    BinOp::Align => e1.checked_div(&e2).and_then(|n| n.checked_mul(&e2)),
-                };
-
-                result.ok_or(EvaluationError(EvaluationErrorInner::IllegalOperation {
-                    left: e1,
-                    right: e2,
-                    op: *op,
-                }))
+                let e1 = self.evaluate_inner(e1, steps_taken)?;
+                let e2 = self.evaluate_inner(e2, steps_taken)?;
+                op.apply(&e1, &e2)
+                    .ok_or(EvaluationError(EvaluationErrorInner::IllegalOperation {
+                        left: e1,
+                        right: e2,
+                        op: *op,
+                    }))
             }
 
             Expr::Deref(address) => {
-                let address = self.evaluate(&*address)?;
+                let address = self.evaluate_inner(address, steps_taken)?;
                 let memory = self
                     .memory
                     .as_ref()
@@ -211,7 +315,57 @@ impl<'memory, A: RegisterValue, E: Endianness> Evaluator<'memory, A, E> {
         Ok(computed_registers)
     }
 
+    /// Evaluates the cfi rule for a single register and returns its value in the caller's frame.
+    ///
+    /// If the rule for the register refers to the CFA, the CFA rule is evaluated first and its
+    /// value is cached like in [`evaluate_cfi_rules`](Self::evaluate_cfi_rules). No other rules
+    /// are evaluated, so a failing CFA rule only affects registers that depend on it. Returns
+    /// `None` if there is no rule for the register.
+    pub fn evaluate_register(
+        &mut self,
+        ident: &Identifier,
+    ) -> Result<Option<A>, EvaluationError<A>> {
+        let is_cfa = matches!(ident, Identifier::Const(c) if c.is_cfa());
+        // The CFA rule is stored separately, so there is no rule for it in `cfi_rules`.
+        let rule = self.cfi_rules.get(ident);
+        if rule.is_none() && !is_cfa {
+            return Ok(None);
+        }
+
+        let mut cfa = None;
+        if is_cfa || matches!(rule, Some(expr) if expr.uses_cfa()) {
+            if let Some(ref expr) = self.cfa_rule {
+                let cfa_val = self.evaluate(expr)?;
+                self.constants.insert(Constant::cfa(), cfa_val);
+                cfa = Some(cfa_val);
+            }
+        }
+
+        match rule {
+            Some(expr) => self.evaluate(expr).map(Some),
+            None => Ok(cfa),
+        }
+    }
+
+    /// Evaluates the cfi rule for the register with the given name.
+    ///
+    /// This is a shorthand for parsing the name as an [`Identifier`] and calling
+    /// [`evaluate_register`](Self::evaluate_register). Unlike that function, it fails if there is
+    /// no rule for the register.
+    pub fn evaluate_register_by_name(&mut self, name: &str) -> Result<A, EvaluationError<A>> {
+        let ident = parsing::identifier_complete(name)
+            .map_err(|e| EvaluationError(EvaluationErrorInner::InvalidRegisterName(e)))?;
+
+        self.evaluate_register(&ident)?.ok_or(EvaluationError(
+            EvaluationErrorInner::NoRuleForRegister(ident),
+        ))
+    }
+
     /// Reads a string of CFI rules and adds them to the evaluator.
+    ///
+    /// The rules are only stored, not evaluated. Together with
+    /// [`evaluate_register`](Self::evaluate_register), this allows loading all rules that apply
+    /// at an address upfront and computing only the registers that are actually needed.
     pub fn add_cfi_rules_string(&mut self, rules_string: &str) -> Result<(), ParseExprError> {
         for Rule(lhs, rhs) in parsing::rules_complete(rules_string.trim())?.into_iter() {
             self.add_cfi_rule(lhs, rhs);
@@ -254,6 +408,18 @@ enum EvaluationErrorInner<A> {
         /// The binary operator.
         op: BinOp,
     },
+
+    /// Evaluation visited more expression nodes than the evaluator's step budget allows.
+    BudgetExceeded {
+        /// The number of steps taken before evaluation was aborted.
+        steps: u64,
+    },
+
+    /// The name of a register could not be parsed.
+    InvalidRegisterName(ParseExprError),
+
+    /// There is no cfi rule for the requested register.
+    NoRuleForRegister(Identifier),
 }
 
 impl<A: fmt::Display> fmt::Display for EvaluationErrorInner<A> {
@@ -270,6 +436,9 @@ impl<A: fmt::Display> fmt::Display for EvaluationErrorInner<A> {
             } => write!(f, "Tried to read {} bytes at address that exceeds the maximum usize value", bytes),
             Self::IllegalOperation {
                 left, right, op } => write!(f, "Illegal operation {} {} {}", left, op, right),
+            Self::BudgetExceeded { steps } => write!(f, "Evaluation exceeded the step budget after {} steps", steps),
+            Self::InvalidRegisterName(e) => write!(f, "Invalid register name: {}", e),
+            Self::NoRuleForRegister(ident) => write!(f, "There is no rule for register {}", ident),
         }
     }
 }
@@ -284,7 +453,14 @@ impl<A: fmt::Display> fmt::Display for EvaluationError<A> {
     }
 }
 
-impl<A: fmt::Debug + fmt::Display> Error for EvaluationError<A> {}
+impl<A: fmt::Debug + fmt::Display> Error for EvaluationError<A> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.0 {
+            EvaluationErrorInner::InvalidRegisterName(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
 /// An error encountered while parsing or evaluating an expression.
 #[derive(Debug)]
@@ -338,7 +514,7 @@ impl<A: fmt::Debug + fmt::Display + 'static> Error for ExpressionError<A> {
 
 /// A variable.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct Variable(String);
+pub struct Variable(pub(crate) Arc<str>);
 
 impl fmt::Display for Variable {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -356,27 +532,27 @@ impl FromStr for Variable {
 
 /// A constant value.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct Constant(String);
+pub struct Constant(pub(crate) Arc<str>);
 
 impl Constant {
     /// Returns true if this is the CFA (Canonical Frame Address) pseudoregister.
     pub fn is_cfa(&self) -> bool {
-        self.0 == ".cfa"
+        &*self.0 == ".cfa"
     }
 
     /// Returns the CFA (Canonical Frame Address) pseudoregister.
     pub fn cfa() -> Self {
-        Self(".cfa".to_string())
+        Self(".cfa".into())
     }
 
     /// Returns true if this is the RA (Return Address) pseudoregister.
     pub fn is_ra(&self) -> bool {
-        self.0 == ".ra"
+        &*self.0 == ".ra"
     }
 
     /// Returns the RA (Return Address) pseudoregister.
     pub fn ra() -> Self {
-        Self(".ra".to_string())
+        Self(".ra".into())
     }
 }
 
@@ -418,6 +594,22 @@ pub enum BinOp {
     Align,
 }
 
+impl BinOp {
+    /// Applies this operator to two values.
+    ///
+    /// Returns `None` if the operation overflows or divides by zero.
+    pub(crate) fn apply<A: RegisterValue>(&self, left: &A, right: &A) -> Option<A> {
+        match self {
+            Self::Add => left.checked_add(right),
+            Self::Sub => left.checked_sub(right),
+            Self::Mul => left.checked_mul(right),
+            Self::Div => left.checked_div(right),
+            Self::Mod => left.checked_rem(right),
+            Self::Align => left.checked_div(right).and_then(|n| n.checked_mul(right)),
+        }
+    }
+}
+
 impl fmt::Display for BinOp {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -452,6 +644,18 @@ pub enum Expr<T> {
     Deref(Box<Expr<T>>),
 }
 
+impl<T> Expr<T> {
+    /// Returns whether this expression refers to the CFA.
+    fn uses_cfa(&self) -> bool {
+        match self {
+            Self::Value(_) | Self::Var(_) => false,
+            Self::Const(c) => c.is_cfa(),
+            Self::Op(e1, e2, _) => e1.uses_cfa() || e2.uses_cfa(),
+            Self::Deref(e) => e.uses_cfa(),
+        }
+    }
+}
+
 impl<T: fmt::Display> fmt::Display for Expr<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -500,6 +704,24 @@ pub enum Identifier {
     Const(Constant),
 }
 
+impl Identifier {
+    /// Creates a variable from an interned name.
+    ///
+    /// The name is not checked against the variable syntax. Use [`RegisterInterner::intern`] to
+    /// obtain shared names.
+    pub fn var_interned(name: Arc<str>) -> Self {
+        Self::Var(Variable(name))
+    }
+
+    /// Creates a constant from an interned name.
+    ///
+    /// The name is not checked against the constant syntax. Use [`RegisterInterner::intern`] to
+    /// obtain shared names.
+    pub fn const_interned(name: Arc<str>) -> Self {
+        Self::Const(Constant(name))
+    }
+}
+
 impl fmt::Display for Identifier {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -509,6 +731,70 @@ impl fmt::Display for Identifier {
     }
 }
 
+/// A program string of either syntax used by Breakpad unwind information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgramString<A> {
+    /// Rules from a `STACK CFI` record, such as `.cfa: $rsp 8 +`.
+    CfiRules(Vec<Rule<A>>),
+
+    /// Assignments from a `STACK WIN` record, such as `$T0 $ebp 8 + =`.
+    WinAssignments(Vec<Assignment<A>>),
+}
+
+impl<T: fmt::Display> fmt::Display for ProgramString<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn join<I: fmt::Display>(f: &mut fmt::Formatter, items: &[I]) -> fmt::Result {
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", item)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            Self::CfiRules(rules) => join(f, rules),
+            Self::WinAssignments(assignments) => join(f, assignments),
+        }
+    }
+}
+
+impl<T: RegisterValue> FromStr for ProgramString<T> {
+    type Err = ParseExprError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parsing::parse_program_string(input)
+    }
+}
+
+/// A pool of register names shared between parsed identifiers.
+///
+/// Interning a name returns the same allocation for every occurrence, so that parsing many rules
+/// referring to the same registers does not allocate a string for each of them.
+#[derive(Clone, Debug, Default)]
+pub struct RegisterInterner {
+    pool: BTreeSet<Arc<str>>,
+}
+
+impl RegisterInterner {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared name equal to `name`, adding it to the pool if necessary.
+    pub fn intern(&mut self, name: &str) -> Arc<str> {
+        if let Some(interned) = self.pool.get(name) {
+            return interned.clone();
+        }
+
+        let interned: Arc<str> = Arc::from(name);
+        self.pool.insert(interned.clone());
+        interned
+    }
+}
+
 /// A `STACK CFI` rule `reg: e`, where `reg` is an identifier and `e` is an expression.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Rule<A>(Identifier, Expr<A>);
@@ -534,4 +820,148 @@ mod test {
         let eval = Evaluator::new(LittleEndian);
         assert!(eval.evaluate(&expr).is_err());
     }
+
+    #[test]
+    fn step_budget() {
+        // `1 1 + 1 + 1 + ...` with 10 additions has 21 nodes.
+        let expr = (0..10).fold(Expr::Value(1u32), |acc, _| {
+            Expr::Op(Box::new(acc), Box::new(Expr::Value(1)), BinOp::Add)
+        });
+
+        let eval = Evaluator::new(LittleEndian).with_max_steps(21);
+        assert_eq!(eval.evaluate(&expr).unwrap(), 11);
+
+        let eval = Evaluator::new(LittleEndian).with_max_steps(20);
+        let err = eval.evaluate(&expr).unwrap_err();
+        assert!(matches!(
+            err.0,
+            EvaluationErrorInner::BudgetExceeded { steps: 20 }
+        ));
+    }
+
+    #[test]
+    fn snapshot_restore() {
+        let mut variables = BTreeMap::new();
+        variables.insert("$rsp".parse::<Variable>().unwrap(), 0x1000u64);
+        let mut eval = Evaluator::new(LittleEndian).variables(variables);
+        let snapshot = eval.snapshot();
+
+        // `$rbx` is undefined, so evaluating these rules fails.
+        eval.add_cfi_rules_string(".cfa: $rsp 8 + $rbp: $rbx")
+            .unwrap();
+        assert!(eval.evaluate_cfi_rules().is_err());
+
+        eval.restore(snapshot);
+        eval.add_cfi_rules_string(".cfa: $rsp 16 +").unwrap();
+        let registers = eval.evaluate_cfi_rules().unwrap();
+
+        let mut expected = BTreeMap::new();
+        expected.insert(Identifier::Const(Constant::cfa()), 0x1010);
+        assert_eq!(registers, expected);
+    }
+
+    #[test]
+    fn apply_frame_result() {
+        let mut variables = BTreeMap::new();
+        variables.insert("$rsp".parse::<Variable>().unwrap(), 0x1000u64);
+        variables.insert("$rbx".parse::<Variable>().unwrap(), 0x1234u64);
+        let mut eval = Evaluator::new(LittleEndian).variables(variables);
+
+        eval.add_cfi_rules_string(".cfa: $rsp 16 + $rsp: .cfa")
+            .unwrap();
+        let registers = eval.evaluate_cfi_rules().unwrap();
+        eval.apply_frame_result(registers);
+
+        // The rules of the previous frame are gone and `$rbx` has no value in the caller.
+        assert!(eval.evaluate_cfi_rules().unwrap().is_empty());
+        let rsp = Expr::Var("$rsp".parse().unwrap());
+        assert_eq!(eval.evaluate(&rsp).unwrap(), 0x1010);
+        let cfa = Expr::Const(Constant::cfa());
+        assert_eq!(eval.evaluate(&cfa).unwrap(), 0x1010);
+        let rbx = Expr::Var("$rbx".parse().unwrap());
+        assert!(eval.evaluate(&rbx).is_err());
+
+        eval.add_cfi_rules_string(".cfa: $rsp 8 +").unwrap();
+        let registers = eval.evaluate_cfi_rules().unwrap();
+        assert_eq!(registers[&Identifier::Const(Constant::cfa())], 0x1018);
+    }
+
+    #[test]
+    fn evaluate_expr_list() {
+        let mut variables = BTreeMap::new();
+        variables.insert("$esp".parse::<Variable>().unwrap(), 0x1000u32);
+        let exprs: Vec<Expr<u32>> = ["$esp 8 +", "$ebp", "$esp 8 + 4 -"]
+            .iter()
+            .map(|expr| expr.parse().unwrap())
+            .collect();
+
+        let eval = Evaluator::new(LittleEndian).variables(variables);
+        let results = eval.evaluate_expr_list(&exprs);
+        assert_eq!(results.len(), exprs.len());
+        assert_eq!(results[0].as_ref().ok(), Some(&0x1008));
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().ok(), Some(&0x1004));
+    }
+
+    #[test]
+    fn evaluate_single_register() {
+        let mut variables = BTreeMap::new();
+        variables.insert("$rsp".parse::<Variable>().unwrap(), 0x1000u64);
+        let mut eval = Evaluator::new(LittleEndian).variables(variables);
+
+        // `$rbx` is undefined, but its rule is never evaluated.
+        eval.add_cfi_rules_string(".cfa: $rsp 16 + $rbp: .cfa 8 - $r12: $rbx")
+            .unwrap();
+
+        let rbp = Identifier::Var("$rbp".parse().unwrap());
+        assert_eq!(eval.evaluate_register(&rbp).unwrap(), Some(0x1008));
+        let cfa = Identifier::Const(Constant::cfa());
+        assert_eq!(eval.evaluate_register(&cfa).unwrap(), Some(0x1010));
+        let rip = Identifier::Var("$rip".parse().unwrap());
+        assert_eq!(eval.evaluate_register(&rip).unwrap(), None);
+        let r12 = Identifier::Var("$r12".parse().unwrap());
+        assert!(eval.evaluate_register(&r12).is_err());
+    }
+
+    #[test]
+    fn evaluate_register_failing_cfa() {
+        let mut variables = BTreeMap::new();
+        variables.insert("$rbx".parse::<Variable>().unwrap(), 0x2000u64);
+        let mut eval = Evaluator::new(LittleEndian).variables(variables);
+
+        // `$rsp` is undefined, so only registers referring to the CFA fail.
+        eval.add_cfi_rules_string(".cfa: $rsp 16 + $rbp: .cfa 8 - $r12: $rbx 8 +")
+            .unwrap();
+
+        let r12 = Identifier::Var("$r12".parse().unwrap());
+        assert_eq!(eval.evaluate_register(&r12).unwrap(), Some(0x2008));
+        let rbp = Identifier::Var("$rbp".parse().unwrap());
+        assert!(eval.evaluate_register(&rbp).is_err());
+        let cfa = Identifier::Const(Constant::cfa());
+        assert!(eval.evaluate_register(&cfa).is_err());
+        let rip = Identifier::Var("$rip".parse().unwrap());
+        assert_eq!(eval.evaluate_register(&rip).unwrap(), None);
+    }
+
+    #[test]
+    fn evaluate_register_by_name() {
+        let mut variables = BTreeMap::new();
+        variables.insert("$rsp".parse::<Variable>().unwrap(), 0x1000u64);
+        let mut eval = Evaluator::new(LittleEndian).variables(variables);
+        eval.add_cfi_rules_string(".cfa: $rsp 16 + $rbp: .cfa 8 -")
+            .unwrap();
+
+        assert_eq!(eval.evaluate_register_by_name("$rbp").unwrap(), 0x1008);
+        assert_eq!(eval.evaluate_register_by_name(".cfa").unwrap(), 0x1010);
+        assert!(matches!(
+            eval.evaluate_register_by_name("$rip"),
+            Err(EvaluationError(EvaluationErrorInner::NoRuleForRegister(_)))
+        ));
+        assert!(matches!(
+            eval.evaluate_register_by_name("$r b"),
+            Err(EvaluationError(EvaluationErrorInner::InvalidRegisterName(
+                _
+            )))
+        ));
+    }
 }
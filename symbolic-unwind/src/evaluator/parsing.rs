@@ -8,7 +8,7 @@ use std::fmt;
 use nom::branch::alt;
 use nom::bytes::complete::{tag, take_while};
 use nom::character::complete::{alpha1, alphanumeric0, alphanumeric1, multispace0};
-use nom::combinator::{all_consuming, map, map_res, not, opt, peek, recognize, value};
+use nom::combinator::{all_consuming, map_res, not, opt, peek, recognize, value};
 use nom::error::ParseError;
 use nom::sequence::{pair, preceded, terminated, tuple};
 use nom::{Err, Finish, IResult, Parser};
@@ -84,41 +84,61 @@ impl Error for ParseExprError {}
 ///
 /// If the child parser doesn't consume any input, you're going to have a bad time.
 fn space_separated<'a, O, P>(
+    mut input: &'a str,
     mut parser: P,
-) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>, ParseExprError>
+) -> IResult<&'a str, Vec<O>, ParseExprError>
 where
-    P: 'a + Parser<&'a str, O, ParseExprError>,
+    P: Parser<&'a str, O, ParseExprError>,
 {
-    move |mut input| {
-        let mut result = Vec::new();
-        match parser.parse(input) {
-            Ok((rest, item)) => {
-                input = rest;
-                result.push(item);
-            }
-            Err(_) => return Ok((input, result)),
+    let mut result = Vec::new();
+    match parser.parse(input) {
+        Ok((rest, item)) => {
+            input = rest;
+            result.push(item);
         }
+        Err(_) => return Ok((input, result)),
+    }
 
-        loop {
-            let rest = multispace0(input)?.0;
-            if let Ok((rest, item)) = parser.parse(rest) {
-                input = rest;
-                result.push(item);
-            } else {
-                break;
-            }
+    loop {
+        let rest = multispace0(input)?.0;
+        if let Ok((rest, item)) = parser.parse(rest) {
+            input = rest;
+            result.push(item);
+        } else {
+            break;
         }
+    }
 
-        Ok((input, result))
+    Ok((input, result))
+}
+
+/// Returns the name from the interner if there is one, and allocates it otherwise.
+fn intern(name: &str, interner: Option<&mut RegisterInterner>) -> Arc<str> {
+    match interner {
+        Some(interner) => interner.intern(name),
+        None => Arc::from(name),
     }
 }
 
+/// Recognizes the name of a [variable](super::Variable).
+fn variable_name(input: &str) -> IResult<&str, &str, ParseExprError> {
+    recognize(tuple((tag("$"), alphanumeric1)))(input)
+}
+
+/// Recognizes the name of a [constant](super::Constant).
+fn constant_name(input: &str) -> IResult<&str, &str, ParseExprError> {
+    recognize(tuple((opt(tag(".")), alpha1, alphanumeric0)))(input)
+}
+
 /// Parses a [variable](super::Variable).
 ///
 /// This accepts identifiers of the form `$[a-zA-Z0-9]+`.
-fn variable(input: &str) -> IResult<&str, Variable, ParseExprError> {
-    let (rest, var) = recognize(tuple((tag("$"), alphanumeric1)))(input)?;
-    Ok((rest, Variable(var.to_string())))
+fn variable<'a>(
+    input: &'a str,
+    interner: Option<&mut RegisterInterner>,
+) -> IResult<&'a str, Variable, ParseExprError> {
+    let (rest, var) = variable_name(input)?;
+    Ok((rest, Variable(intern(var, interner))))
 }
 
 /// Parses a [variable](super::Variable).
@@ -126,15 +146,20 @@ fn variable(input: &str) -> IResult<&str, Variable, ParseExprError> {
 /// This accepts identifiers of the form `$[a-zA-Z0-9]+`.
 /// It will fail if there is any input remaining afterwards.
 pub fn variable_complete(input: &str) -> Result<Variable, ParseExprError> {
-    all_consuming(variable)(input).finish().map(|(_, v)| v)
+    all_consuming(|i| variable(i, None))(input)
+        .finish()
+        .map(|(_, v)| v)
 }
 
 /// Parses a [constant](super::Constant).
 ///
 /// This accepts identifiers of the form `\.?[a-zA-Z][a-zA-Z0-9]*`.
-fn constant(input: &str) -> IResult<&str, Constant, ParseExprError> {
-    let (rest, con) = recognize(tuple((opt(tag(".")), alpha1, alphanumeric0)))(input)?;
-    Ok((rest, Constant(con.to_string())))
+fn constant<'a>(
+    input: &'a str,
+    interner: Option<&mut RegisterInterner>,
+) -> IResult<&'a str, Constant, ParseExprError> {
+    let (rest, con) = constant_name(input)?;
+    Ok((rest, Constant(intern(con, interner))))
 }
 
 /// Parses a [constant](super::Constant).
@@ -142,15 +167,27 @@ fn constant(input: &str) -> IResult<&str, Constant, ParseExprError> {
 /// This accepts identifiers of the form `\.[a-zA-Z0-9]+`.
 /// It will fail if there is any input remaining afterwards.
 pub fn constant_complete(input: &str) -> Result<Constant, ParseExprError> {
-    all_consuming(constant)(input).finish().map(|(_, c)| c)
+    all_consuming(|i| constant(i, None))(input)
+        .finish()
+        .map(|(_, c)| c)
 }
 
 /// Parses an [identifier](super::Identifier).
 pub fn identifier(input: &str) -> IResult<&str, Identifier, ParseExprError> {
-    alt((
-        map(variable, Identifier::Var),
-        map(constant, Identifier::Const),
-    ))(input)
+    identifier_with(input, None)
+}
+
+fn identifier_with<'a>(
+    input: &'a str,
+    interner: Option<&mut RegisterInterner>,
+) -> IResult<&'a str, Identifier, ParseExprError> {
+    match variable_name(input) {
+        Ok((rest, name)) => Ok((rest, Identifier::var_interned(intern(name, interner)))),
+        Err(_) => {
+            let (rest, name) = constant_name(input)?;
+            Ok((rest, Identifier::const_interned(intern(name, interner))))
+        }
+    }
 }
 
 /// Parses an [identifier](super::Identifier).
@@ -183,12 +220,20 @@ fn number<T: RegisterValue>(input: &str) -> IResult<&str, T, ParseExprError> {
 ///
 /// Variables or constants followed by ":" don't count; that's the start
 /// of a [rule](super::Rule), not an expression.
-fn base_expr<T: RegisterValue>(input: &str) -> IResult<&str, Expr<T>, ParseExprError> {
-    alt((
-        map(number, Expr::Value),
-        map(terminated(variable, peek(not(tag(":")))), Expr::Var),
-        map(terminated(constant, peek(not(tag(":")))), Expr::Const),
-    ))(input)
+fn base_expr<'a, T: RegisterValue>(
+    input: &'a str,
+    interner: Option<&mut RegisterInterner>,
+) -> IResult<&'a str, Expr<T>, ParseExprError> {
+    if let Ok((rest, value)) = number(input) {
+        return Ok((rest, Expr::Value(value)));
+    }
+
+    if let Ok((rest, name)) = terminated(variable_name, peek(not(tag(":"))))(input) {
+        return Ok((rest, Expr::Var(Variable(intern(name, interner)))));
+    }
+
+    let (rest, name) = terminated(constant_name, peek(not(tag(":"))))(input)?;
+    Ok((rest, Expr::Const(Constant(intern(name, interner)))))
 }
 
 /// Parses an [expression](super::Expr).
@@ -208,11 +253,18 @@ fn base_expr<T: RegisterValue>(input: &str) -> IResult<&str, Expr<T>, ParseExprE
 /// assert_eq!(expr("1 -2").unwrap(), (" -2", e1));
 /// assert_eq!(expr("1 -2 + 3").unwrap(), (" 3", e2));
 /// ```
-pub fn expr<T: RegisterValue>(mut input: &str) -> IResult<&str, Expr<T>, ParseExprError> {
+pub fn expr<T: RegisterValue>(input: &str) -> IResult<&str, Expr<T>, ParseExprError> {
+    expr_with(input, None)
+}
+
+fn expr_with<'a, T: RegisterValue>(
+    mut input: &'a str,
+    mut interner: Option<&mut RegisterInterner>,
+) -> IResult<&'a str, Expr<T>, ParseExprError> {
     let mut stack = Vec::new();
 
     // Parse an initial expression. If this fails, we are done.
-    let (rest, (sign, e)) = pair(opt(tag("-")), base_expr)(input)?;
+    let (rest, (sign, e)) = pair(opt(tag("-")), |i| base_expr(i, interner.as_deref_mut()))(input)?;
     stack.push((e.clone(), sign.is_some()));
 
     // Invariant: saved_expr is the largest whole expressions we parsed so far, saved_sign
@@ -226,7 +278,9 @@ pub fn expr<T: RegisterValue>(mut input: &str) -> IResult<&str, Expr<T>, ParseEx
         input = multispace0(input)?.0;
 
         // Try to parse a constant, variable, or number.
-        if let Ok((rest, (sign, e))) = pair(opt(tag("-")), base_expr)(input) {
+        if let Ok((rest, (sign, e))) =
+            pair(opt(tag("-")), |i| base_expr(i, interner.as_deref_mut()))(input)
+        {
             stack.push((e, sign.is_some()));
             input = rest;
             if stack.len() == 1 {
@@ -334,8 +388,16 @@ pub fn expr_complete<T: RegisterValue>(input: &str) -> Result<Expr<T>, ParseExpr
 
 /// Parses an [assignment](super::Assignment).
 pub fn assignment<T: RegisterValue>(input: &str) -> IResult<&str, Assignment<T>, ParseExprError> {
-    let (rest, (v, _, e, _, _)) =
-        tuple((variable, multispace0, expr, multispace0, tag("=")))(input)?;
+    assignment_with(input, None)
+}
+
+fn assignment_with<'a, T: RegisterValue>(
+    input: &'a str,
+    mut interner: Option<&mut RegisterInterner>,
+) -> IResult<&'a str, Assignment<T>, ParseExprError> {
+    let (input, v) = terminated(|i| variable(i, interner.as_deref_mut()), multispace0)(input)?;
+    let (rest, e) = terminated(|i| expr_with(i, interner.as_deref_mut()), multispace0)(input)?;
+    let (rest, _) = tag("=")(rest)?;
     Ok((rest, Assignment(v, e)))
 }
 
@@ -350,7 +412,7 @@ pub fn assignment_complete<T: RegisterValue>(input: &str) -> Result<Assignment<T
 pub fn assignments<'a, T: 'a + RegisterValue>(
     input: &'a str,
 ) -> IResult<&'a str, Vec<Assignment<T>>, ParseExprError> {
-    space_separated(assignment)(input)
+    space_separated(input, assignment)
 }
 
 /// Parses a sequence of [assignments](super::Assignment).
@@ -362,10 +424,31 @@ pub fn assignments_complete<T: RegisterValue>(
     all_consuming(assignments)(input).finish().map(|(_, a)| a)
 }
 
+/// Parses a sequence of [assignments](super::Assignment), sharing register names through the
+/// interner.
+///
+/// It will fail if there is any input remaining afterwards.
+pub fn assignments_complete_interned<T: RegisterValue>(
+    input: &str,
+    interner: &mut RegisterInterner,
+) -> Result<Vec<Assignment<T>>, ParseExprError> {
+    all_consuming(|i| space_separated(i, |i| assignment_with(i, Some(&mut *interner))))(input)
+        .finish()
+        .map(|(_, a)| a)
+}
+
 ///Parses a [rule](super::Rule).
 pub fn rule<T: RegisterValue>(input: &str) -> IResult<&str, Rule<T>, ParseExprError> {
-    let (input, ident) = terminated(identifier, tag(":"))(input)?;
-    let (rest, expr) = preceded(multispace0, expr)(input)?;
+    rule_with(input, None)
+}
+
+fn rule_with<'a, T: RegisterValue>(
+    input: &'a str,
+    mut interner: Option<&mut RegisterInterner>,
+) -> IResult<&'a str, Rule<T>, ParseExprError> {
+    let (input, ident) =
+        terminated(|i| identifier_with(i, interner.as_deref_mut()), tag(":"))(input)?;
+    let (rest, expr) = preceded(multispace0, |i| expr_with(i, interner.as_deref_mut()))(input)?;
 
     Ok((rest, Rule(ident, expr)))
 }
@@ -381,7 +464,7 @@ pub fn rule_complete<T: RegisterValue>(input: &str) -> Result<Rule<T>, ParseExpr
 pub fn rules<'a, T: 'a + RegisterValue>(
     input: &'a str,
 ) -> IResult<&'a str, Vec<Rule<T>>, ParseExprError> {
-    space_separated(rule)(input)
+    space_separated(input, rule)
 }
 
 /// Parses a sequence of [rules](super::Rule).
@@ -391,6 +474,36 @@ pub fn rules_complete<T: RegisterValue>(input: &str) -> Result<Vec<Rule<T>>, Par
     all_consuming(rules)(input).finish().map(|(_, a)| a)
 }
 
+/// Parses a sequence of [rules](super::Rule), sharing register names through the interner.
+///
+/// It will fail if there is any input remaining afterwards.
+pub fn rules_complete_interned<T: RegisterValue>(
+    input: &str,
+    interner: &mut RegisterInterner,
+) -> Result<Vec<Rule<T>>, ParseExprError> {
+    all_consuming(|i| space_separated(i, |i| rule_with(i, Some(&mut *interner))))(input)
+        .finish()
+        .map(|(_, a)| a)
+}
+
+/// Parses a [program string](super::ProgramString) of either rules or assignments.
+///
+/// The input is parsed as `STACK CFI` rules if its first token ends with a colon, such as
+/// `.cfa:`, and as `STACK WIN` assignments otherwise.
+/// It will fail if there is any input remaining afterwards.
+pub fn parse_program_string<T: RegisterValue>(
+    input: &str,
+) -> Result<ProgramString<T>, ParseExprError> {
+    let input = input.trim();
+    let is_rule = matches!(input.split_whitespace().next(), Some(token) if token.ends_with(':'));
+
+    if is_rule {
+        rules_complete(input).map(ProgramString::CfiRules)
+    } else {
+        assignments_complete(input).map(ProgramString::WinAssignments)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -424,11 +537,56 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_program_string() {
+        let input = ".cfa: $rsp 8 + .ra: .cfa -8 + ^";
+        let parsed = parse_program_string::<u64>(input).unwrap();
+        assert_eq!(
+            parsed,
+            ProgramString::CfiRules(rules_complete(input).unwrap())
+        );
+        assert_eq!(parsed.to_string(), ".cfa: $rsp 8 + .ra: .cfa 8 - ^");
+
+        let input = "$T0 $ebp 8 + = $eip $T0 ^ =";
+        let parsed = parse_program_string::<u32>(input).unwrap();
+        assert_eq!(
+            parsed,
+            ProgramString::WinAssignments(assignments_complete(input).unwrap())
+        );
+        assert_eq!(parsed.to_string(), input);
+
+        assert!(parse_program_string::<u32>(".cfa: $esp 4 + $T0 $ebp =").is_err());
+    }
+
+    #[test]
+    fn test_rules_interned() {
+        let mut interner = RegisterInterner::new();
+        let input = ".cfa: $rsp 8 + .ra: .cfa -8 + ^ $rsp: .cfa";
+        let parsed = rules_complete_interned::<u64>(input, &mut interner).unwrap();
+        assert_eq!(parsed, rules_complete::<u64>(input).unwrap());
+
+        let again = rules_complete_interned::<u64>(".cfa: $rsp 16 +", &mut interner).unwrap();
+        match (&parsed[0], &again[0], &parsed[2]) {
+            (
+                Rule(Identifier::Const(first), Expr::Op(var, _, _)),
+                Rule(Identifier::Const(second), _),
+                Rule(Identifier::Var(target), _),
+            ) => {
+                assert!(Arc::ptr_eq(&first.0, &second.0));
+                match **var {
+                    Expr::Var(ref var) => assert!(Arc::ptr_eq(&var.0, &target.0)),
+                    _ => panic!("expected a variable"),
+                }
+            }
+            _ => panic!("unexpected rules"),
+        }
+    }
+
     #[test]
     fn test_var() {
         let input = "$foo bar";
-        let v = Variable(String::from("$foo"));
-        let (rest, parsed) = variable(input).unwrap();
+        let v = Variable("$foo".into());
+        let (rest, parsed) = variable(input, None).unwrap();
         assert_eq!(rest, " bar");
         assert_eq!(parsed, v);
     }
@@ -555,7 +713,7 @@ mod test {
     fn rule_lhs() {
         let input = "$foo: ";
 
-        base_expr::<u8>(input).unwrap_err();
+        base_expr::<u8>(input, None).unwrap_err();
     }
 
     #[test]
@@ -608,6 +766,42 @@ mod test {
         rules_complete::<u64>(input).unwrap();
     }
 
+    /// Checks whether the lightweight rule validation in `symbolic-debuginfo` accepts the rules.
+    fn breakpad_accepts(rules: &str) -> bool {
+        symbolic_debuginfo::breakpad::BreakpadStackCfiRecord::from_rules(0, 1, rules).is_ok()
+    }
+
+    #[test]
+    fn test_rules_match_breakpad_validation() {
+        let valid = [
+            ".cfa: $rsp 8 +",
+            ".cfa: sp 80 + x29: .cfa -80 + ^ .ra: .cfa -72 + ^",
+            ".cfa: $esp 4 + $ebp: .cfa 8 - ^ .ra: .cfa 4 - ^",
+            ".cfa: $rsp 16 @ 8 * 2 / 3 %",
+        ];
+        for rules in valid {
+            assert!(rules_complete::<u64>(rules).is_ok(), "{}", rules);
+            assert!(breakpad_accepts(rules), "{}", rules);
+        }
+
+        let invalid = [
+            "$rsp 8 +",
+            ".cfa:",
+            ".cfa: $rsp +",
+            ".cfa: $rsp 8",
+            ".cfa: -8",
+            ".cfa: -8 $rsp +",
+            ".cfa: $rsp 8 -8 * +",
+            ".cfa: -$rsp ^",
+            ".cfa: $ 8 +",
+            ".cfa: 8a",
+        ];
+        for rules in invalid {
+            assert!(rules_complete::<u64>(rules).is_err(), "{}", rules);
+            assert!(!breakpad_accepts(rules), "{}", rules);
+        }
+    }
+
     proptest! {
         #[test]
         fn proptest_constant(c in strategies::arb_constant()) {
@@ -625,5 +819,19 @@ mod test {
             let rules_string = rules.iter().map(|r| r.to_string()).collect::<Vec<_>>().join(" ");
             assert_eq!(rules_complete(&rules_string).unwrap(), rules);
         }
+
+        #[test]
+        fn proptest_rules_match_breakpad_validation(
+            tokens in prop::collection::vec(
+                prop::sample::select(vec![
+                    ".cfa:", ".ra:", "$rbp:", "$rsp", ".cfa", "x29", "$", "8", "-8", "16", "+",
+                    "-", "*", "@", "^",
+                ]),
+                1..12,
+            )
+        ) {
+            let rules = tokens.join(" ");
+            prop_assert_eq!(breakpad_accepts(&rules), rules_complete::<u64>(&rules).is_ok(), "{}", rules);
+        }
     }
 }
@@ -1,6 +1,51 @@
 //! Stack unwinding functionality for `symbolic`.
+//!
+//! # Unwinding with Breakpad symbols
+//!
+//! The unwind information of a Breakpad symbol file can be looked up with
+//! `BreakpadObject::unwind_session` from `symbolic-debuginfo`, which resolves the CFI rules that
+//! apply at an instruction address. These rules can be evaluated directly to compute the caller's
+//! registers:
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use symbolic_debuginfo::breakpad::BreakpadObject;
+//! use symbolic_unwind::evaluator::{Constant, Evaluator, Identifier, Variable};
+//! use symbolic_unwind::{LittleEndian, MemoryRegion};
+//!
+//! let symbols = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash
+//! STACK CFI INIT 1000 20 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+//! STACK CFI 1004 .cfa: $rsp 16 +
+//! ";
+//! let object = BreakpadObject::parse(symbols).unwrap();
+//! let session = object.unwind_session().unwrap();
+//! let rules = session.cfi_rules_for_address(0x1008).unwrap();
+//!
+//! // The return address is stored right below the CFA.
+//! let stack = 0x2000u64.to_le_bytes();
+//! let memory = MemoryRegion {
+//!     base_addr: 0x7008,
+//!     contents: &stack,
+//! };
+//!
+//! let mut variables = BTreeMap::new();
+//! variables.insert("$rsp".parse::<Variable>().unwrap(), 0x7000u64);
+//!
+//! let mut evaluator = Evaluator::new(LittleEndian)
+//!     .memory(memory)
+//!     .variables(variables);
+//! evaluator.add_cfi_rules_string(&rules).unwrap();
+//! let caller = evaluator.evaluate_cfi_rules().unwrap();
+//!
+//! assert_eq!(caller[&Identifier::Const(Constant::cfa())], 0x7010);
+//! assert_eq!(caller[&Identifier::Const(Constant::ra())], 0x2000);
+//! ```
 #![warn(missing_docs)]
 pub use base::*;
 
+pub mod arm64;
 mod base;
 pub mod evaluator;
+pub mod riscv;
+pub mod x86_64;
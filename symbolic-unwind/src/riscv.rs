@@ -0,0 +1,114 @@
+//! Register conventions specific to RISC-V.
+//!
+//! The RISC-V calling convention passes the return address in `x1` (`ra`) and keeps the stack
+//! pointer in `x2` (`sp`). The registers `x8`-`x9` (`s0`-`s1`) and `x18`-`x27` (`s2`-`s11`) are
+//! callee-saved, so their values in the caller can be recovered by unwinding. All other registers
+//! are clobbered by calls.
+use super::evaluator::Constant;
+
+/// Maps DWARF register numbers of the integer registers to their ABI names.
+///
+/// The DWARF register number of `xN` is `N`, so the table is indexed by register number.
+pub const RISCV64_DWARF_REGS: [(u16, &str); 32] = [
+    (0, "zero"),
+    (1, "ra"),
+    (2, "sp"),
+    (3, "gp"),
+    (4, "tp"),
+    (5, "t0"),
+    (6, "t1"),
+    (7, "t2"),
+    (8, "s0"),
+    (9, "s1"),
+    (10, "a0"),
+    (11, "a1"),
+    (12, "a2"),
+    (13, "a3"),
+    (14, "a4"),
+    (15, "a5"),
+    (16, "a6"),
+    (17, "a7"),
+    (18, "s2"),
+    (19, "s3"),
+    (20, "s4"),
+    (21, "s5"),
+    (22, "s6"),
+    (23, "s7"),
+    (24, "s8"),
+    (25, "s9"),
+    (26, "s10"),
+    (27, "s11"),
+    (28, "t3"),
+    (29, "t4"),
+    (30, "t5"),
+    (31, "t6"),
+];
+
+/// Returns the DWARF register number of the register with the given name.
+///
+/// Both the architectural names `x0`-`x31` and the ABI names are accepted. The frame pointer
+/// alias `fp` refers to `s0`.
+fn register_number(name: &str) -> Option<u16> {
+    if let Some(number) = name.strip_prefix('x') {
+        // Reject leading zeros and signs, which `parse` would accept.
+        if number.starts_with(|c: char| c.is_ascii_digit())
+            && (number == "0" || !number.starts_with('0'))
+        {
+            return number.parse().ok().filter(|&number| number < 32);
+        }
+    }
+
+    let name = if name == "fp" { "s0" } else { name };
+    RISCV64_DWARF_REGS
+        .iter()
+        .find(|(_, abi_name)| *abi_name == name)
+        .map(|(number, _)| *number)
+}
+
+/// Returns true if the register is preserved across calls.
+///
+/// These are the registers `x8`-`x9` (`s0`-`s1`) and `x18`-`x27` (`s2`-`s11`). Registers may be
+/// given by their architectural or ABI names.
+pub fn is_callee_saved(reg: &Constant) -> bool {
+    matches!(register_number(&reg.0), Some(8..=9) | Some(18..=27))
+}
+
+/// Returns the register holding the return address, `ra` (`x1`).
+pub fn return_address_register() -> Constant {
+    Constant("ra".into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn register(name: &str) -> Constant {
+        Constant(name.into())
+    }
+
+    #[test]
+    fn dwarf_register_table() {
+        for (index, (number, _)) in RISCV64_DWARF_REGS.iter().enumerate() {
+            assert_eq!(usize::from(*number), index);
+        }
+
+        assert_eq!(register_number("x1"), Some(1));
+        assert_eq!(register_number("ra"), Some(1));
+        assert_eq!(register_number("fp"), Some(8));
+        assert_eq!(register_number("x32"), None);
+        assert_eq!(register_number("x01"), None);
+    }
+
+    #[test]
+    fn callee_saved_registers() {
+        for name in ["s0", "fp", "x9", "s2", "x27", "s11"] {
+            assert!(is_callee_saved(&register(name)), "{}", name);
+        }
+
+        for name in ["ra", "sp", "x1", "a0", "t0", "x28", ".cfa"] {
+            assert!(!is_callee_saved(&register(name)), "{}", name);
+        }
+
+        assert!(!is_callee_saved(&return_address_register()));
+    }
+}
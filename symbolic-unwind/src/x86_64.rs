@@ -0,0 +1,61 @@
+//! Callee-saved registers of the x86-64 calling conventions.
+//!
+//! If a `STACK CFI` record has no rule for a register, unwinders usually carry the callee's value
+//! over to the caller. This is only correct for callee-saved registers, and x86-64 has two
+//! conventions that disagree on them: the System V ABI on Linux and macOS preserves `$rbx`, `$rbp`
+//! and `$r12`-`$r15`, while the Windows x64 ABI additionally preserves `$rdi`, `$rsi` and
+//! `$xmm6`-`$xmm15`. Use [`is_callee_saved_sysv`] or [`is_callee_saved_win64`], depending on the
+//! platform of the module, before carrying a register value over.
+//!
+//! Registers are named as in Breakpad symbol files, with a `$` prefix.
+use super::evaluator::Variable;
+
+/// The callee-saved registers of the System V x86-64 ABI.
+pub const SYSV_CALLEE_SAVED: &[&str] = &["$rbx", "$rbp", "$r12", "$r13", "$r14", "$r15"];
+
+/// The callee-saved registers of the Windows x64 ABI.
+pub const WIN64_CALLEE_SAVED: &[&str] = &[
+    "$rbx", "$rbp", "$rdi", "$rsi", "$r12", "$r13", "$r14", "$r15", "$xmm6", "$xmm7", "$xmm8",
+    "$xmm9", "$xmm10", "$xmm11", "$xmm12", "$xmm13", "$xmm14", "$xmm15",
+];
+
+/// Returns true if the register is preserved across calls in the System V x86-64 ABI.
+pub fn is_callee_saved_sysv(reg: &Variable) -> bool {
+    SYSV_CALLEE_SAVED.contains(&&*reg.0)
+}
+
+/// Returns true if the register is preserved across calls in the Windows x64 ABI.
+pub fn is_callee_saved_win64(reg: &Variable) -> bool {
+    WIN64_CALLEE_SAVED.contains(&&*reg.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn register(name: &str) -> Variable {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn sysv_callee_saved_registers() {
+        for name in SYSV_CALLEE_SAVED {
+            assert!(is_callee_saved_sysv(&register(name)), "{}", name);
+        }
+
+        for name in ["$rax", "$rdi", "$rsi", "$rsp", "$rip", "$xmm6"] {
+            assert!(!is_callee_saved_sysv(&register(name)), "{}", name);
+        }
+    }
+
+    #[test]
+    fn win64_callee_saved_registers() {
+        for name in ["$rbx", "$rdi", "$rsi", "$r15", "$xmm6", "$xmm15"] {
+            assert!(is_callee_saved_win64(&register(name)), "{}", name);
+        }
+
+        for name in ["$rax", "$rcx", "$r8", "$r11", "$xmm5", "$rip"] {
+            assert!(!is_callee_saved_win64(&register(name)), "{}", name);
+        }
+    }
+}